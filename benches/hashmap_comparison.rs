@@ -1,5 +1,5 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use deferred_map::DeferredMap;
+use deferred_map::{DeferredMap, Key};
 use rustc_hash::FxHashMap;
 use std::hint::black_box;
 
@@ -88,6 +88,15 @@ fn bench_get(c: &mut Criterion) {
             });
         });
 
+        let raw_keys: Vec<u64> = keys.iter().map(|k| k.raw()).collect();
+        group.bench_with_input(BenchmarkId::new("DeferredMap/raw", size), size, |b, _| {
+            b.iter(|| {
+                for &raw in &raw_keys {
+                    black_box(deferred_map.get_raw(raw));
+                }
+            });
+        });
+
         // 准备 FxHashMap
         let mut fx_map = FxHashMap::default();
         for i in 0..*size {
@@ -134,6 +143,15 @@ fn bench_random_get(c: &mut Criterion) {
             });
         });
 
+        let raw_keys: Vec<u64> = keys.iter().map(|k| k.raw()).collect();
+        group.bench_with_input(BenchmarkId::new("DeferredMap/raw", size), size, |b, _| {
+            b.iter(|| {
+                for &idx in &query_indices {
+                    black_box(deferred_map.get_raw(raw_keys[idx]));
+                }
+            });
+        });
+
         // 准备 FxHashMap
         let mut fx_map = FxHashMap::default();
         for i in 0..*size {
@@ -152,6 +170,48 @@ fn bench_random_get(c: &mut Criterion) {
     group.finish();
 }
 
+/// 测试批量查询（带预取）与逐个查询在大型稀疏 map 上的性能
+fn bench_get_many(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_many");
+
+    for size in [10_000, 1_000_000].iter() {
+        // 准备稀疏的 DeferredMap：删除 90% 的元素
+        let mut deferred_map = DeferredMap::<usize>::new();
+        let mut keys = Vec::with_capacity(*size);
+        for i in 0..*size {
+            let handle = deferred_map.allocate_handle();
+            keys.push(handle.key());
+            deferred_map.insert(handle, i);
+        }
+        for (i, &key) in keys.iter().enumerate() {
+            if i % 10 != 0 {
+                deferred_map.remove(key);
+            }
+        }
+
+        // 随机查询序列（使用简单的伪随机）
+        let queries: Vec<_> = (0..*size).map(|i| keys[(i * 7919) % size]).collect();
+
+        group.bench_with_input(BenchmarkId::new("get", size), size, |b, _| {
+            b.iter(|| {
+                for &key in &queries {
+                    black_box(deferred_map.get(key));
+                }
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("get_many", size), size, |b, _| {
+            let mut out = Vec::with_capacity(queries.len());
+            b.iter(|| {
+                deferred_map.get_many(&queries, &mut out);
+                black_box(&out);
+            });
+        });
+    }
+
+    group.finish();
+}
+
 /// 测试删除操作性能
 fn bench_remove(c: &mut Criterion) {
     let mut group = c.benchmark_group("remove");
@@ -522,9 +582,9 @@ fn bench_sparse(c: &mut Criterion) {
                 }
 
                 // 删除 90% 的元素，只保留 10%
-                for i in 0..size {
+                for (i, &key) in keys.iter().enumerate() {
                     if i % 10 != 0 {
-                        map.remove(keys[i]);
+                        map.remove(key);
                     }
                 }
 
@@ -568,6 +628,58 @@ fn bench_sparse(c: &mut Criterion) {
     group.finish();
 }
 
+/// 测试稀疏 map 的纯迭代性能（只保留 10% 的元素）
+fn bench_sparse_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sparse_iter");
+
+    for size in [100, 1000, 10000].iter() {
+        // 准备 DeferredMap，删除 90% 的元素
+        let mut deferred_map = DeferredMap::<usize>::new();
+        let mut keys = Vec::new();
+        for i in 0..*size {
+            let handle = deferred_map.allocate_handle();
+            keys.push(handle.key());
+            deferred_map.insert(handle, i);
+        }
+        for (i, &key) in keys.iter().enumerate() {
+            if i % 10 != 0 {
+                deferred_map.remove(key);
+            }
+        }
+
+        group.bench_with_input(BenchmarkId::new("DeferredMap", size), size, |b, _| {
+            b.iter(|| {
+                for (key, value) in deferred_map.iter() {
+                    black_box(key);
+                    black_box(value);
+                }
+            });
+        });
+
+        // 准备 FxHashMap
+        let mut fx_map = FxHashMap::default();
+        for i in 0..*size {
+            fx_map.insert(i, i);
+        }
+        for i in 0..*size {
+            if i % 10 != 0 {
+                fx_map.remove(&i);
+            }
+        }
+
+        group.bench_with_input(BenchmarkId::new("FxHashMap", size), size, |b, _| {
+            b.iter(|| {
+                for (key, value) in &fx_map {
+                    black_box(key);
+                    black_box(value);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
 /// 测试缓存友好性（顺序访问）
 fn bench_sequential_access(c: &mut Criterion) {
     let mut group = c.benchmark_group("sequential_access");
@@ -623,6 +735,7 @@ criterion_group!(
     bench_preallocated_insert,
     bench_get,
     bench_random_get,
+    bench_get_many,
     bench_remove,
     bench_iter,
     bench_mixed_operations,
@@ -630,6 +743,7 @@ criterion_group!(
     bench_deferred_insertion,
     bench_clone,
     bench_sparse,
+    bench_sparse_iter,
     bench_sequential_access,
 );
 
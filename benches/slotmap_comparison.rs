@@ -21,6 +21,18 @@ fn bench_insert(c: &mut Criterion) {
             });
         });
 
+        group.bench_with_input(
+            BenchmarkId::new("DeferredMap/seed", size),
+            size,
+            |b, &size| {
+                b.iter(|| {
+                    let mut map = DeferredMap::<usize>::new();
+                    map.seed((0..size).map(black_box));
+                    map
+                });
+            },
+        );
+
         group.bench_with_input(BenchmarkId::new("SlotMap", size), size, |b, &size| {
             b.iter(|| {
                 let mut map = SlotMap::new();
@@ -441,6 +453,47 @@ fn bench_clone(c: &mut Criterion) {
     group.finish();
 }
 
+/// 测试 clone 与 clone_from 在经过 churn 的 map 上的差异
+fn bench_clone_from(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone_from");
+
+    for size in [100, 1000, 10000].iter() {
+        // 准备经过 churn 的源 map：删除每第三个元素
+        let mut source = DeferredMap::<String>::new();
+        let mut keys = Vec::with_capacity(*size);
+        for i in 0..*size {
+            let handle = source.allocate_handle();
+            keys.push(handle.key());
+            source.insert(handle, i.to_string());
+        }
+        for key in keys.iter().step_by(3) {
+            source.remove(*key);
+        }
+
+        group.bench_with_input(BenchmarkId::new("DeferredMap_clone", size), size, |b, _| {
+            let mut target = source.clone();
+            b.iter(|| {
+                target = source.clone();
+                black_box(&target);
+            });
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("DeferredMap_clone_from", size),
+            size,
+            |b, _| {
+                let mut target = source.clone();
+                b.iter(|| {
+                    target.clone_from(&source);
+                    black_box(&target);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_insert,
@@ -452,6 +505,7 @@ criterion_group!(
     bench_churn,
     bench_deferred_insertion,
     bench_clone,
+    bench_clone_from,
 );
 
 criterion_main!(benches);
@@ -0,0 +1,242 @@
+use crate::handle::Handle;
+use crate::map::DeferredMap;
+use crate::slot::{Slot, SlotUnion};
+use crate::utils::likely;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Lock-free handle allocator that lets several threads allocate handles at once
+///
+/// The allocator exclusively borrows a `DeferredMap`, pops vacant slots off the
+/// map's free list with an atomic head (a pop-only Treiber stack over the
+/// `next_free` links), and hands out fresh indices past the end of the slot
+/// vector with an atomic counter. When the allocator is dropped, every handed-out
+/// slot is committed to the Reserved state, after which the handles can be
+/// inserted as usual.
+///
+/// 无锁 handle 分配器，允许多个线程同时分配 handle
+///
+/// 分配器独占借用一个 `DeferredMap`，通过原子头指针从空闲列表中弹出空闲 slot
+/// （基于 `next_free` 链接、只出栈的 Treiber 栈），并通过原子计数器分配 slot
+/// 向量末尾之后的新索引。分配器被 drop 时，所有已分配的 slot 都会被提交为
+/// Reserved 状态，之后即可像往常一样使用 handle 插入。
+///
+/// # Memory Ordering (内存序)
+///
+/// The map is exclusively borrowed for the allocator's lifetime, so the
+/// `next_free` links are immutable while threads race on the head. Since the
+/// stack is pop-only, an index can never reappear at the head and the CAS is
+/// free of ABA problems. The head is loaded with `Acquire` and swapped with
+/// `AcqRel`; the fresh-index counter only needs uniqueness and uses `Relaxed`.
+/// Joining the worker threads (e.g. leaving `std::thread::scope`) is what
+/// publishes their allocations before the allocator is dropped.
+///
+/// 分配器存活期间 map 被独占借用，因此线程竞争头指针时 `next_free` 链接不可变。
+/// 由于栈只出不进，同一索引不会再次出现在栈顶，CAS 不存在 ABA 问题。
+/// 头指针使用 `Acquire` 读取、`AcqRel` 交换；新索引计数器只需唯一性，使用 `Relaxed`。
+/// 在分配器被 drop 之前，需要 join 工作线程（例如离开 `std::thread::scope`）
+/// 以发布它们的分配结果。
+///
+/// # Synchronization (同步)
+///
+/// Only allocation is concurrent. `insert`, `release_handle` and every other
+/// map operation still require `&mut DeferredMap`, which is only available again
+/// once the allocator has been dropped.
+///
+/// 只有分配是并发的。`insert`、`release_handle` 以及其他所有 map 操作仍需要
+/// `&mut DeferredMap`，只有在分配器被 drop 后才能再次获得。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::DeferredMap;
+///
+/// let mut map = DeferredMap::new();
+///
+/// let handles: Vec<_> = {
+///     let allocator = map.concurrent_allocator();
+///     std::thread::scope(|s| {
+///         let workers: Vec<_> = (0..4)
+///             .map(|_| s.spawn(|| (0..10).map(|_| allocator.allocate_handle()).collect::<Vec<_>>()))
+///             .collect();
+///         workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+///     })
+/// };
+///
+/// for (i, handle) in handles.into_iter().enumerate() {
+///     map.insert(handle, i);
+/// }
+/// assert_eq!(map.len(), 40);
+/// ```
+pub struct ConcurrentAllocator<'a, T, K: crate::Key = crate::DefaultKey> {
+    map: &'a mut DeferredMap<T, K>,
+    start_head: u32,       // free_head when created | 创建时的 free_head
+    head: AtomicU32,       // Current free list head | 当前空闲列表头
+    next_fresh: AtomicU32, // Next index past the end | 末尾之后的下一个索引
+}
+
+impl<T, K: crate::Key> DeferredMap<T, K> {
+    /// Create a ConcurrentAllocator for allocating handles from several threads
+    ///
+    /// 创建一个 ConcurrentAllocator，用于从多个线程分配 handle
+    #[inline]
+    pub fn concurrent_allocator(&mut self) -> ConcurrentAllocator<'_, T, K> {
+        let start_head = self.free_head;
+        let len = self.slots.len() as u32;
+        ConcurrentAllocator {
+            map: self,
+            start_head,
+            head: AtomicU32::new(start_head),
+            next_fresh: AtomicU32::new(len),
+        }
+    }
+}
+
+impl<T, K: crate::Key> ConcurrentAllocator<'_, T, K> {
+    /// Allocate a Handle through a shared reference
+    ///
+    /// Vacant slots from the free list are reused first; once the free list is
+    /// exhausted, fresh indices past the end of the map are handed out.
+    ///
+    /// 通过共享引用分配 Handle
+    ///
+    /// 优先复用空闲列表中的 slot；空闲列表耗尽后，分配 map 末尾之后的新索引。
+    ///
+    /// # Panics
+    /// Panics if the index space (`u32::MAX - 1` slots) is exhausted.
+    ///
+    /// 如果索引空间（`u32::MAX - 1` 个 slot）耗尽则 panic。
+    pub fn allocate_handle(&self) -> Handle<K> {
+        let slots = &self.map.slots;
+        let mut head = self.head.load(Ordering::Acquire);
+
+        // Pop from the free list while it is not exhausted; the chain ends at slots.len()
+        // 空闲列表未耗尽时从中弹出；链表终止于 slots.len()
+        while likely((head as usize) < slots.len()) {
+            let slot = &slots[head as usize];
+            debug_assert!(
+                slot.is_vacant(),
+                "Free list corrupted: slot {} is not vacant",
+                head
+            );
+
+            // SAFETY: slots on the free list are vacant, so next_free is the active field
+            let next = unsafe { slot.u.next_free };
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => {
+                    let key = K::from_parts(
+                        head,
+                        slot.generation(),
+                        #[cfg(debug_assertions)]
+                        self.map.map_id,
+                    );
+                    return Handle::new(key);
+                }
+                Err(current) => head = current,
+            }
+        }
+
+        // Free list exhausted (it never refills while the allocator lives)
+        // 空闲列表已耗尽（分配器存活期间不会再补充）
+        // Refuse u32::MAX without advancing the counter, so it can never wrap around
+        // to the sentinel
+        // 拒绝 u32::MAX 且不推进计数器，使其永远不会回绕到 sentinel
+        let Ok(index) =
+            self.next_fresh
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |next| {
+                    next.checked_add(1)
+                })
+        else {
+            panic!("DeferredMap index space exhausted");
+        };
+        let key = K::from_parts(
+            index,
+            self.map.fresh_generation,
+            #[cfg(debug_assertions)]
+            self.map.map_id,
+        );
+        Handle::new(key)
+    }
+}
+
+impl<T, K: crate::Key> Drop for ConcurrentAllocator<'_, T, K> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let next_fresh = *self.next_fresh.get_mut();
+        let slots = &mut self.map.slots;
+
+        // Commit popped free-list slots: they form the chain start_head -> ... -> head
+        // 提交弹出的空闲 slot：它们构成 start_head -> ... -> head 的链
+        let mut committed = 0;
+        let mut index = self.start_head;
+        while index != head {
+            let slot = &mut slots[index as usize];
+            // SAFETY: the slot is still vacant, so next_free is the active field
+            index = unsafe { slot.u.next_free };
+            slot.version.vacant_to_reserved();
+            committed += 1;
+        }
+
+        // Materialize fresh indices as reserved slots of the fresh generation
+        // 将新索引实例化为新建代数的 reserved slot
+        let len = slots.len() as u32;
+        if next_fresh > len {
+            slots.reserve((next_fresh - len) as usize);
+            for _ in len..next_fresh {
+                slots.push(Slot {
+                    u: SlotUnion { next_free: 0 },
+                    version: crate::Version::new(self.map.fresh_generation, 0b01),
+                });
+            }
+            committed += next_fresh - len;
+            // Fresh indices are only handed out once the free list is exhausted
+            // 只有空闲列表耗尽后才会分配新索引
+            self.map.free_head = next_fresh;
+        } else {
+            self.map.free_head = head;
+        }
+        self.map.num_reserved += committed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_handle_at_index_limit() {
+        // Materializing u32::MAX slots would take tens of GiB, so start the fresh
+        // counter just below the limit instead
+        // 实例化 u32::MAX 个 slot 需要数十 GiB 内存，因此改为让新索引计数器从上限之下开始
+        let mut map = DeferredMap::<u32>::new();
+        let allocator = map.concurrent_allocator();
+        allocator.next_fresh.store(u32::MAX - 1, Ordering::Relaxed);
+
+        let last = allocator.allocate_handle();
+        assert_eq!(last.index(), u32::MAX - 1);
+        let _ = last.into_raw();
+
+        // Every later call keeps refusing instead of wrapping to the sentinel
+        // 之后的每次调用都继续拒绝，而不是回绕到 sentinel
+        for _ in 0..2 {
+            let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                allocator.allocate_handle();
+            }))
+            .unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<&str>(),
+                Some(&"DeferredMap index space exhausted")
+            );
+            assert_eq!(allocator.next_fresh.load(Ordering::Relaxed), u32::MAX);
+        }
+
+        // Roll back so Drop does not materialize the fake fresh slots
+        // 回滚，避免 Drop 实例化这些伪造的新 slot
+        allocator.next_fresh.store(1, Ordering::Relaxed);
+        drop(allocator);
+        assert_eq!(map.reserved_len(), 0);
+        map.validate().unwrap();
+    }
+}
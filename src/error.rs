@@ -0,0 +1,125 @@
+use std::fmt;
+
+/// Errors returned by the fallible (`try_*`) operations of DeferredMap
+///
+/// DeferredMap 可失败操作（`try_*`）返回的错误
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DeferredMapError {
+    /// The handle points at the sentinel or outside the map
+    ///
+    /// handle 指向 sentinel 或超出 map 范围
+    InvalidHandle,
+
+    /// The slot's generation differs from the handle's generation
+    ///
+    /// slot 的 generation 与 handle 的 generation 不一致
+    GenerationMismatch,
+
+    /// Index and generation match, but the slot is not in Reserved state
+    /// (the handle was already inserted or released)
+    ///
+    /// index 和 generation 匹配，但 slot 不处于 Reserved 状态
+    /// （handle 已被插入或释放）
+    NotReserved,
+
+    /// Two keys passed to a disjoint lookup point at the same slot
+    ///
+    /// 传给不相交查找的两个 key 指向同一个 slot
+    Aliased,
+
+    /// A raw key encodes a zero generation, which no key ever has
+    ///
+    /// 原始 key 编码的代数为零，任何 key 都不会有这样的代数
+    ZeroGeneration,
+
+    /// A raw key encodes a generation above `Generation::MAX`, which no slot can hold
+    ///
+    /// 原始 key 编码的代数超过 `Generation::MAX`，任何 slot 都无法持有
+    GenerationOutOfRange,
+}
+
+impl fmt::Display for DeferredMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::InvalidHandle => "invalid handle: sentinel or out-of-bounds index",
+            Self::GenerationMismatch => "generation mismatch",
+            Self::NotReserved => "slot is not reserved: handle already used or released",
+            Self::Aliased => "keys alias the same slot",
+            Self::ZeroGeneration => "raw key has a zero generation",
+            Self::GenerationOutOfRange => "raw key has a generation above Generation::MAX",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for DeferredMapError {}
+
+/// Convert into an `io::Error` of kind `InvalidInput`, keeping the `Display` message
+///
+/// This lets `?` propagate a `DeferredMapError` from functions returning `io::Result`.
+///
+/// 转换为 `InvalidInput` 类型的 `io::Error`，并保留 `Display` 消息
+///
+/// 这使得在返回 `io::Result` 的函数中可以用 `?` 传播 `DeferredMapError`。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::DeferredMap;
+/// use std::io;
+///
+/// fn store(map: &mut DeferredMap<i32>) -> io::Result<()> {
+///     let handle = map.allocate_handle();
+///     map.try_insert(handle, 1)?;
+///     Ok(())
+/// }
+///
+/// let mut map = DeferredMap::new();
+/// store(&mut map).unwrap();
+/// assert_eq!(map.len(), 1);
+/// ```
+impl From<DeferredMapError> for std::io::Error {
+    fn from(err: DeferredMapError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Why a lookup through [`DeferredMap::get_or_reason`](crate::DeferredMap::get_or_reason) failed
+///
+/// A plain `Copy` enum the caller keeps around and passes by `&mut`, so reporting
+/// the reason costs a single store and never allocates.
+///
+/// 通过 [`DeferredMap::get_or_reason`](crate::DeferredMap::get_or_reason) 查找失败的原因
+///
+/// 一个普通的 `Copy` 枚举，由调用方持有并以 `&mut` 传入，因此报告原因只需一次写入，
+/// 永远不会分配内存。
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GetFailure {
+    /// The key's index is outside the map
+    ///
+    /// key 的索引超出 map 范围
+    OutOfBounds,
+
+    /// The slot has moved on to a different generation
+    ///
+    /// slot 已经进入不同的代数
+    StaleGeneration,
+
+    /// Index and generation match, but the slot holds no value
+    /// (it is reserved by a Handle that has not been inserted yet)
+    ///
+    /// index 和 generation 匹配，但 slot 不持有值
+    /// （被一个尚未插入的 Handle 预留）
+    NotOccupied,
+}
+
+impl fmt::Display for GetFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::OutOfBounds => "key index out of bounds",
+            Self::StaleGeneration => "stale key generation",
+            Self::NotOccupied => "slot is not occupied",
+        };
+        f.write_str(msg)
+    }
+}
@@ -0,0 +1,258 @@
+use crate::handle::Handle;
+use crate::map::DeferredMap;
+
+/// DeferredMap with eight per-entry flag bits
+///
+/// Flags live in a `Vec<u8>` parallel to the slot array and are indexed by slot
+/// index, so reading or writing them costs one byte access and no hashing. Flags
+/// start cleared on insert and are reset on remove, so a recycled slot never
+/// inherits the flags of its previous occupant.
+///
+/// 带有每条目 8 个标志位的 DeferredMap
+///
+/// 标志存储在与 slot 数组平行的 `Vec<u8>` 中，并按 slot 索引寻址，
+/// 因此读写标志只需一次字节访问，无需哈希。插入时标志为清除状态，删除时被重置，
+/// 因此复用的 slot 永远不会继承前一个占用者的标志。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::FlaggedDeferredMap;
+///
+/// const DIRTY: u8 = 0;
+///
+/// let mut map = FlaggedDeferredMap::new();
+/// let handle = map.allocate_handle();
+/// let key = handle.key();
+/// map.insert(handle, "texture");
+///
+/// assert!(map.set_flag(key, DIRTY));
+/// assert_eq!(map.get_flag(key, DIRTY), Some(true));
+///
+/// map.remove(key);
+/// assert_eq!(map.get_flag(key, DIRTY), None);
+/// ```
+pub struct FlaggedDeferredMap<T, K: crate::Key = crate::DefaultKey> {
+    map: DeferredMap<T, K>,
+    flags: Vec<u8>, // Indexed by slot index | 按 slot 索引寻址
+}
+
+impl<T> FlaggedDeferredMap<T, crate::DefaultKey> {
+    /// Create a new empty FlaggedDeferredMap
+    ///
+    /// 创建一个新的空 FlaggedDeferredMap
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<T, K: crate::Key> Default for FlaggedDeferredMap<T, K> {
+    #[inline]
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<T, K: crate::Key> FlaggedDeferredMap<T, K> {
+    /// Create a FlaggedDeferredMap with specified capacity
+    ///
+    /// 创建一个指定容量的 FlaggedDeferredMap
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: DeferredMap::with_capacity_and_key(capacity),
+            flags: Vec::with_capacity(capacity + 1), // +1 for sentinel | +1 用于 sentinel
+        }
+    }
+
+    /// Pre-allocate a Handle, see [`DeferredMap::allocate_handle`]
+    ///
+    /// 预分配 Handle，参见 [`DeferredMap::allocate_handle`]
+    #[inline]
+    pub fn allocate_handle(&mut self) -> Handle<K> {
+        self.map.allocate_handle()
+    }
+
+    /// Release an unused Handle, see [`DeferredMap::release_handle`]
+    ///
+    /// 释放未使用的 Handle，参见 [`DeferredMap::release_handle`]
+    #[inline]
+    pub fn release_handle(&mut self, handle: Handle<K>) {
+        self.map.release_handle(handle);
+    }
+
+    /// Insert value using Handle, with all flags cleared, returning its key
+    ///
+    /// 使用 Handle 插入值，所有标志均为清除状态，并返回其 key
+    pub fn insert(&mut self, handle: Handle<K>, value: T) -> K {
+        let index = handle.index() as usize;
+        let key = self.map.insert(handle, value);
+
+        if index >= self.flags.len() {
+            self.flags.resize(index + 1, 0);
+        }
+        self.flags[index] = 0;
+        key
+    }
+
+    /// Remove value by key, resetting its flags
+    ///
+    /// 通过 key 删除值，并重置其标志
+    pub fn remove(&mut self, key: K) -> Option<T> {
+        let value = self.map.remove(key)?;
+        self.flags[key.index() as usize] = 0;
+        Some(value)
+    }
+
+    /// Get immutable reference to value by key
+    ///
+    /// 通过 key 获取值的不可变引用
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Get mutable reference to value by key
+    ///
+    /// 通过 key 获取值的可变引用
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        self.map.get_mut(key)
+    }
+
+    /// Check if key exists
+    ///
+    /// 检查 key 是否存在
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Set flag `bit` on the entry at `key`
+    ///
+    /// Returns `false` if `key` is not live.
+    ///
+    /// 设置 `key` 处条目的标志位 `bit`
+    ///
+    /// 如果 `key` 不存活则返回 `false`。
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit >= 8`.
+    ///
+    /// 如果 `bit >= 8` 则 panic。
+    #[inline]
+    pub fn set_flag(&mut self, key: K, bit: u8) -> bool {
+        let mask = Self::mask(bit);
+        match self.flags_mut(key) {
+            Some(flags) => {
+                *flags |= mask;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clear flag `bit` on the entry at `key`
+    ///
+    /// Returns `false` if `key` is not live.
+    ///
+    /// 清除 `key` 处条目的标志位 `bit`
+    ///
+    /// 如果 `key` 不存活则返回 `false`。
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit >= 8`.
+    ///
+    /// 如果 `bit >= 8` 则 panic。
+    #[inline]
+    pub fn clear_flag(&mut self, key: K, bit: u8) -> bool {
+        let mask = Self::mask(bit);
+        match self.flags_mut(key) {
+            Some(flags) => {
+                *flags &= !mask;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Read flag `bit` of the entry at `key`
+    ///
+    /// Returns `None` if `key` is not live.
+    ///
+    /// 读取 `key` 处条目的标志位 `bit`
+    ///
+    /// 如果 `key` 不存活则返回 `None`。
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit >= 8`.
+    ///
+    /// 如果 `bit >= 8` 则 panic。
+    #[inline]
+    pub fn get_flag(&self, key: K, bit: u8) -> Option<bool> {
+        let mask = Self::mask(bit);
+        Some(self.flags(key)? & mask != 0)
+    }
+
+    /// Get all eight flag bits of the entry at `key`
+    ///
+    /// 获取 `key` 处条目的全部 8 个标志位
+    #[inline]
+    pub fn flags(&self, key: K) -> Option<u8> {
+        if !self.map.contains_key(key) {
+            return None;
+        }
+        Some(self.flags[key.index() as usize])
+    }
+
+    /// Return the number of valid elements
+    ///
+    /// 返回有效元素的数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check if the map is empty
+    ///
+    /// 检查 map 是否为空
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove all entries and their flags
+    ///
+    /// 清空所有条目及其标志
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.flags.clear();
+    }
+
+    /// Get a shared reference to the underlying DeferredMap
+    ///
+    /// 获取底层 DeferredMap 的共享引用
+    #[inline]
+    pub fn as_map(&self) -> &DeferredMap<T, K> {
+        &self.map
+    }
+
+    #[inline]
+    fn flags_mut(&mut self, key: K) -> Option<&mut u8> {
+        if !self.map.contains_key(key) {
+            return None;
+        }
+        Some(&mut self.flags[key.index() as usize])
+    }
+
+    #[inline]
+    fn mask(bit: u8) -> u8 {
+        assert!(bit < 8, "flag bit {bit} out of range (0..8)");
+        1 << bit
+    }
+}
@@ -1,12 +1,63 @@
+#[cfg(debug_assertions)]
+thread_local! {
+    static DROPPED_UNUSED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Return how many Handles were dropped unused on the current thread
+///
+/// A Handle dropped without being inserted, released or turned into a raw value
+/// leaves its slot Reserved forever. Debug builds count such drops per thread, so
+/// tests and services can detect leaks; release builds track nothing and always
+/// return 0. See also `DeferredMap::assert_no_leaked_handles`.
+///
+/// 返回当前线程上未使用就被 drop 的 Handle 数量
+///
+/// 未插入、未释放也未转换为原始值就被 drop 的 Handle 会使其 slot 永远处于 Reserved 状态。
+/// debug 构建会按线程统计此类 drop，便于测试和服务检测泄漏；release 构建不做任何统计，
+/// 始终返回 0。另请参见 `DeferredMap::assert_no_leaked_handles`。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::{DeferredMap, dropped_unused_handles};
+///
+/// let mut map = DeferredMap::<i32>::new();
+/// let before = dropped_unused_handles();
+///
+/// drop(map.allocate_handle());
+/// // 1 in debug builds, 0 in release builds
+/// // debug 构建中为 1，release 构建中为 0
+/// assert!(dropped_unused_handles() - before <= 1);
+/// assert_eq!(map.reserved_len(), 1);
+/// ```
+#[inline]
+pub fn dropped_unused_handles() -> usize {
+    #[cfg(debug_assertions)]
+    {
+        DROPPED_UNUSED.with(|count| count.get())
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        0
+    }
+}
+
 /// Handle is a one-time token for inserting values into DeferredMap
 ///
 /// Handle does not implement the Clone trait, ensuring it can only be used once
 /// through Rust's move semantics.
 ///
+/// In debug builds, dropping a Handle instead of passing it to `insert`,
+/// `release_handle` (or their `try_` forms) or `into_raw` is counted as a leak;
+/// see [`dropped_unused_handles`].
+///
 /// Handle 是一次性令牌，用于向 DeferredMap 插入值
 ///
 /// Handle 不实现 Clone trait，通过 Rust 的 move semantics 确保只能使用一次
 ///
+/// 在 debug 构建中，没有传给 `insert`、`release_handle`（或其 `try_` 形式）或 `into_raw`
+/// 就被 drop 的 Handle 会被计为泄漏；参见 [`dropped_unused_handles`]。
+///
 /// # Examples (示例)
 ///
 /// ```
@@ -45,6 +96,72 @@ impl<K: crate::Key> Handle<K> {
         self.key
     }
 
+    /// Consume the Handle and return the raw representation of its key
+    ///
+    /// Intended for passing the token across FFI boundaries; turn it back into a
+    /// Handle with `from_raw`.
+    ///
+    /// 消耗 Handle 并返回其 key 的原始表示
+    ///
+    /// 用于跨 FFI 边界传递令牌；可以通过 `from_raw` 转换回 Handle。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Handle};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    ///
+    /// let raw: u64 = handle.into_raw();
+    /// // SAFETY: `raw` came from `into_raw` and is turned back into a Handle only once
+    /// let handle: Handle = unsafe { Handle::from_raw(raw) };
+    /// map.insert(handle, 42);
+    /// assert_eq!(map.get(key), Some(&42));
+    /// ```
+    #[inline(always)]
+    pub fn into_raw(self) -> K::Raw {
+        self.into_key().raw()
+    }
+
+    /// Consume the Handle as used, without counting it as dropped unused
+    ///
+    /// 将 Handle 作为已使用消耗掉，不计为未使用就被 drop
+    #[inline(always)]
+    pub(crate) fn into_key(self) -> K {
+        let this = std::mem::ManuallyDrop::new(self);
+        this.key
+    }
+
+    /// Rebuild a Handle from the raw value produced by `into_raw`
+    ///
+    /// In debug builds the Handle is not bound to any map instance, so the
+    /// wrong-map assertion accepts it for every map.
+    ///
+    /// 从 `into_raw` 产生的原始值重建 Handle
+    ///
+    /// 在 debug 构建中，该 Handle 不绑定任何 map 实例，因此错误 map 断言对所有 map 都会接受它。
+    ///
+    /// # Safety
+    /// This bypasses the move-once guarantee of Handle. `raw` must come from
+    /// `into_raw`, and the caller must turn each raw value back into a Handle at
+    /// most once and only use it with the map that allocated it.
+    ///
+    /// 这会绕过 Handle 的一次性使用保证。`raw` 必须来自 `into_raw`，
+    /// 调用者必须保证每个原始值最多只被转换回 Handle 一次，且只用于分配它的 map。
+    #[inline(always)]
+    pub unsafe fn from_raw(raw: K::Raw) -> Self {
+        let key = unsafe {
+            K::from_raw(
+                raw,
+                #[cfg(debug_assertions)]
+                crate::utils::UNBOUND_MAP_ID,
+            )
+        };
+        Self { key }
+    }
+
     /// Extract index (lower 32 bits)
     ///
     /// 提取 index（低 32 位）
@@ -54,9 +171,25 @@ impl<K: crate::Key> Handle<K> {
     }
     /// Extract generation (upper 32 bits)
     ///
+    /// Returns the logical `Generation`, the same value the slot reports; it
+    /// compares directly with `u32`, and `as_u32` gives the plain number.
+    ///
     /// 提取 generation（高 32 位）
+    ///
+    /// 返回逻辑 `Generation`，与 slot 报告的值相同；它可以直接与 `u32` 比较，
+    /// `as_u32` 则给出普通数值。
     #[inline(always)]
     pub fn generation(&self) -> crate::Generation {
         self.key.generation()
     }
 }
+
+#[cfg(debug_assertions)]
+impl<K: crate::Key> Drop for Handle<K> {
+    fn drop(&mut self) {
+        // Every consuming path wraps the Handle in ManuallyDrop, so reaching here
+        // means it leaked
+        // 所有消耗 Handle 的路径都会用 ManuallyDrop 包装它，因此执行到这里说明它泄漏了
+        DROPPED_UNUSED.with(|count| count.set(count.get() + 1));
+    }
+}
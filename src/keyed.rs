@@ -0,0 +1,225 @@
+use crate::map::DeferredMap;
+use crate::secondary::SecondaryMap;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// DeferredMap addressed by a user-supplied key
+///
+/// Combines a `DeferredMap<T, K>` with a `HashMap<UserK, K>` forward index and a
+/// `SecondaryMap<UserK, K>` reverse index, so values can be looked up either by
+/// user key or by map key, and removing through either side keeps both indices
+/// consistent.
+///
+/// 通过用户提供的键寻址的 DeferredMap
+///
+/// 组合了 `DeferredMap<T, K>`、`HashMap<UserK, K>` 正向索引和
+/// `SecondaryMap<UserK, K>` 反向索引，因此既可以通过用户键也可以通过 map 键查找值，
+/// 并且从任一方向删除都会保持两个索引一致。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::KeyedMap;
+///
+/// let mut users = KeyedMap::new();
+///
+/// *users.get_or_allocate("alice", || 0) += 1;
+/// *users.get_or_allocate("alice", || 0) += 1;
+///
+/// assert_eq!(users.get("alice"), Some(&2));
+/// assert_eq!(users.remove("alice"), Some(2));
+/// assert!(users.is_empty());
+/// ```
+pub struct KeyedMap<UserK, T, K: crate::Key = crate::DefaultKey> {
+    map: DeferredMap<T, K>,
+    index: HashMap<UserK, K>,       // User key -> map key | 用户键 -> map 键
+    owners: SecondaryMap<UserK, K>, // Map key -> user key | map 键 -> 用户键
+}
+
+impl<UserK: Hash + Eq + Clone, T> KeyedMap<UserK, T, crate::DefaultKey> {
+    /// Create a new empty KeyedMap
+    ///
+    /// 创建一个新的空 KeyedMap
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<UserK: Hash + Eq + Clone, T, K: crate::Key> Default for KeyedMap<UserK, T, K> {
+    #[inline]
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<UserK: Hash + Eq + Clone, T, K: crate::Key> KeyedMap<UserK, T, K> {
+    /// Create a KeyedMap with specified capacity
+    ///
+    /// 创建一个指定容量的 KeyedMap
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: DeferredMap::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+            owners: SecondaryMap::with_capacity(capacity),
+        }
+    }
+
+    /// Get the value mapped to `user_key`, allocating and inserting `f()` if absent
+    ///
+    /// `f` is only called when `user_key` is not mapped yet.
+    ///
+    /// 获取 `user_key` 映射的值，不存在时分配并插入 `f()`
+    ///
+    /// 只有 `user_key` 尚未映射时才会调用 `f`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::KeyedMap;
+    ///
+    /// let mut map = KeyedMap::new();
+    /// map.get_or_allocate(7u32, Vec::new).push("a");
+    /// map.get_or_allocate(7u32, Vec::new).push("b");
+    /// assert_eq!(map.get(&7), Some(&vec!["a", "b"]));
+    /// ```
+    pub fn get_or_allocate<F>(&mut self, user_key: UserK, f: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        if let Some(&key) = self.index.get(&user_key) {
+            return self
+                .map
+                .get_mut(key)
+                .expect("KeyedMap index points at a missing entry");
+        }
+
+        let value = f();
+        let handle = self.map.allocate_handle();
+        let key = handle.key();
+        self.index.insert(user_key.clone(), key);
+        self.owners.insert(key, user_key);
+        self.map.insert_and_get_mut(handle, value).1
+    }
+
+    /// Get the map key currently assigned to `user_key`
+    ///
+    /// 获取当前分配给 `user_key` 的 map 键
+    #[inline]
+    pub fn key_of<Q>(&self, user_key: &Q) -> Option<K>
+    where
+        UserK: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(user_key).copied()
+    }
+
+    /// Get the user key that owns the entry at `key`
+    ///
+    /// 获取拥有 `key` 处条目的用户键
+    #[inline]
+    pub fn user_key(&self, key: K) -> Option<&UserK> {
+        self.owners.get(key)
+    }
+
+    /// Get an immutable reference to the value mapped to `user_key`
+    ///
+    /// 获取 `user_key` 映射的值的不可变引用
+    #[inline]
+    pub fn get<Q>(&self, user_key: &Q) -> Option<&T>
+    where
+        UserK: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(self.key_of(user_key)?)
+    }
+
+    /// Get a mutable reference to the value mapped to `user_key`
+    ///
+    /// 获取 `user_key` 映射的值的可变引用
+    #[inline]
+    pub fn get_mut<Q>(&mut self, user_key: &Q) -> Option<&mut T>
+    where
+        UserK: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let key = self.key_of(user_key)?;
+        self.map.get_mut(key)
+    }
+
+    /// Check if `user_key` is mapped
+    ///
+    /// 检查 `user_key` 是否已映射
+    #[inline]
+    pub fn contains<Q>(&self, user_key: &Q) -> bool
+    where
+        UserK: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(user_key)
+    }
+
+    /// Remove the value mapped to `user_key`
+    ///
+    /// The freed slot may be reused by a later allocation, but keys issued for
+    /// the removed entry stay invalid thanks to generation checking.
+    ///
+    /// 删除 `user_key` 映射的值
+    ///
+    /// 释放的 slot 可能被之后的分配复用，但由于代数检查，为已删除条目发出的键仍然无效。
+    pub fn remove<Q>(&mut self, user_key: &Q) -> Option<T>
+    where
+        UserK: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let key = self.index.remove(user_key)?;
+        self.owners.remove(key);
+        self.map.remove(key)
+    }
+
+    /// Remove the entry at `key`, returning its user key and value
+    ///
+    /// 删除 `key` 处的条目，返回其用户键和值
+    pub fn remove_by_key(&mut self, key: K) -> Option<(UserK, T)> {
+        let user_key = self.owners.remove(key)?;
+        self.index.remove(&user_key);
+        let value = self.map.remove(key)?;
+        Some((user_key, value))
+    }
+
+    /// Return the number of mapped entries
+    ///
+    /// 返回已映射条目的数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check if the map is empty
+    ///
+    /// 检查 map 是否为空
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove all entries
+    ///
+    /// 清空所有条目
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.index.clear();
+        self.owners.clear();
+    }
+
+    /// Get a shared reference to the underlying DeferredMap
+    ///
+    /// 获取底层 DeferredMap 的共享引用
+    #[inline]
+    pub fn as_map(&self) -> &DeferredMap<T, K> {
+        &self.map
+    }
+}
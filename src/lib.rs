@@ -1,22 +1,62 @@
+#[cfg(feature = "concurrent")]
+mod concurrent;
+mod error;
+mod flagged;
 mod handle;
+mod keyed;
 mod map;
-mod secondary;
+pub mod secondary;
 mod slot;
 mod utils;
 
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use std::cmp::Ordering;
 use std::fmt;
 use std::num::NonZeroU32;
-use std::ops::Deref;
+use std::ops::{Add, Deref};
 
 use crate::utils::unlikely;
 
 /// A generation counter that is always non-zero.
 ///
 /// Used for preventing ABA problems in slot re-use.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+///
+/// A `Generation` always holds the logical value: a fresh slot starts at
+/// `Generation::MIN` (1), and each free bumps it by one. The same logical value is
+/// returned by `Handle::generation`, `Key::generation` and the slot itself; only the
+/// storage differs. `Version` packs it as `generation << 2 | state`, so a raw
+/// version `v` has generation `v >> 2`, while `DefaultKey` stores it unshifted in
+/// the upper 32 bits of its raw `u64`. `Display` and `Debug` both show the logical
+/// value, never a packed one.
+///
+/// 一个始终非零的代数计数器。
+///
+/// 用于防止 slot 复用时的 ABA 问题。
+///
+/// `Generation` 始终保存逻辑值：新 slot 从 `Generation::MIN`（1）开始，每次释放加一。
+/// `Handle::generation`、`Key::generation` 和 slot 本身返回相同的逻辑值，只是存储方式不同。
+/// `Version` 将其打包为 `generation << 2 | state`，因此原始 version `v` 的代数为 `v >> 2`；
+/// 而 `DefaultKey` 将其不移位地存储在原始 `u64` 的高 32 位。`Display` 和 `Debug`
+/// 都显示逻辑值，而不是打包后的值。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::{DeferredMap, Key};
+///
+/// let mut map = DeferredMap::new();
+/// let handle = map.allocate_handle();
+/// let key = handle.key();
+/// assert_eq!(handle.generation().as_u32(), 1);
+///
+/// map.insert(handle, "a");
+/// assert_eq!(map.versions().nth(1).unwrap() >> 2, 1);
+/// assert_eq!(key.raw() >> 32, 1);
+/// assert_eq!(format!("{:?}", key.generation()), "Generation(1)");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Generation(NonZeroU32);
 
@@ -26,8 +66,87 @@ impl fmt::Display for Generation {
     }
 }
 
+/// Compare against a plain logical generation, e.g. `handle.generation() == 1`
+///
+/// 与普通的逻辑代数比较，例如 `handle.generation() == 1`
+impl PartialEq<u32> for Generation {
+    #[inline(always)]
+    fn eq(&self, other: &u32) -> bool {
+        self.as_u32() == *other
+    }
+}
+
+impl PartialEq<Generation> for u32 {
+    #[inline(always)]
+    fn eq(&self, other: &Generation) -> bool {
+        *self == other.as_u32()
+    }
+}
+
+impl PartialOrd<u32> for Generation {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &u32) -> Option<Ordering> {
+        self.as_u32().partial_cmp(other)
+    }
+}
+
+impl PartialOrd<Generation> for u32 {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Generation) -> Option<Ordering> {
+        self.partial_cmp(&other.as_u32())
+    }
+}
+
+/// Advance a generation by `rhs` steps, wrapping like a slot does
+///
+/// Follows the same policy as freeing a slot: after `Generation::MAX` comes
+/// `Generation::MIN`, so zero is skipped and the result always fits in 30 bits.
+/// `generation + 1` is therefore exactly the generation a slot gets when freed.
+///
+/// 将代数前进 `rhs` 步，回绕方式与 slot 相同
+///
+/// 遵循与释放 slot 相同的策略：`Generation::MAX` 之后是 `Generation::MIN`，
+/// 因此会跳过零，并且结果始终在 30 位以内。所以 `generation + 1` 恰好是 slot 释放后得到的代数。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::Generation;
+///
+/// assert_eq!(Generation::MIN + 1, 2);
+/// assert_eq!(Generation::MAX + 1, Generation::MIN);
+/// ```
+impl Add<u32> for Generation {
+    type Output = Generation;
+
+    #[inline]
+    fn add(self, rhs: u32) -> Generation {
+        let period = Self::MAX.as_u32() as u64;
+        let offset = (self.as_u32() as u64 - 1 + rhs as u64) % period;
+        // SAFETY: offset < period, so the result is in 1..=MAX
+        unsafe { Self::new_unchecked(offset as u32 + 1) }
+    }
+}
+
+impl fmt::Debug for Generation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Generation").field(&self.as_u32()).finish()
+    }
+}
+
 impl Generation {
-    pub const MIN: Self = unsafe { Self(NonZeroU32::new_unchecked(1)) };
+    pub const MIN: Self = Self(NonZeroU32::new(1).unwrap());
+
+    /// The largest generation a slot can hold
+    ///
+    /// `Version` keeps the generation in its upper 30 bits, so this is `2^30 - 1`.
+    /// Freeing a slot at this generation wraps it back to `MIN`.
+    ///
+    /// slot 能持有的最大代数
+    ///
+    /// `Version` 将代数保存在高 30 位中，因此该值为 `2^30 - 1`。
+    /// 释放处于该代数的 slot 会使其回绕到 `MIN`。
+    pub const MAX: Self = Self(NonZeroU32::new(u32::MAX >> 2).unwrap());
 
     #[inline(always)]
     #[cfg(feature = "serde")]
@@ -35,6 +154,22 @@ impl Generation {
         Self(val)
     }
 
+    /// Return the logical generation as a plain `u32`
+    ///
+    /// 以普通 `u32` 返回逻辑代数
+    #[inline(always)]
+    pub const fn as_u32(self) -> u32 {
+        self.0.get()
+    }
+
+    /// Create a Generation without checking that the value is non-zero
+    ///
+    /// 创建 Generation，不检查值是否非零
+    ///
+    /// # Safety
+    /// `val` must be non-zero.
+    ///
+    /// `val` 必须非零。
     #[inline(always)]
     pub const unsafe fn new_unchecked(val: u32) -> Self {
         unsafe { Self(NonZeroU32::new_unchecked(val)) }
@@ -50,6 +185,26 @@ impl Deref for Generation {
     }
 }
 
+/// The logical state of a slot, as encoded in the low 2 bits of a `Version`
+///
+/// slot 的逻辑状态，编码在 `Version` 的最低 2 位中
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[repr(u32)]
+pub enum VersionState {
+    /// Slot is free (0b00)
+    ///
+    /// slot 空闲 (0b00)
+    Vacant = 0b00,
+    /// Slot is held by an outstanding Handle (0b01)
+    ///
+    /// slot 被未完成的 Handle 持有 (0b01)
+    Reserved = 0b01,
+    /// Slot holds a value (0b11)
+    ///
+    /// slot 持有值 (0b11)
+    Occupied = 0b11,
+}
+
 /// Represents the version of a slot, combining generation and state.
 ///
 /// The lowest 2 bits represent the state:
@@ -75,17 +230,49 @@ impl Version {
     /// Create a new Version with specified generation and state
     ///
     /// 使用指定的代数和状态创建一个新 Version
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if `generation` exceeds `Generation::MAX`: shifting it into the upper
+    /// 30 bits would drop its high bits and could produce a zero version.
+    ///
+    /// 当 `generation` 超过 `Generation::MAX` 时 panic：将其移入高 30 位会丢失高位，
+    /// 并可能产生值为零的版本。
     #[inline(always)]
     pub fn new(generation: Generation, state: u32) -> Self {
         debug_assert!(state <= 0b11);
+        assert!(generation <= Generation::MAX, "Generation exceeds 30 bits");
         let g = generation.0.get();
         // Shift generation left by 2, add state
-        // generation is NonZeroU32, so g >= 1. g << 2 >= 4.
-        // So the result is always non-zero.
+        // 1 <= g <= MAX, so g << 2 neither overflows nor drops to zero.
         let v = (g << 2) | (state & 0b11);
         unsafe { Self(NonZeroU32::new_unchecked(v)) }
     }
 
+    /// Create a Version with specified generation and typed state
+    ///
+    /// 使用指定的代数和类型化状态创建 Version
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if `generation` exceeds `Generation::MAX`, like [`Version::new`].
+    ///
+    /// 与 [`Version::new`] 相同，当 `generation` 超过 `Generation::MAX` 时 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{Generation, Version, VersionState};
+    ///
+    /// let version = Version::with_state(Generation::MIN, VersionState::Reserved);
+    /// assert_eq!(version.to_state(), VersionState::Reserved);
+    /// assert_eq!(version.generation(), Generation::MIN);
+    /// ```
+    #[inline(always)]
+    pub fn with_state(generation: Generation, state: VersionState) -> Self {
+        Self::new(generation, state as u32)
+    }
+
     /// Create a sentinel version
     ///
     /// 创建哨兵版本
@@ -111,6 +298,43 @@ impl Version {
         self.0.get() & 0b11
     }
 
+    /// Get the state part as a `VersionState`
+    ///
+    /// 以 `VersionState` 形式获取状态部分
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if the state bits are the invalid `0b10`, which only corrupted data
+    /// can produce; use `try_to_state` to inspect such a version.
+    ///
+    /// 当状态位为无效的 `0b10` 时 panic，只有损坏的数据才会产生这种情况；
+    /// 检查此类版本请使用 `try_to_state`。
+    #[inline(always)]
+    pub fn to_state(&self) -> VersionState {
+        match self.try_to_state() {
+            Some(state) => state,
+            None => panic!("invalid version state bits {:#04b}", self.state()),
+        }
+    }
+
+    /// Get the state part as a `VersionState`, or `None` for the invalid bits `0b10`
+    ///
+    /// Intended for validators and diagnostics that must report corruption
+    /// instead of panicking on it.
+    ///
+    /// 以 `VersionState` 形式获取状态部分，对于无效的状态位 `0b10` 返回 `None`
+    ///
+    /// 适用于必须报告损坏而不是因此 panic 的校验和诊断代码。
+    #[inline(always)]
+    pub fn try_to_state(&self) -> Option<VersionState> {
+        match self.state() {
+            0b00 => Some(VersionState::Vacant),
+            0b01 => Some(VersionState::Reserved),
+            0b11 => Some(VersionState::Occupied),
+            _ => None,
+        }
+    }
+
     /// Check if logic state is Vacant (0b00)
     ///
     /// 检查逻辑状态是否为空闲 (0b00)
@@ -221,6 +445,15 @@ impl Version {
 
 pub trait Key: Copy + Clone + PartialEq + Eq + Hash + Debug {
     type Raw: Copy + Clone + PartialEq + Eq + Hash + Debug;
+    /// Reconstruct a key from its raw representation
+    ///
+    /// 从原始表示重建 key
+    ///
+    /// # Safety
+    /// `raw` must have been produced by `Key::raw` of the same key type,
+    /// so that the encoded generation is non-zero.
+    ///
+    /// `raw` 必须由同一 key 类型的 `Key::raw` 产生，以保证编码的 generation 非零。
     unsafe fn from_raw(raw: Self::Raw, #[cfg(debug_assertions)] map_id: u64) -> Self;
     fn from_parts(index: u32, generation: Generation, #[cfg(debug_assertions)] map_id: u64)
     -> Self;
@@ -309,19 +542,238 @@ impl DefaultKey {
     }
 }
 
-pub use handle::Handle;
-pub use map::DeferredMap;
-pub use secondary::SecondaryMap;
+impl From<DefaultKey> for u64 {
+    #[inline(always)]
+    fn from(key: DefaultKey) -> u64 {
+        key.raw
+    }
+}
+
+/// Rebuild a key from its raw `u64`, rejecting a zero generation
+///
+/// Generations above `Generation::MAX` are rejected as well, since no slot can
+/// ever hold them. In debug builds the key is not bound to any map instance, so
+/// the wrong-map assertion accepts it for every map.
+///
+/// 从原始 `u64` 重建 key，拒绝代数为零的值
+///
+/// 代数超过 `Generation::MAX` 的值同样会被拒绝，因为任何 slot 都无法持有它。
+/// 在 debug 构建中，该 key 不绑定任何 map 实例，因此错误 map 断言对所有 map 都会接受它。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::{DefaultKey, DeferredMap, DeferredMapError};
+///
+/// let mut map = DeferredMap::new();
+/// let handle = map.allocate_handle();
+/// let raw = u64::from(handle.key());
+/// map.insert(handle, "a");
+///
+/// let key = DefaultKey::try_from(raw).unwrap();
+/// assert_eq!(map.get(key), Some(&"a"));
+/// assert_eq!(DefaultKey::try_from(7u64), Err(DeferredMapError::ZeroGeneration));
+/// assert_eq!(
+///     DefaultKey::try_from(1u64 << 62),
+///     Err(DeferredMapError::GenerationOutOfRange)
+/// );
+/// ```
+impl TryFrom<u64> for DefaultKey {
+    type Error = DeferredMapError;
+
+    #[inline]
+    fn try_from(raw: u64) -> Result<Self, Self::Error> {
+        let generation = raw >> 32;
+        if generation == 0 {
+            return Err(DeferredMapError::ZeroGeneration);
+        }
+        if generation > u64::from(Generation::MAX.get()) {
+            return Err(DeferredMapError::GenerationOutOfRange);
+        }
+        // SAFETY: the generation (upper 32 bits) was just checked to be non-zero
+        Ok(unsafe {
+            <Self as Key>::from_raw(
+                raw,
+                #[cfg(debug_assertions)]
+                crate::utils::UNBOUND_MAP_ID,
+            )
+        })
+    }
+}
+
+/// Declare a new key type backed by `DefaultKey`
+///
+/// The generated type is a `#[repr(transparent)]` newtype implementing `Key`, so maps
+/// keyed by different types cannot be mixed up at compile time.
+///
+/// 声明一个由 `DefaultKey` 支持的新 key 类型
+///
+/// 生成的类型是实现了 `Key` 的 `#[repr(transparent)]` newtype，
+/// 因此使用不同 key 类型的 map 在编译期就无法混用。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::{DeferredMap, SecondaryMap, new_key_type};
+///
+/// new_key_type! {
+///     /// Key of an entity
+///     pub struct EntityKey;
+///     struct ItemKey;
+/// }
+///
+/// let mut entities = DeferredMap::<&str, EntityKey>::with_key();
+/// let handle = entities.allocate_handle();
+/// let key: EntityKey = handle.key();
+/// entities.insert(handle, "player");
+///
+/// let mut health = SecondaryMap::<u32, EntityKey>::new();
+/// health.insert(key, 100);
+/// assert_eq!(health.get(key), Some(&100));
+///
+/// let items = DeferredMap::<u32, ItemKey>::with_capacity_and_key(8);
+/// assert!(items.is_empty());
+/// ```
+#[macro_export]
+macro_rules! new_key_type {
+    ( $(#[$outer:meta])* $vis:vis struct $name:ident; $($rest:tt)* ) => {
+        $(#[$outer])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        #[repr(transparent)]
+        $vis struct $name($crate::DefaultKey);
+
+        $crate::__impl_key_for_newtype!($name);
+
+        impl From<$name> for $crate::DefaultKey {
+            #[inline(always)]
+            fn from(key: $name) -> Self {
+                key.0
+            }
+        }
+
+        $crate::new_key_type!($($rest)*);
+    };
+
+    () => {};
+}
+
+// The `Key` signatures depend on this crate's `debug_assertions`, so the impl is
+// selected here rather than with `#[cfg]` inside `new_key_type!`, which would be
+// evaluated in the calling crate
+// `Key` 的签名取决于本 crate 的 `debug_assertions`，因此在这里选择实现，
+// 而不是在 `new_key_type!` 内部使用 `#[cfg]`（那会在调用方 crate 中求值）
+#[cfg(debug_assertions)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_key_for_newtype {
+    ($name:ident) => {
+        impl $crate::Key for $name {
+            type Raw = <$crate::DefaultKey as $crate::Key>::Raw;
+
+            #[inline(always)]
+            unsafe fn from_raw(raw: Self::Raw, map_id: u64) -> Self {
+                Self(unsafe { <$crate::DefaultKey as $crate::Key>::from_raw(raw, map_id) })
+            }
+
+            #[inline(always)]
+            fn from_parts(index: u32, generation: $crate::Generation, map_id: u64) -> Self {
+                Self(<$crate::DefaultKey as $crate::Key>::from_parts(
+                    index, generation, map_id,
+                ))
+            }
+
+            #[inline(always)]
+            fn index(&self) -> u32 {
+                $crate::Key::index(&self.0)
+            }
+
+            #[inline(always)]
+            fn generation(&self) -> $crate::Generation {
+                $crate::Key::generation(&self.0)
+            }
+
+            #[inline(always)]
+            fn raw(&self) -> Self::Raw {
+                $crate::Key::raw(&self.0)
+            }
+
+            #[inline(always)]
+            fn map_id(&self) -> u64 {
+                $crate::Key::map_id(&self.0)
+            }
+        }
+    };
+}
+
+#[cfg(not(debug_assertions))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_key_for_newtype {
+    ($name:ident) => {
+        impl $crate::Key for $name {
+            type Raw = <$crate::DefaultKey as $crate::Key>::Raw;
+
+            #[inline(always)]
+            unsafe fn from_raw(raw: Self::Raw) -> Self {
+                Self(unsafe { <$crate::DefaultKey as $crate::Key>::from_raw(raw) })
+            }
+
+            #[inline(always)]
+            fn from_parts(index: u32, generation: $crate::Generation) -> Self {
+                Self(<$crate::DefaultKey as $crate::Key>::from_parts(
+                    index, generation,
+                ))
+            }
+
+            #[inline(always)]
+            fn index(&self) -> u32 {
+                $crate::Key::index(&self.0)
+            }
+
+            #[inline(always)]
+            fn generation(&self) -> $crate::Generation {
+                $crate::Key::generation(&self.0)
+            }
+
+            #[inline(always)]
+            fn raw(&self) -> Self::Raw {
+                $crate::Key::raw(&self.0)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "concurrent")]
+pub use concurrent::ConcurrentAllocator;
+pub use error::{DeferredMapError, GetFailure};
+pub use flagged::FlaggedDeferredMap;
+pub use handle::{Handle, dropped_unused_handles};
+pub use keyed::KeyedMap;
+pub use map::{
+    DeferredMap, ExtractIf, GrowthPolicy, IterMutRange, OneOrTwo, RemovedGuard, RetainResult,
+    SlotStats,
+};
+pub use secondary::{ConflictPolicy, SecondaryMap};
 
 #[cfg(test)]
 mod tests {
     // Test modules for DeferredMap
     // DeferredMap 的测试模块
+    #[cfg(feature = "concurrent")]
+    mod concurrent;
+    #[cfg(debug_assertions)]
     mod debug_safety;
     mod edge_cases;
+    mod errors;
+    mod flagged;
     mod handle;
     mod insertion;
+    mod key_type;
+    mod keyed;
+    mod model;
     mod new_features;
     mod removal;
     mod secondary_test;
+    mod send_sync;
+    mod version;
 }
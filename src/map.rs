@@ -1,8 +1,9 @@
+use crate::error::{DeferredMapError, GetFailure};
 use crate::handle::Handle;
 use crate::slot::SlotContent::Occupied;
-use crate::slot::SlotContentMut::OccupiedMut;
 use crate::slot::{Slot, SlotUnion};
 use crate::utils::{likely, unlikely};
+use std::iter::FusedIterator;
 use std::mem::ManuallyDrop;
 #[cfg(debug_assertions)]
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -10,6 +11,73 @@ use std::sync::atomic::{AtomicU64, Ordering};
 #[cfg(debug_assertions)]
 static NEXT_MAP_ID: AtomicU64 = AtomicU64::new(0);
 
+/// How many keys ahead batched lookups prefetch
+///
+/// 批量查找时提前预取的 key 数量
+const PREFETCH_DISTANCE: usize = 8;
+
+/// Breakdown of slot states returned by [`DeferredMap::slot_stats`]
+///
+/// The sentinel slot is not counted, so `occupied + reserved + vacant == total`.
+///
+/// [`DeferredMap::slot_stats`] 返回的 slot 状态统计
+///
+/// 不计入 sentinel slot，因此 `occupied + reserved + vacant == total`。
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SlotStats {
+    /// Slots holding a value | 持有值的 slot
+    pub occupied: usize,
+    /// Slots held by an outstanding Handle | 被未完成 Handle 持有的 slot
+    pub reserved: usize,
+    /// Free slots | 空闲的 slot
+    pub vacant: usize,
+    /// All slots except the sentinel | 除 sentinel 外的所有 slot
+    pub total: usize,
+}
+
+/// Outcome of [`DeferredMap::retain`] and [`DeferredMap::retain_mut`]
+///
+/// `removed + retained` equals the map's length before the call.
+///
+/// [`DeferredMap::retain`] 和 [`DeferredMap::retain_mut`] 的结果
+///
+/// `removed + retained` 等于调用前 map 的长度。
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RetainResult {
+    /// Entries removed by the predicate | 被谓词移除的条目
+    pub removed: usize,
+    /// Entries kept | 保留的条目
+    pub retained: usize,
+}
+
+/// How `allocate_handle` grows the slot storage when it runs out of room
+///
+/// Only the branch that appends a fresh slot is affected; reusing a slot from the
+/// free list never allocates. Set it with [`DeferredMap::set_growth`].
+///
+/// `allocate_handle` 在 slot 存储空间用尽时的增长方式
+///
+/// 只影响追加新 slot 的分支；从空闲列表复用 slot 永远不会分配内存。
+/// 通过 [`DeferredMap::set_growth`] 设置。
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GrowthPolicy {
+    /// `Vec`'s amortized doubling (the default)
+    ///
+    /// `Vec` 的摊还倍增（默认）
+    #[default]
+    Double,
+
+    /// Grow by a fixed number of slots at a time (treated as 1 if zero)
+    ///
+    /// 每次增长固定数量的 slot（为零时按 1 处理）
+    Linear(usize),
+
+    /// Grow by exactly one slot, never over-allocating
+    ///
+    /// 每次只增长一个 slot，从不过度分配
+    Exact,
+}
+
 /// DeferredMap is a high-performance map based on slotmap
 ///
 /// Usage requires first obtaining a Handle via `allocate_handle`,
@@ -26,6 +94,47 @@ static NEXT_MAP_ID: AtomicU64 = AtomicU64::new(0);
 /// - Handle-based deferred insertion | 基于 Handle 的延迟插入
 /// - Memory efficient with union-based slots | 使用 union 的内存高效 slot
 ///
+/// # Limits (限制)
+///
+/// Indices and the element count are `u32`, and index 0 is reserved for the sentinel,
+/// so a map holds at most `u32::MAX - 1` live entries. The largest index is
+/// `u32::MAX - 1` (the free list uses `slots.len()` as its terminator, which must
+/// itself fit in a `u32`); allocating past it panics instead of truncating.
+///
+/// 索引和元素计数均为 `u32`，且索引 0 保留给 sentinel，
+/// 因此一个 map 最多容纳 `u32::MAX - 1` 个存活条目。最大索引为 `u32::MAX - 1`
+/// （空闲列表以 `slots.len()` 作为终止标记，它本身也必须能用 `u32` 表示）；
+/// 超出后继续分配会 panic，而不是截断。
+///
+/// # Sentinel slot (哨兵 slot)
+///
+/// Index 0 always holds a vacant sentinel that is never handed out, so every real
+/// key has a non-zero index and `capacity()` excludes one slot. The sentinel is
+/// part of the stable key format: serialized maps and raw keys produced by
+/// `Key::raw` encode indices starting at 1, and fallible operations report index 0
+/// as `InvalidHandle`. A sentinel-free layout would shift every index by one, so
+/// it cannot be offered as a drop-in option without breaking those keys. The cost
+/// it would remove is small in practice: one slot of memory and a `skip(1)` at the
+/// start of each scan, not a per-element branch.
+///
+/// 索引 0 始终保存一个永不分配出去的空闲 sentinel，因此每个真实 key 的索引都非零，
+/// 且 `capacity()` 不计入这一个 slot。sentinel 是稳定 key 格式的一部分：序列化的 map
+/// 以及 `Key::raw` 产生的原始 key 的索引都从 1 开始，可失败操作会将索引 0 报告为
+/// `InvalidHandle`。去掉 sentinel 的布局会让所有索引整体偏移一位，因此无法在不破坏这些 key
+/// 的前提下作为可替换的选项提供。它实际节省的开销很小：一个 slot 的内存，
+/// 以及每次扫描开头的一次 `skip(1)`，而不是逐元素的分支。
+///
+/// # Thread Safety (线程安全)
+///
+/// `DeferredMap<T>` is `Send`/`Sync` exactly when `T` is: the slot union only
+/// holds a `ManuallyDrop<T>` or a plain `u32`, so the auto traits follow `T`
+/// without any manual `unsafe impl`. `Handle` and `DefaultKey` are plain data
+/// and always `Send + Sync`.
+///
+/// 当且仅当 `T` 是 `Send`/`Sync` 时，`DeferredMap<T>` 才是 `Send`/`Sync`：
+/// slot union 只包含 `ManuallyDrop<T>` 或普通的 `u32`，因此自动 trait 跟随 `T`，
+/// 无需手动 `unsafe impl`。`Handle` 和 `DefaultKey` 是纯数据，始终为 `Send + Sync`。
+///
 /// # Examples (示例)
 ///
 /// ```
@@ -35,10 +144,9 @@ static NEXT_MAP_ID: AtomicU64 = AtomicU64::new(0);
 ///
 /// // Allocate handle first | 先分配 handle
 /// let handle = map.allocate_handle();
-/// let key = handle.key();
 ///
-/// // Insert value later | 之后插入值
-/// map.insert(handle, 42);
+/// // Insert value later, getting its key back | 之后插入值并取回其 key
+/// let key = map.insert(handle, 42);
 ///
 /// // Access value | 访问值
 /// assert_eq!(map.get(key), Some(&42));
@@ -48,11 +156,23 @@ static NEXT_MAP_ID: AtomicU64 = AtomicU64::new(0);
 /// ```
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeferredMap<T, K: crate::Key = crate::DefaultKey> {
-    slots: Vec<Slot<T>>,
-    free_head: u32, // Head of free list | 空闲列表的头部索引
-    num_elems: u32, // Current element count | 当前元素数量
+    pub(crate) slots: Vec<Slot<T>>,
+    pub(crate) free_head: u32, // Head of free list | 空闲列表的头部索引
+    num_elems: u32,            // Current element count | 当前元素数量
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) num_reserved: u32, // Outstanding reserved slots | 未完成的预留 slot 数量
+    #[cfg_attr(feature = "serde", serde(skip))]
+    epoch: u64, // Structural mutation counter | 结构性修改计数器
+    #[cfg_attr(feature = "serde", serde(skip))]
+    growth: GrowthPolicy, // Grow policy of allocate_handle | allocate_handle 的增长策略
+    #[cfg_attr(feature = "serde", serde(default = "min_generation"))]
+    pub(crate) fresh_generation: crate::Generation, // Generation of newly created slots | 新建 slot 的代数
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fifo: bool, // Vacated slots join the tail of the free list | 空出的 slot 加入空闲列表尾部
+    #[cfg_attr(feature = "serde", serde(skip))]
+    free_tail: u32, // Tail of free list, maintained only when fifo | 空闲列表尾部，仅在 fifo 时维护
     #[cfg(debug_assertions)]
-    map_id: u64,
+    pub(crate) map_id: u64,
     #[cfg_attr(feature = "serde", serde(skip))]
     _marker: std::marker::PhantomData<K>,
 }
@@ -77,6 +197,90 @@ impl<T> DeferredMap<T, crate::DefaultKey> {
 }
 
 impl<T, K: crate::Key> DeferredMap<T, K> {
+    /// Create a new empty DeferredMap for a custom key type
+    ///
+    /// `new` is only available for `DefaultKey` so that `DeferredMap::new()` never
+    /// needs a type annotation; use this constructor for keys declared with
+    /// `new_key_type!`.
+    ///
+    /// 为自定义 key 类型创建一个新的空 DeferredMap
+    ///
+    /// `new` 只适用于 `DefaultKey`，以保证 `DeferredMap::new()` 无需类型标注；
+    /// 对于用 `new_key_type!` 声明的 key，请使用此构造函数。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, new_key_type};
+    ///
+    /// new_key_type! { struct NodeKey; }
+    ///
+    /// let mut map = DeferredMap::<i32, NodeKey>::with_key();
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    /// map.insert(handle, 1);
+    /// assert_eq!(map.get(key), Some(&1));
+    /// ```
+    #[inline(always)]
+    pub fn with_key() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a DeferredMap with specified capacity for a custom key type
+    ///
+    /// Same as `with_capacity`; the name mirrors `with_key` for readability.
+    ///
+    /// 为自定义 key 类型创建一个指定容量的 DeferredMap
+    ///
+    /// 与 `with_capacity` 相同；命名与 `with_key` 对应以便阅读。
+    #[inline(always)]
+    pub fn with_capacity_and_key(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+
+    /// Create an empty DeferredMap whose free list is FIFO instead of LIFO
+    ///
+    /// Vacated slots are appended at the tail of the free list and handles are
+    /// allocated from its head, so reuse rotates through every freed slot instead
+    /// of hammering the most recently freed one. Generations then age uniformly,
+    /// which keeps a single hot slot from burning through its generations and
+    /// triggering wrap long before the others. The cost is cache locality: the
+    /// reused slot is the coldest one rather than the one just touched.
+    ///
+    /// The setting survives `clone` and `clear`, but is not serialized; a
+    /// deserialized map uses the default LIFO order.
+    ///
+    /// 创建一个空闲列表为 FIFO 而非 LIFO 的空 DeferredMap
+    ///
+    /// 空出的 slot 追加到空闲列表尾部，handle 从列表头部分配，因此复用会轮转所有被释放的
+    /// slot，而不是反复使用最近释放的那个。这样代数会均匀老化，避免单个热点 slot 远早于
+    /// 其他 slot 耗尽代数并发生回绕。代价是缓存局部性：被复用的是最冷的 slot，而不是刚访问过的。
+    ///
+    /// 该设置在 `clone` 和 `clear` 后保留，但不会被序列化；反序列化得到的 map 使用默认的 LIFO 顺序。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Key};
+    ///
+    /// let mut map = DeferredMap::<i32>::with_fifo_free_list();
+    /// let keys = map.seed([1, 2, 3]);
+    /// map.remove(keys[0]);
+    /// map.remove(keys[1]);
+    ///
+    /// // The first slot freed is the first reused
+    /// // 最先释放的 slot 最先被复用
+    /// let handle = map.allocate_handle();
+    /// assert_eq!(handle.index(), keys[0].index());
+    /// # map.insert(handle, 4);
+    /// ```
+    #[inline]
+    pub fn with_fifo_free_list() -> Self {
+        let mut map = Self::with_key();
+        map.fifo = true;
+        map
+    }
+
     /// Create a DeferredMap with specified capacity
     ///
     /// 创建一个指定容量的 DeferredMap
@@ -111,12 +315,45 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
             slots,
             free_head: 1, // Start allocation from index 1 | 从索引 1 开始分配
             num_elems: 0,
+            num_reserved: 0,
+            epoch: 0,
+            growth: GrowthPolicy::Double,
+            fresh_generation: crate::Generation::MIN,
+            fifo: false,
+            free_tail: 0,
             #[cfg(debug_assertions)]
             map_id: NEXT_MAP_ID.fetch_add(1, Ordering::Relaxed),
             _marker: std::marker::PhantomData,
         }
     }
 
+    /// Create a DeferredMap holding every value of `values`, in iteration order
+    ///
+    /// Returns the map together with the assigned keys, where `keys[i]` is the key
+    /// of the `i`-th value.
+    ///
+    /// 创建一个按迭代顺序包含 `values` 中所有值的 DeferredMap
+    ///
+    /// 返回 map 以及分配的 key，其中 `keys[i]` 是第 `i` 个值的 key。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (map, keys) = DeferredMap::<_>::from_values(["a", "b", "c"]);
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get(keys[1]), Some(&"b"));
+    /// ```
+    pub fn from_values<I>(values: I) -> (Self, Vec<K>)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut map = Self::with_key();
+        let keys = map.seed(values);
+        (map, keys)
+    }
+
     /// Pre-allocate a Handle
     ///
     /// This Handle can be used later to insert a value.
@@ -133,6 +370,12 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     /// # 返回值
     /// 用于后续插入的唯一 Handle
     ///
+    /// # Panics
+    /// Panics if the free list is empty and the map already uses every index up to
+    /// `u32::MAX - 1`.
+    ///
+    /// 如果空闲列表为空且 map 已用尽直到 `u32::MAX - 1` 的所有索引，则 panic。
+    ///
     /// # Examples (示例)
     ///
     /// ```
@@ -140,8 +383,12 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     ///
     /// let mut map = DeferredMap::new();
     /// let handle = map.allocate_handle();
-    /// let key = handle.key();
-    /// map.insert(handle, "value");
+    ///
+    /// // The key is known before insertion... | 插入前即可得知 key……
+    /// let early = handle.key();
+    /// // ...and returned again by insert | ……insert 也会再次返回它
+    /// let key = map.insert(handle, "value");
+    /// assert_eq!(key, early);
     /// assert_eq!(map.get(key), Some(&"value"));
     /// ```
     pub fn allocate_handle(&mut self) -> Handle<K> {
@@ -150,6 +397,16 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
             // 从空闲列表中复用已有的空闲 slot
             let index = self.free_head;
 
+            // A slot on the free list must be vacant; anything else means the list is corrupt
+            // (double free, bad deserialized data) and reusing it would clobber live data
+            // 空闲列表中的 slot 必须为空闲状态；否则说明列表已损坏
+            // （重复释放、错误的反序列化数据），复用它会覆盖存活数据
+            debug_assert!(
+                slot.is_vacant(),
+                "Free list corrupted: slot {} is not vacant",
+                index
+            );
+
             // Update free_head to next free slot before changing state
             // 在改变状态前更新 free_head 到下一个空闲 slot
             unsafe {
@@ -159,6 +416,7 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
             // Transition: vacant(0bXX00) -> reserved(0bXX01)
             // 状态转换：vacant(0bXX00) -> reserved(0bXX01)
             slot.version.vacant_to_reserved();
+            self.num_reserved += 1;
 
             let key = K::from_parts(
                 index,
@@ -170,8 +428,9 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         } else {
             // Need to extend Vec, allocate new slot
             // 需要扩展 Vec，分配新 slot
-            let index = self.slots.len() as u32;
-            let version = crate::Version::new(crate::Generation::MIN, 0b01); // New slot starts at Generation 1, reserved state | 新 slot 从 Generation 1 开始，reserved 状态
+            let index = fresh_index(self.slots.len());
+            self.reserve_for_growth();
+            let version = crate::Version::new(self.fresh_generation, 0b01); // New slot starts at the fresh generation, reserved state | 新 slot 从新建代数开始，reserved 状态
 
             // Create reserved slot
             // 创建 reserved slot
@@ -183,6 +442,7 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
             // Update free_head
             // 更新 free_head
             self.free_head = index + 1;
+            self.num_reserved += 1;
 
             // Extract generation from version (reserved state: 0b01)
             // 从 version 提取 generation（reserved 状态：0b01）
@@ -196,6 +456,167 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         }
     }
 
+    /// Pre-allocate `n` Handles at once
+    ///
+    /// Vacant slots from the free list are reused first; room for the remaining
+    /// fresh slots is reserved up front, so the slot storage reallocates at most once
+    /// per call no matter how large `n` is. Handles are returned in allocation order.
+    ///
+    /// 一次预分配 `n` 个 Handle
+    ///
+    /// 优先复用空闲列表中的 slot；剩余新 slot 所需的空间会预先一次性预留，
+    /// 因此无论 `n` 多大，每次调用 slot 存储最多只重新分配一次。Handle 按分配顺序返回。
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index space is exhausted, see [`allocate_handle`](Self::allocate_handle).
+    ///
+    /// 如果索引空间耗尽则 panic，参见 [`allocate_handle`](Self::allocate_handle)。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handles = map.allocate_handles(3);
+    /// assert_eq!(map.reserved_len(), 3);
+    ///
+    /// for (i, handle) in handles.into_iter().enumerate() {
+    ///     map.insert(handle, i);
+    /// }
+    /// assert_eq!(map.len(), 3);
+    /// ```
+    pub fn allocate_handles(&mut self, n: usize) -> Vec<Handle<K>> {
+        // Every vacant slot is on the free list
+        // 每个空闲 slot 都在空闲列表中
+        let vacant = (self.slots.len() - 1)
+            .saturating_sub(self.num_elems as usize + self.num_reserved as usize);
+        self.slots.reserve(n.saturating_sub(vacant));
+
+        (0..n).map(|_| self.allocate_handle()).collect()
+    }
+
+    /// Append every value of `values` at consecutive fresh indices, in one pass
+    ///
+    /// Each value goes straight into a new occupied slot at the end of the slot
+    /// storage, skipping the reserved state and the free list: vacant slots are
+    /// not reused, so `keys[i].index()` is the old slot count plus `i`. Storage for
+    /// the iterator's lower size bound is reserved up front. Much cheaper than an
+    /// `allocate_handle` + `insert` loop when bulk-loading.
+    ///
+    /// 将 `values` 中的每个值一次性追加到连续的新索引上
+    ///
+    /// 每个值直接写入 slot 存储末尾的新占用 slot，跳过预留状态和空闲列表：
+    /// 不会复用空闲 slot，因此 `keys[i].index()` 等于原 slot 数加 `i`。
+    /// 会预先按迭代器的下界大小预留存储。批量加载时比 `allocate_handle` + `insert`
+    /// 循环开销小得多。
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if the index space is exhausted. If `values` panics instead, the
+    /// values appended so far stay in the map and the map remains consistent.
+    ///
+    /// 如果索引空间耗尽则 panic。如果是 `values` 发生 panic，已追加的值会保留在
+    /// map 中，map 仍保持一致。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Key};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let keys = map.seed(["a", "b", "c"]);
+    ///
+    /// assert_eq!(keys.iter().map(|k| k.index()).collect::<Vec<_>>(), [1, 2, 3]);
+    /// assert_eq!(map.get(keys[2]), Some(&"c"));
+    /// ```
+    pub fn seed<I>(&mut self, values: I) -> Vec<K>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        /// Moves the free list end past the appended slots and bumps the epoch
+        /// once `seed` stops appending, including when `values` panics
+        ///
+        /// 在 `seed` 停止追加时（包括 `values` panic 时）将空闲列表末端移到追加的
+        /// slot 之后并递增 epoch
+        struct SeedGuard<'a, T, K: crate::Key> {
+            map: &'a mut DeferredMap<T, K>,
+            old_len: u32,
+        }
+
+        impl<T, K: crate::Key> Drop for SeedGuard<'_, T, K> {
+            fn drop(&mut self) {
+                let new_len = self.map.slots.len() as u32;
+                if new_len != self.old_len {
+                    // The free list ended at the old slot count, which is now occupied
+                    // 空闲列表原本终止于旧的 slot 数，该位置现在已被占用
+                    self.map.retarget_free_end(self.old_len, new_len);
+                    self.map.bump_epoch();
+                }
+            }
+        }
+
+        let values = values.into_iter();
+        let (lower, _) = values.size_hint();
+        self.slots.reserve(lower);
+        let mut keys = Vec::with_capacity(lower);
+
+        let old_len = self.slots.len() as u32;
+        let guard = SeedGuard { map: self, old_len };
+        for value in values {
+            let map = &mut *guard.map;
+            let index = fresh_index(map.slots.len());
+            let num_elems = map
+                .num_elems
+                .checked_add(1)
+                .expect("DeferredMap element count overflow");
+            map.slots.push(Slot {
+                u: SlotUnion {
+                    value: ManuallyDrop::new(value),
+                },
+                version: crate::Version::new(map.fresh_generation, 0b11),
+            });
+            map.num_elems = num_elems;
+            keys.push(K::from_parts(
+                index,
+                map.fresh_generation,
+                #[cfg(debug_assertions)]
+                map.map_id,
+            ));
+        }
+        drop(guard);
+        keys
+    }
+
+    /// Make the free list terminate at `new_end` instead of `old_end`
+    ///
+    /// 使空闲列表终止于 `new_end` 而不是 `old_end`
+    fn retarget_free_end(&mut self, old_end: u32, new_end: u32) {
+        if self.free_head == old_end {
+            self.free_head = new_end;
+            return;
+        }
+        if self.fifo {
+            // The list is not empty, so free_tail is its last slot
+            // 列表非空，因此 free_tail 是其最后一个 slot
+            self.slots[self.free_tail as usize].u.next_free = new_end;
+            return;
+        }
+
+        let mut current = self.free_head;
+        loop {
+            let slot = &mut self.slots[current as usize];
+            // SAFETY: slots on the free list are vacant
+            let next = unsafe { slot.u.next_free };
+            if next == old_end {
+                slot.u.next_free = new_end;
+                return;
+            }
+            current = next;
+        }
+    }
+
     /// Insert value using Handle
     ///
     /// The Handle is consumed (moved), ensuring it can only be used once.
@@ -210,10 +631,27 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     /// - `handle`: The Handle obtained from `allocate_handle`
     /// - `value`: The value to insert
     ///
+    /// # Returns
+    /// The key of the inserted value, the same as `handle.key()`. It can be ignored
+    /// when the key was already taken from the handle.
+    ///
     /// # 参数
     /// - `handle`: 从 `allocate_handle` 获取的 Handle
     /// - `value`: 要插入的值
     ///
+    /// # 返回值
+    /// 插入值的 key，与 `handle.key()` 相同。如果已经从 handle 取得 key，可以忽略返回值。
+    ///
+    /// # Panics
+    /// Panics if the handle points at the sentinel or outside the map, or if its
+    /// slot is no longer reserved for it (for example after `release_all_reserved`).
+    /// The map is left untouched and the value is dropped. Use `try_insert` to get
+    /// an error instead.
+    ///
+    /// 如果 handle 指向 sentinel 或超出 map 范围，或者其 slot 已不再为它预留
+    /// （例如在 `release_all_reserved` 之后），则 panic。map 保持不变，值会被丢弃。
+    /// 如需得到错误而不是 panic，请使用 `try_insert`。
+    ///
     /// # Examples (示例)
     ///
     /// ```
@@ -221,55 +659,258 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     ///
     /// let mut map = DeferredMap::new();
     /// let handle = map.allocate_handle();
-    /// let key = handle.key();
-    /// map.insert(handle, 42);
+    /// let key = map.insert(handle, 42);
     /// assert_eq!(map.get(key), Some(&42));
+    ///
+    /// // The returned key may be ignored
+    /// // 返回的 key 可以被忽略
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, 7);
+    /// assert_eq!(map.len(), 2);
     /// ```
-    pub fn insert(&mut self, handle: Handle<K>, value: T) {
-        #[cfg(debug_assertions)]
-        debug_assert_eq!(
-            self.map_id,
-            handle.key.map_id(),
-            "Handle used with wrong map instance"
-        );
+    pub fn insert(&mut self, handle: Handle<K>, value: T) -> K {
+        let key = handle.key();
+        self.insert_slot(handle, value)
+            .expect("Invalid handle: sentinel, out-of-bounds or stale index");
+        key
+    }
 
-        let index = handle.index();
+    /// Insert value using Handle and return the key with a mutable reference to the value
+    ///
+    /// Equivalent to `insert` followed by `get_mut`, without the second lookup.
+    ///
+    /// 使用 Handle 插入值，并返回 key 以及该值的可变引用
+    ///
+    /// 等价于 `insert` 之后再调用 `get_mut`，但无需第二次查找
+    ///
+    /// # Panics
+    /// Panics if the handle points at the sentinel or outside the map, or if its
+    /// slot is no longer reserved for it.
+    ///
+    /// 如果 handle 指向 sentinel 或超出 map 范围，或者其 slot 已不再为它预留，则 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let (key, value) = map.insert_and_get_mut(handle, vec![1]);
+    /// value.push(2);
+    /// assert_eq!(map.get(key), Some(&vec![1, 2]));
+    /// ```
+    pub fn insert_and_get_mut(&mut self, handle: Handle<K>, value: T) -> (K, &mut T) {
+        let key = handle.key();
+        let value = self
+            .insert_slot(handle, value)
+            .expect("Invalid handle: sentinel, out-of-bounds or stale index");
+        (key, value)
+    }
 
-        // Validate index (skip sentinel)
-        // 验证 index 有效（跳过 sentinel）
-        debug_assert!(index != 0, "Invalid handle: sentinel index");
+    /// Insert a value at a caller-chosen index and generation
+    ///
+    /// Meant for rebuilding a map from an external format or mirroring another map's
+    /// layout. `slots` is extended with vacant slots as needed, the target slot is made
+    /// occupied with `generation`, and the free list is fixed up so the index is no
+    /// longer considered free. Unlinking the index walks the free list, so this is
+    /// O(number of vacant slots).
+    ///
+    /// 在调用者指定的索引和代数处插入值
+    ///
+    /// 用于从外部格式重建 map 或复制另一个 map 的布局。`slots` 会按需扩展空闲 slot，
+    /// 目标 slot 以 `generation` 变为占用状态，并修复空闲列表使该索引不再被视为空闲。
+    /// 从空闲列表摘除索引需要遍历空闲列表，因此复杂度为 O(空闲 slot 数量)。
+    ///
+    /// # Safety
+    /// - The slot at `index` must not be occupied or reserved (it is vacant or past the end).
+    ///   Otherwise the free-list link is read out of a union that holds a live value
+    ///   (or no link at all), the free list is relinked from garbage, and later
+    ///   allocations hand out occupied slots whose values get overwritten: undefined
+    ///   behavior.
+    /// - `generation` should be at least the slot's current generation. This one is
+    ///   a logic requirement rather than a memory-safety one: if it is violated, keys
+    ///   issued for earlier occupants of the slot may resolve to the new entry.
+    ///
+    /// - `index` 处的 slot 不能处于占用或预留状态（必须为空闲或超出末尾）。否则会从持有
+    ///   存活值（或根本没有链接）的 union 中读取空闲列表链接，空闲列表会按垃圾数据重新链接，
+    ///   之后的分配会交出已占用的 slot 并覆盖其中的值：这是未定义行为。
+    /// - `generation` 应不小于该 slot 当前的代数。这是逻辑要求而非内存安全要求：
+    ///   违反时，该 slot 之前条目的 key 可能解析到新条目。
+    ///
+    /// # Panics
+    /// Panics if `index` is 0 (the sentinel) or `u32::MAX`, or if `generation`
+    /// exceeds `Generation::MAX`.
+    ///
+    /// 如果 `index` 为 0（sentinel）或 `u32::MAX`，或者 `generation` 超过
+    /// `Generation::MAX`，则 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Generation, Key};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let generation = unsafe { Generation::new_unchecked(3) };
+    /// let key = unsafe { map.insert_at(5, generation, "restored") };
+    ///
+    /// assert_eq!(key.index(), 5);
+    /// assert_eq!(key.generation(), generation);
+    /// assert_eq!(map.get(key), Some(&"restored"));
+    /// ```
+    pub unsafe fn insert_at(&mut self, index: u32, generation: crate::Generation, value: T) -> K {
+        assert!(index != 0, "Invalid index: sentinel index");
+        assert!(index != u32::MAX, "Invalid index: index space exhausted");
+        assert!(
+            generation <= crate::Generation::MAX,
+            "Generation does not fit in 30 bits"
+        );
+        let num_elems = self
+            .num_elems
+            .checked_add(1)
+            .expect("DeferredMap element count overflow");
+
+        // Extend with vacant slots chained in index order; the old terminal
+        // (the previous slots.len()) becomes the first of them, so the chain
+        // still ends at the new slots.len()
+        // 扩展按索引顺序链接的空闲 slot；旧的终止位置（之前的 slots.len()）
+        // 成为其中第一个，因此链表仍终止于新的 slots.len()
+        let len = self.slots.len() as u32;
+        if index >= len {
+            self.slots.reserve((index - len + 1) as usize);
+            for next_free in len + 1..=index + 1 {
+                self.slots.push(Slot {
+                    u: SlotUnion { next_free },
+                    version: crate::Version::new(self.fresh_generation, 0b00),
+                });
+            }
+        }
 
-        // Slot must exist (allocate_handle should have created it)
-        // slot 必须存在（allocate_handle 应该已经创建了它）
         debug_assert!(
-            (index as usize) < self.slots.len(),
-            "Invalid handle: index out of bounds"
+            self.slots[index as usize].is_vacant(),
+            "insert_at target slot is not vacant"
+        );
+        debug_assert!(
+            generation >= self.slots[index as usize].generation(),
+            "insert_at generation is older than the slot's generation"
         );
 
-        let slot = unsafe { self.slots.get_unchecked_mut(index as usize) };
+        self.unlink_free(index);
 
-        // Validate generation match (handle stores generation, not version)
-        // 验证 generation 匹配（handle 存储 generation，不是 version）
+        let slot = &mut self.slots[index as usize];
+        slot.u.value = ManuallyDrop::new(value);
+        slot.version = crate::Version::new(generation, 0b11);
+
+        self.num_elems = num_elems;
+        self.bump_epoch();
+
+        K::from_parts(
+            index,
+            generation,
+            #[cfg(debug_assertions)]
+            self.map_id,
+        )
+    }
+
+    /// Remove a vacant slot from the free list
+    ///
+    /// 从空闲列表中移除一个空闲 slot
+    fn unlink_free(&mut self, index: u32) {
+        // SAFETY: the slot is vacant, so next_free is the active field
+        let next = unsafe { self.slots[index as usize].u.next_free };
+
+        if self.free_head == index {
+            self.free_head = next;
+        } else {
+            let mut current = self.free_head;
+            while (current as usize) < self.slots.len() {
+                let slot = &mut self.slots[current as usize];
+                // SAFETY: slots on the free list are vacant
+                let following = unsafe { slot.u.next_free };
+                if following == index {
+                    slot.u.next_free = next;
+                    break;
+                }
+                current = following;
+            }
+        }
+        self.refresh_free_tail();
+    }
+
+    /// Shared body of `insert` and `insert_and_get_mut`
+    ///
+    /// Returns `None` only when the handle is rejected by the sentinel/bounds,
+    /// generation or reserved-state check; the map is then left untouched.
+    ///
+    /// `insert` 和 `insert_and_get_mut` 的共享实现
+    ///
+    /// 只有 handle 未通过 sentinel/边界、代数或预留状态检查时才返回 `None`，此时 map 保持不变
+    #[inline(always)]
+    fn insert_slot(&mut self, handle: Handle<K>, value: T) -> Option<&mut T> {
+        // The Handle is used up here, even if it is rejected below
+        // Handle 在此被用掉，即使下面拒绝了它
+        let handle = ManuallyDrop::new(handle);
+
+        #[cfg(debug_assertions)]
         debug_assert!(
-            slot.generation() == handle.generation(),
-            "Generation mismatch"
+            crate::utils::map_id_matches(self.map_id, handle.key.map_id()),
+            "Handle used with wrong map instance"
         );
 
-        // Validate slot is in Reserved state
-        // 验证 slot 处于 Reserved 状态
-        debug_assert!(slot.is_reserved(), "Handle already used or invalid state");
+        let index = handle.index();
+
+        // Reject the sentinel and out-of-bounds indices in every build profile,
+        // so the sentinel can never be overwritten. For index 0, `index - 1` wraps
+        // to u32::MAX, so a single comparison covers both cases.
+        // 在任何构建配置下都拒绝 sentinel 和越界索引，确保 sentinel 永远不会被覆盖。
+        // index 为 0 时 `index - 1` 回绕为 u32::MAX，因此一次比较即可覆盖两种情况。
+        if unlikely(index.wrapping_sub(1) as usize >= self.slots.len() - 1) {
+            return None;
+        }
+
+        // SAFETY: 0 < index < slots.len() checked above
+        let slot = unsafe { self.slots.get_unchecked(index as usize) };
+
+        // Reject stale Handles in every build profile too: writing through one
+        // would overwrite a live value or a free-list link. The generation must
+        // match and the slot must still be Reserved.
+        // 在任何构建配置下也拒绝过期的 Handle：通过它写入会覆盖存活的值或空闲列表链接。
+        // 代数必须匹配，且 slot 必须仍处于 Reserved 状态。
+        if unlikely(slot.generation() != handle.generation() || !slot.is_reserved()) {
+            return None;
+        }
+        self.bump_epoch();
+
+        // SAFETY: 0 < index < slots.len() checked above
+        let slot = unsafe { self.slots.get_unchecked_mut(index as usize) };
+
+        // Check the count before touching the slot, so a panic leaves it reserved
+        // 在修改 slot 之前检查计数，使 panic 时 slot 仍保持预留状态
+        let num_elems = self
+            .num_elems
+            .checked_add(1)
+            .expect("DeferredMap element count overflow");
 
         // Insert value and transition: reserved(0bXX01) -> occupied(0bXX11)
         // 插入值并状态转换：reserved(0bXX01) -> occupied(0bXX11)
         slot.u.value = ManuallyDrop::new(value);
         slot.version.reserved_to_occupied(); // 0bXX01 -> 0bXX11
+        debug_assert!(self.num_reserved > 0, "num_reserved underflow");
+        self.num_reserved -= 1;
+        self.num_elems = num_elems;
 
-        self.num_elems += 1;
+        // SAFETY: the slot was just made occupied
+        Some(unsafe { &mut *slot.u.value })
     }
 
-    /// Get immutable reference to value by u64 key
+    /// Get immutable reference to value by key
+    ///
+    /// 通过 key 获取值的不可变引用
     ///
-    /// 通过 u64 key 获取值的不可变引用
+    /// In debug builds this also asserts that `key` came from this map; see
+    /// `get_raw` for the same lookup without that check.
+    ///
+    /// 在 debug 构建中还会断言 `key` 来自此 map；不带该检查的相同查找请参见 `get_raw`。
     ///
     /// # Parameters
     /// - `key`: The key returned from `insert`
@@ -299,15 +940,48 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     #[inline]
     pub fn get(&self, key: K) -> Option<&T> {
         #[cfg(debug_assertions)]
-        debug_assert_eq!(
-            self.map_id,
-            key.map_id(),
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, key.map_id()),
             "Key used with wrong map instance"
         );
 
-        let index = key.index();
-        let generation = key.generation();
+        self.lookup(key.index(), key.generation().as_u32())
+    }
+
+    /// Get an owned clone of the value by key
+    ///
+    /// Shorthand for `map.get(key).cloned()`.
+    ///
+    /// 通过 key 获取值的克隆
+    ///
+    /// 等价于 `map.get(key).cloned()` 的简写。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (map, keys) = DeferredMap::<_>::from_values([String::from("a")]);
+    /// let owned: Option<String> = map.get_cloned(keys[0]);
+    /// assert_eq!(owned.as_deref(), Some("a"));
+    /// ```
+    #[inline]
+    pub fn get_cloned(&self, key: K) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get(key).cloned()
+    }
 
+    /// Resolve an (index, generation) pair to its value, with no map_id check
+    ///
+    /// A zero generation never matches, since every slot generation is at least 1.
+    ///
+    /// 将 (index, generation) 解析为值，不检查 map_id
+    ///
+    /// 代数为零永远不会匹配，因为每个 slot 的代数至少为 1。
+    #[inline(always)]
+    fn lookup(&self, index: u32, generation: u32) -> Option<&T> {
         // Bounds check
         // 边界检查
         if unlikely(index as usize >= self.slots.len()) {
@@ -319,7 +993,7 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
 
         // Fast path: check generation match and return value
         // 快速路径：检查 generation 匹配并返回值
-        if likely(slot.generation() == generation && slot.is_occupied()) {
+        if likely(slot.generation().as_u32() == generation && slot.is_occupied()) {
             // SAFETY: We've checked that slot is occupied
             Some(unsafe { &*slot.u.value })
         } else {
@@ -327,9 +1001,108 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         }
     }
 
-    /// Get mutable reference to value by u64 key
+    /// Look up a batch of keys, writing `out[i] = self.get(keys[i])`
+    ///
+    /// `out` is cleared first so the buffer can be reused across calls. While
+    /// resolving each key, the slot of a key a few positions ahead is prefetched,
+    /// which hides cache-miss latency for scattered lookups in large maps. On
+    /// targets without a prefetch instruction this is a plain loop over `get`.
+    ///
+    /// 批量查找 key，写入 `out[i] = self.get(keys[i])`
+    ///
+    /// 会先清空 `out`，以便在多次调用之间复用缓冲区。解析每个 key 的同时，会预取
+    /// 前方若干位置的 key 所在的 slot，从而在大型 map 中的分散查找时隐藏缓存未命中的延迟。
+    /// 在没有预取指令的平台上，这就是一个简单的 `get` 循环。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let h1 = map.allocate_handle();
+    /// let k1 = h1.key();
+    /// map.insert(h1, 1);
+    /// let h2 = map.allocate_handle();
+    /// let k2 = h2.key();
+    /// map.insert(h2, 2);
+    /// map.remove(k1);
+    ///
+    /// let mut out = Vec::new();
+    /// map.get_many(&[k2, k1], &mut out);
+    /// assert_eq!(out, [Some(&2), None]);
+    /// ```
+    pub fn get_many<'a>(&'a self, keys: &[K], out: &mut Vec<Option<&'a T>>) {
+        out.clear();
+        out.resize(keys.len(), None);
+        self.get_many_into(keys, out);
+    }
+
+    /// Look up a batch of keys into a caller-provided slice
+    ///
+    /// Like [`get_many`](Self::get_many), but writes `out[i] = self.get(keys[i])`
+    /// into an existing slice so hot loops never allocate. Entries of `out` past
+    /// `keys.len()` are left untouched. Returns the number of results written,
+    /// which is always `keys.len()`.
+    ///
+    /// 批量查找 key，结果写入调用者提供的切片
+    ///
+    /// 与 [`get_many`](Self::get_many) 类似，但将 `out[i] = self.get(keys[i])`
+    /// 写入已有切片，热循环中不会产生分配。`out` 中超出 `keys.len()` 的部分保持不变。
+    /// 返回写入的结果数量，总是等于 `keys.len()`。
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if `out.len() < keys.len()`.
+    ///
+    /// 当 `out.len() < keys.len()` 时 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let h1 = map.allocate_handle();
+    /// let k1 = map.insert(h1, 1);
+    /// let h2 = map.allocate_handle();
+    /// let k2 = map.insert(h2, 2);
+    /// map.remove(k1);
+    ///
+    /// let mut out = [None; 4];
+    /// assert_eq!(map.get_many_into(&[k2, k1], &mut out), 2);
+    /// assert_eq!(out, [Some(&2), None, None, None]);
+    /// ```
+    pub fn get_many_into<'a>(&'a self, keys: &[K], out: &mut [Option<&'a T>]) -> usize {
+        assert!(
+            out.len() >= keys.len(),
+            "output buffer too small: {} slots for {} keys",
+            out.len(),
+            keys.len()
+        );
+
+        let slots = self.slots.as_ptr();
+        for (i, (&key, result)) in keys.iter().zip(out.iter_mut()).enumerate() {
+            if let Some(ahead) = keys.get(i + PREFETCH_DISTANCE) {
+                // wrapping_add keeps the address computation defined for out-of-bounds keys
+                // wrapping_add 保证越界 key 的地址计算也是良定义的
+                crate::utils::prefetch_read(slots.wrapping_add(ahead.index() as usize));
+            }
+            *result = self.get(key);
+        }
+        keys.len()
+    }
+
+    /// Get mutable reference to value by key
+    ///
+    /// This only hands out a reference: it never touches version bits, the free
+    /// list or the epoch, so mutating through it cannot invalidate `key` or any
+    /// other key, and a failed lookup leaves the map exactly as it was.
     ///
-    /// 通过 u64 key 获取值的可变引用
+    /// 通过 key 获取值的可变引用
+    ///
+    /// 该方法只返回引用：它从不修改版本位、空闲列表或 epoch，因此通过它进行修改
+    /// 不会使 `key` 或任何其他 key 失效，查找失败时 map 也保持原样。
     ///
     /// # Parameters
     /// - `key`: The key returned from `insert`
@@ -363,9 +1136,8 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     #[inline]
     pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
         #[cfg(debug_assertions)]
-        debug_assert_eq!(
-            self.map_id,
-            key.map_id(),
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, key.map_id()),
             "Key used with wrong map instance"
         );
 
@@ -391,11 +1163,220 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         }
     }
 
-    /// Remove value by u64 key
+    /// Get immutable reference to value by key, reporting why the lookup failed
+    ///
+    /// On success `reason` is left untouched. On `None`, `reason` is overwritten with
+    /// the cause, letting hot paths get diagnostics without building a `Result`.
+    ///
+    /// 通过 key 获取值的不可变引用，并报告查找失败的原因
+    ///
+    /// 成功时 `reason` 保持不变。返回 `None` 时，`reason` 会被写入失败原因，
+    /// 使热路径无需构建 `Result` 即可获得诊断信息。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, GetFailure};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1]);
+    /// map.remove(keys[0]);
+    ///
+    /// let mut reason = GetFailure::OutOfBounds;
+    /// assert_eq!(map.get_or_reason(keys[0], &mut reason), None);
+    /// assert_eq!(reason, GetFailure::StaleGeneration);
+    /// ```
+    #[inline]
+    pub fn get_or_reason(&self, key: K, reason: &mut GetFailure) -> Option<&T> {
+        #[cfg(debug_assertions)]
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, key.map_id()),
+            "Key used with wrong map instance"
+        );
+
+        let Some(slot) = self.slots.get(key.index() as usize) else {
+            *reason = GetFailure::OutOfBounds;
+            return None;
+        };
+
+        if unlikely(slot.generation() != key.generation()) {
+            *reason = GetFailure::StaleGeneration;
+            None
+        } else if unlikely(!slot.is_occupied()) {
+            *reason = GetFailure::NotOccupied;
+            None
+        } else {
+            // SAFETY: We've checked that slot is occupied
+            Some(unsafe { &*slot.u.value })
+        }
+    }
+
+    /// Get mutable references to `N` distinct values at once
+    ///
+    /// Returns `None` if any key is invalid (out of bounds, stale or not occupied),
+    /// or if two keys point at the same slot. Aliasing is checked pairwise, so this
+    /// is O(N²) and meant for small `N`.
+    ///
+    /// 一次获取 `N` 个不同值的可变引用
+    ///
+    /// 如果任一 key 无效（越界、过期或未占用），或两个 key 指向同一个 slot，则返回 `None`。
+    /// 别名检查是两两进行的，复杂度为 O(N²)，适用于较小的 `N`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    ///
+    /// let [a, c] = map.get_disjoint_mut([keys[0], keys[2]]).unwrap();
+    /// std::mem::swap(a, c);
+    /// assert_eq!(map.get(keys[0]), Some(&3));
+    ///
+    /// assert!(map.get_disjoint_mut([keys[1], keys[1]]).is_none());
+    /// ```
+    pub fn get_disjoint_mut<const N: usize>(&mut self, keys: [K; N]) -> Option<[&mut T; N]> {
+        self.try_get_disjoint_mut(keys).ok()
+    }
+
+    /// Get mutable references to `N` distinct values at once, reporting which key failed
+    ///
+    /// On failure, returns the position of the first offending key in `keys` and the
+    /// reason:
+    /// - `InvalidHandle`: out of bounds, the sentinel, or reserved but not inserted yet
+    /// - `GenerationMismatch`: the key is stale
+    /// - `Aliased`: the key points at the same slot as an earlier key
+    ///
+    /// 一次获取 `N` 个不同值的可变引用，并报告哪个 key 失败
+    ///
+    /// 失败时返回 `keys` 中第一个出错 key 的位置及原因：
+    /// - `InvalidHandle`：越界、指向 sentinel，或已预留但尚未插入
+    /// - `GenerationMismatch`：key 已过期
+    /// - `Aliased`：该 key 与之前的某个 key 指向同一个 slot
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, DeferredMapError};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    ///
+    /// assert_eq!(
+    ///     map.try_get_disjoint_mut([keys[0], keys[1], keys[0]]).unwrap_err(),
+    ///     (2, DeferredMapError::Aliased)
+    /// );
+    /// ```
+    pub fn try_get_disjoint_mut<const N: usize>(
+        &mut self,
+        keys: [K; N],
+    ) -> Result<[&mut T; N], (usize, DeferredMapError)> {
+        let mut reason = GetFailure::OutOfBounds;
+        for (i, &key) in keys.iter().enumerate() {
+            if self.get_or_reason(key, &mut reason).is_none() {
+                let err = match reason {
+                    GetFailure::StaleGeneration => DeferredMapError::GenerationMismatch,
+                    GetFailure::OutOfBounds | GetFailure::NotOccupied => {
+                        DeferredMapError::InvalidHandle
+                    }
+                };
+                return Err((i, err));
+            }
+            if keys[..i].iter().any(|k| k.index() == key.index()) {
+                return Err((i, DeferredMapError::Aliased));
+            }
+        }
+
+        let slots = self.slots.as_mut_ptr();
+        // SAFETY: every key was checked to be in bounds and occupied, and all indices
+        // are pairwise distinct, so the references never alias
+        Ok(keys.map(|key| unsafe { &mut *(*slots.add(key.index() as usize)).u.value }))
+    }
+
+    /// Get mutable references to two distinct values at once
+    ///
+    /// Shorthand for `get_disjoint_mut([a, b])`. Returns `None` if either key is
+    /// invalid or both point at the same slot.
+    ///
+    /// 一次获取两个不同值的可变引用
+    ///
+    /// `get_disjoint_mut([a, b])` 的简写。如果任一 key 无效或两者指向同一个 slot，
+    /// 则返回 `None`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([10, 20]);
+    ///
+    /// let (a, b) = map.get_pair_mut(keys[0], keys[1]).unwrap();
+    /// *a += *b;
+    /// assert_eq!(map.get(keys[0]), Some(&30));
+    /// ```
+    #[inline]
+    pub fn get_pair_mut(&mut self, a: K, b: K) -> Option<(&mut T, &mut T)> {
+        let [a, b] = self.get_disjoint_mut([a, b])?;
+        Some((a, b))
+    }
+
+    /// Get mutable access to the values at `a` and `b`, allowing them to be the same entry
+    ///
+    /// Returns `One` when `a == b`, `Two` when they name distinct entries, and `None`
+    /// if either key is invalid.
+    ///
+    /// 获取 `a` 和 `b` 处值的可变访问，允许它们是同一个条目
+    ///
+    /// 当 `a == b` 时返回 `One`，当它们指向不同条目时返回 `Two`，任一 key 无效时返回 `None`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, OneOrTwo};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    ///
+    /// match map.get2_mut_or_same(keys[0], keys[0]) {
+    ///     Some(OneOrTwo::One(v)) => *v *= 10,
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(map.get(keys[0]), Some(&10));
+    /// ```
+    pub fn get2_mut_or_same(&mut self, a: K, b: K) -> Option<OneOrTwo<'_, T>> {
+        if a == b {
+            return self.get_mut(a).map(OneOrTwo::One);
+        }
+        let (a, b) = self.get_pair_mut(a, b)?;
+        Some(OneOrTwo::Two(a, b))
+    }
+
+    /// Take the value out by key, an alias for `remove`
+    ///
+    /// Reads like `Option::take`/`mem::take` at call sites that move the value out.
+    /// Behaves exactly like `remove`.
+    ///
+    /// 通过 key 取出值，`remove` 的别名
+    ///
+    /// 在移出值的调用处读起来像 `Option::take`/`mem::take`。行为与 `remove` 完全相同。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([String::from("a")]);
+    /// let owned: String = map.take(keys[0]).unwrap();
+    /// assert_eq!(owned, "a");
+    /// assert_eq!(map.take(keys[0]), None);
+    /// ```
+    #[inline]
+    pub fn take(&mut self, key: K) -> Option<T> {
+        self.remove(key)
+    }
+
+    /// Remove value by key
     ///
     /// If successful, returns the removed value and adds the slot to the free list.
     ///
-    /// 通过 u64 key 移除值
+    /// 通过 key 移除值
     ///
     /// 如果成功移除，返回被移除的值，并将该 slot 加入空闲列表
     ///
@@ -429,9 +1410,8 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     #[inline]
     pub fn remove(&mut self, key: K) -> Option<T> {
         #[cfg(debug_assertions)]
-        debug_assert_eq!(
-            self.map_id,
-            key.map_id(),
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, key.map_id()),
             "Key used with wrong map instance"
         );
 
@@ -450,23 +1430,162 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         // Fast path: check generation and occupied state
         // 快速路径：检查 generation 和占用状态
         if likely(slot.generation() == generation && slot.is_occupied()) {
-            // Take value from slot
-            // 从 slot 中取出值
-            let value = unsafe { ManuallyDrop::take(&mut slot.u.value) };
+            // SAFETY: index is in bounds and the slot is occupied
+            Some(unsafe { self.vacate(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Remove many values by key, returning one result per key in order
+    ///
+    /// Each entry is `Some(value)` if its key was live at that point in the slice,
+    /// `None` otherwise. Duplicate keys are handled naturally: the first occurrence
+    /// removes the value and bumps the generation, so later occurrences get `None`.
+    ///
+    /// 通过 key 批量删除值，按顺序为每个 key 返回一个结果
+    ///
+    /// 如果 key 在切片中该位置时仍然存活，则对应项为 `Some(value)`，否则为 `None`。
+    /// 重复的 key 会被自然处理：第一次出现时删除值并递增代数，因此之后的出现返回 `None`。
+    ///
+    /// Unlike a loop over `remove`, the element count is updated and the epoch
+    /// bumped once for the whole batch; each freed slot is still linked onto the
+    /// free list in O(1), in slice order.
+    ///
+    /// 与循环调用 `remove` 不同，元素计数只针对整个批次更新一次，epoch 也只递增一次；
+    /// 每个被释放的 slot 仍按切片顺序以 O(1) 链入空闲列表。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    ///
+    /// let removed = map.bulk_remove(&[keys[2], keys[0], keys[2]]);
+    /// assert_eq!(removed, [Some(3), Some(1), None]);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn bulk_remove(&mut self, keys: &[K]) -> Vec<Option<T>> {
+        // Check every key up front, so nothing below can panic mid-batch
+        // 预先检查所有 key，使下面的批处理过程中不会 panic
+        #[cfg(debug_assertions)]
+        for key in keys {
+            debug_assert!(
+                crate::utils::map_id_matches(self.map_id, key.map_id()),
+                "Key used with wrong map instance"
+            );
+        }
+
+        let mut removed = Vec::with_capacity(keys.len());
+        let mut count = 0;
+        for &key in keys {
+            let index = key.index();
+            let value = match self.slots.get_mut(index as usize) {
+                Some(slot)
+                    if index != 0
+                        && slot.generation() == key.generation()
+                        && slot.is_occupied() =>
+                {
+                    slot.version.occupied_to_vacant();
+                    // SAFETY: the slot was occupied and has just been marked vacant
+                    let value = unsafe { ManuallyDrop::take(&mut slot.u.value) };
+                    self.push_free(index);
+                    count += 1;
+                    Some(value)
+                }
+                _ => None,
+            };
+            removed.push(value);
+        }
 
-            // Add this slot to free list head
-            // 将此 slot 加入空闲列表头部
-            slot.u.next_free = self.free_head;
-            self.free_head = index;
+        if count > 0 {
+            debug_assert!(
+                self.num_elems >= count,
+                "DeferredMap element count underflow"
+            );
+            self.num_elems -= count;
+            self.bump_epoch();
+        }
+        removed
+    }
 
-            // Transition: occupied(0bXX11) -> vacant(0bYY00, next generation)
-            // 状态转换：occupied(0bXX11) -> vacant(0bYY00，下一代）
-            slot.version.occupied_to_vacant();
+    /// Remove value by key, keeping the option to put it back under the same key
+    ///
+    /// The slot is freed and the key invalidated exactly as with `remove`, but the
+    /// value is returned inside a [`RemovedGuard`] whose `restore` reverses the
+    /// removal as long as the slot has not been reused. Useful for rollback.
+    ///
+    /// 通过 key 删除值，同时保留以相同 key 放回的可能
+    ///
+    /// slot 的释放和 key 的失效与 `remove` 完全相同，但值会包装在 [`RemovedGuard`] 中返回，
+    /// 只要 slot 尚未被复用，其 `restore` 就能撤销这次删除。适用于回滚场景。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1]);
+    ///
+    /// let guard = map.remove_guarded(keys[0]).unwrap();
+    /// assert_eq!(guard.commit(), 1);
+    /// assert_eq!(map.get(keys[0]), None);
+    /// ```
+    #[inline]
+    pub fn remove_guarded(&mut self, key: K) -> Option<RemovedGuard<T, K>> {
+        let value = self.remove(key)?;
+        Some(RemovedGuard { key, value })
+    }
 
-            self.num_elems -= 1;
-            Some(value)
-        } else {
-            None
+    /// Remove value by key only if the predicate returns `true` for it
+    ///
+    /// If the key is invalid or the predicate returns `false`, the map is unchanged.
+    ///
+    /// 仅当谓词对值返回 `true` 时才通过 key 移除值
+    ///
+    /// 如果 key 无效或谓词返回 `false`，map 保持不变
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value to check
+    /// - `pred`: Called with the live value; decides whether to remove it
+    ///
+    /// # Returns
+    /// - `Some(T)`: The removed value
+    /// - `None`: If key is invalid or the predicate returned `false`
+    ///
+    /// # 参数
+    /// - `key`: 要检查的值的 key
+    /// - `pred`: 以存活的值调用，决定是否移除
+    ///
+    /// # 返回值
+    /// - `Some(T)`: 被移除的值
+    /// - `None`: 如果 key 无效或谓词返回 `false`
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    /// map.insert(handle, 42);
+    ///
+    /// assert_eq!(map.remove_if(key, |v| *v > 100), None);
+    /// assert_eq!(map.remove_if(key, |v| *v == 42), Some(42));
+    /// assert_eq!(map.get(key), None);
+    /// ```
+    pub fn remove_if<F>(&mut self, key: K, pred: F) -> Option<T>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        match self.get(key) {
+            Some(value) if pred(value) => {
+                // SAFETY: get() validated bounds and occupancy
+                Some(unsafe { self.vacate(key.index()) })
+            }
+            _ => None,
         }
     }
 
@@ -499,10 +1618,13 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     /// map.release_handle(handle);
     /// ```
     pub fn release_handle(&mut self, handle: Handle<K>) {
+        // The Handle is used up here, even if it is rejected below
+        // Handle 在此被用掉，即使下面拒绝了它
+        let handle = ManuallyDrop::new(handle);
+
         #[cfg(debug_assertions)]
-        debug_assert_eq!(
-            self.map_id,
-            handle.key.map_id(),
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, handle.key.map_id()),
             "Handle used with wrong map instance"
         );
 
@@ -520,7 +1642,17 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
             "Invalid handle: index out of bounds"
         );
 
-        let slot = &mut self.slots[index as usize];
+        // Reject the sentinel and out-of-bounds indices in every build profile,
+        // so the sentinel can never be overwritten. For index 0, `index - 1` wraps
+        // to u32::MAX, so a single comparison covers both cases.
+        // 在任何构建配置下都拒绝 sentinel 和越界索引，确保 sentinel 永远不会被覆盖。
+        // index 为 0 时 `index - 1` 回绕为 u32::MAX，因此一次比较即可覆盖两种情况。
+        if unlikely(index.wrapping_sub(1) as usize >= self.slots.len() - 1) {
+            return;
+        }
+
+        // SAFETY: 0 < index < slots.len() checked above
+        let slot = unsafe { self.slots.get_unchecked_mut(index as usize) };
 
         // Validate generation match (handle stores generation, not version)
         // 验证 generation 匹配（handle 存储 generation，不是 version）
@@ -533,14 +1665,439 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         // 验证 slot 处于 Reserved 状态
         debug_assert!(slot.is_reserved(), "Handle already used or invalid state");
 
-        // Add this slot to free list head
-        // 将此 slot 加入空闲列表头部
-        slot.u.next_free = self.free_head;
-        self.free_head = index;
+        // Reject stale Handles in every build profile too: releasing one would
+        // push a live or already free slot onto the free list.
+        // 在任何构建配置下也拒绝过期的 Handle：释放它会把存活或已空闲的 slot 放入空闲列表。
+        if unlikely(slot.generation() != handle_generation || !slot.is_reserved()) {
+            return;
+        }
 
         // Transition: reserved(0bXX01) -> vacant(0bYY00, next generation)
         // 状态转换：reserved(0bXX01) -> vacant(0bYY00，下一代）
         slot.version.reserved_to_vacant();
+        debug_assert!(self.num_reserved > 0, "num_reserved underflow");
+        self.num_reserved -= 1;
+
+        // Add this slot to free list head
+        // 将此 slot 加入空闲列表头部
+        self.push_free(index);
+        self.bump_epoch();
+    }
+
+    /// Release every Reserved slot, returning how many were reclaimed
+    ///
+    /// Reclaims slots of leaked Handles (allocated, then dropped without `insert` or
+    /// `release_handle`). Each Reserved slot moves to vacant with the next generation
+    /// and goes back on the free list. Any Handle still held for those slots becomes
+    /// stale and its `insert` fails cleanly, leaving the map untouched:
+    /// `try_insert` and `try_release_handle` return `GenerationMismatch`, and
+    /// `insert` panics instead of storing the value.
+    ///
+    /// 释放所有 Reserved slot，返回回收的数量
+    ///
+    /// 回收泄漏的 Handle 对应的 slot（已分配但未 `insert` 或 `release_handle` 就被 drop）。
+    /// 每个 Reserved slot 以下一代转为空闲并归还到空闲列表。仍持有的这些 slot 的 Handle
+    /// 会失效，其 `insert` 会干净地失败且 map 保持不变：`try_insert` 和
+    /// `try_release_handle` 返回 `GenerationMismatch`，`insert` 则会 panic 而不会存储该值。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, DeferredMapError};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let kept = map.allocate_handle();
+    /// map.insert(kept, 1);
+    ///
+    /// let _leaked = map.allocate_handle();
+    /// let stale = map.allocate_handle();
+    /// assert_eq!(map.release_all_reserved(), 2);
+    /// assert_eq!(map.reserved_len(), 0);
+    ///
+    /// assert_eq!(map.try_insert(stale, 2), Err(DeferredMapError::GenerationMismatch));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn release_all_reserved(&mut self) -> usize {
+        if self.num_reserved == 0 {
+            return 0;
+        }
+
+        let mut released = 0;
+        for index in 1..self.slots.len() {
+            let slot = &mut self.slots[index];
+            if slot.is_reserved() {
+                slot.version.reserved_to_vacant();
+                self.push_free(index as u32);
+                released += 1;
+            }
+        }
+
+        self.num_reserved = 0;
+        self.bump_epoch();
+        released
+    }
+
+    /// Take the value out of an occupied slot and return the slot to the free list
+    ///
+    /// 从占用的 slot 中取出值，并将 slot 归还到空闲列表
+    ///
+    /// # Safety
+    /// `index` must be in bounds and the slot must be occupied.
+    ///
+    /// `index` 必须在范围内，且 slot 必须处于占用状态。
+    ///
+    /// The slot is marked vacant in the same step the value is moved out, before
+    /// anything that could panic runs, so an unwind can never leave an occupied slot
+    /// whose value has already been taken (which the slot's `Drop` would drop again).
+    ///
+    /// slot 在值被移出的同一步骤中被标记为空闲，早于任何可能 panic 的操作，
+    /// 因此展开时绝不会留下值已被取出却仍处于占用状态的 slot（slot 的 `Drop` 会再次 drop 它）。
+    #[inline(always)]
+    unsafe fn vacate(&mut self, index: u32) -> T {
+        let slot = unsafe { self.slots.get_unchecked_mut(index as usize) };
+
+        // Transition: occupied(0bXX11) -> vacant(0bYY00, next generation)
+        // 状态转换：occupied(0bXX11) -> vacant(0bYY00，下一代）
+        slot.version.occupied_to_vacant();
+
+        // Take value from slot; neither step can panic
+        // 从 slot 中取出值；这两步都不会 panic
+        let value = unsafe { ManuallyDrop::take(&mut slot.u.value) };
+
+        // An occupied slot was just found, so the count is at least 1
+        // 刚找到一个占用的 slot，因此计数至少为 1
+        debug_assert!(self.num_elems > 0, "DeferredMap element count underflow");
+        self.num_elems -= 1;
+
+        // Add this slot to free list head
+        // 将此 slot 加入空闲列表头部
+        self.push_free(index);
+        self.bump_epoch();
+        value
+    }
+
+    /// Check every internal invariant, returning a description of the first violation
+    ///
+    /// Verifies that:
+    /// - slot 0 is the vacant sentinel;
+    /// - the free list visits each slot at most once, contains only vacant slots,
+    ///   covers every vacant slot and terminates exactly at `slots.len()`;
+    /// - the element and reserved counters match the occupied and reserved slots;
+    /// - every generation is non-zero and fits in 30 bits.
+    ///
+    /// O(capacity); meant for tests and fuzzing. Available under `cfg(test)` or the
+    /// `validate` feature.
+    ///
+    /// 检查所有内部不变量，返回第一个违反项的描述
+    ///
+    /// 验证：
+    /// - slot 0 是空闲的 sentinel；
+    /// - 空闲列表对每个 slot 最多访问一次，只包含空闲 slot，覆盖所有空闲 slot，
+    ///   并恰好终止于 `slots.len()`；
+    /// - 元素计数和预留计数与占用和预留的 slot 一致；
+    /// - 每个代数都非零且能用 30 位表示。
+    ///
+    /// 复杂度为 O(capacity)；用于测试和模糊测试。在 `cfg(test)` 或 `validate` feature 下可用。
+    #[cfg(any(test, feature = "validate"))]
+    pub fn validate(&self) -> Result<(), String> {
+        let len = self.slots.len();
+        match self.slots.first() {
+            Some(sentinel) if sentinel.is_vacant() => {}
+            _ => return Err("slot 0 is not a vacant sentinel".to_string()),
+        }
+
+        let (mut occupied, mut reserved, mut vacant) = (0usize, 0usize, 0usize);
+        for (index, slot) in self.slots.iter().enumerate() {
+            let generation = slot.generation().get();
+            if generation == 0 || generation > crate::Generation::MAX.get() {
+                return Err(format!("slot {index} has invalid generation {generation}"));
+            }
+            if index == 0 {
+                continue;
+            }
+            match slot.version.try_to_state() {
+                Some(crate::VersionState::Occupied) => occupied += 1,
+                Some(crate::VersionState::Reserved) => reserved += 1,
+                Some(crate::VersionState::Vacant) => vacant += 1,
+                None => {
+                    let bits = slot.version.state();
+                    return Err(format!("slot {index} has invalid state bits {bits:#b}"));
+                }
+            }
+        }
+
+        let mut on_free_list = vec![false; len];
+        let mut current = self.free_head as usize;
+        let mut free_len = 0usize;
+        let mut last = None;
+        while current < len {
+            if current == 0 {
+                return Err("free list contains the sentinel".to_string());
+            }
+            if std::mem::replace(&mut on_free_list[current], true) {
+                return Err(format!("free list revisits slot {current}"));
+            }
+            let slot = &self.slots[current];
+            if !slot.is_vacant() {
+                return Err(format!("free list contains non-vacant slot {current}"));
+            }
+            free_len += 1;
+            last = Some(current as u32);
+            // SAFETY: the slot is vacant, so next_free is the active field
+            current = unsafe { slot.u.next_free } as usize;
+        }
+        if let Some(last) = last
+            && self.fifo
+            && last != self.free_tail
+        {
+            return Err(format!(
+                "free list ends at slot {last} but free_tail is {}",
+                self.free_tail
+            ));
+        }
+        if current != len {
+            return Err(format!("free list terminates at {current}, expected {len}"));
+        }
+        if free_len != vacant {
+            return Err(format!(
+                "free list holds {free_len} slots but {vacant} slots are vacant"
+            ));
+        }
+
+        if occupied != self.num_elems as usize {
+            return Err(format!(
+                "num_elems is {} but {occupied} slots are occupied",
+                self.num_elems
+            ));
+        }
+        if reserved != self.num_reserved as usize {
+            return Err(format!(
+                "num_reserved is {} but {reserved} slots are reserved",
+                self.num_reserved
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether at least half of the slots are occupied
+    ///
+    /// Sampled once when an iterator is created to pick its branch hint.
+    ///
+    /// 是否至少一半的 slot 被占用
+    ///
+    /// 在创建迭代器时采样一次，用于选择分支提示。
+    #[inline(always)]
+    fn is_dense(&self) -> bool {
+        self.num_elems as usize * 2 >= self.slots.len()
+    }
+
+    /// Record a structural mutation
+    ///
+    /// 记录一次结构性修改
+    #[inline(always)]
+    fn bump_epoch(&mut self) {
+        self.epoch = self.epoch.wrapping_add(1);
+    }
+
+    /// Push a vacant slot onto the free list
+    ///
+    /// 将空闲 slot 压入空闲列表
+    #[inline(always)]
+    fn push_free(&mut self, index: u32) {
+        debug_assert!(self.slots[index as usize].is_vacant());
+        let end = self.slots.len() as u32;
+        if self.fifo && self.free_head < end {
+            // Append behind the tail so the oldest vacated slot is reused first
+            // 追加到尾部之后，使最早空出的 slot 最先被复用
+            // SAFETY: index is in bounds (see below) and free_tail is on the non-empty list
+            unsafe {
+                self.slots.get_unchecked_mut(index as usize).u.next_free = end;
+                self.slots
+                    .get_unchecked_mut(self.free_tail as usize)
+                    .u
+                    .next_free = index;
+            }
+            self.free_tail = index;
+            return;
+        }
+
+        // SAFETY: callers pass the in-bounds index of a slot they just made vacant
+        let slot = unsafe { self.slots.get_unchecked_mut(index as usize) };
+        slot.u.next_free = self.free_head;
+        self.free_head = index;
+        if self.fifo {
+            // The list was empty, so the new head is also the tail
+            // 列表原本为空，因此新的头部同时也是尾部
+            self.free_tail = index;
+        }
+    }
+
+    /// Recompute `free_tail` by walking the free list (FIFO maps only)
+    ///
+    /// 遍历空闲列表重新计算 `free_tail`（仅限 FIFO map）
+    fn refresh_free_tail(&mut self) {
+        if !self.fifo {
+            return;
+        }
+        let mut current = self.free_head;
+        while (current as usize) < self.slots.len() {
+            self.free_tail = current;
+            // SAFETY: slots on the free list are vacant
+            current = unsafe { self.slots[current as usize].u.next_free };
+        }
+    }
+
+    /// Reserve room for one more slot according to the growth policy
+    ///
+    /// 按照增长策略为再追加一个 slot 预留空间
+    #[inline(always)]
+    fn reserve_for_growth(&mut self) {
+        if self.slots.len() < self.slots.capacity() {
+            return;
+        }
+        match self.growth {
+            // push below doubles on its own
+            // 下面的 push 会自行倍增
+            GrowthPolicy::Double => {}
+            GrowthPolicy::Linear(step) => self.slots.reserve_exact(step.max(1)),
+            GrowthPolicy::Exact => self.slots.reserve_exact(1),
+        }
+    }
+
+    /// Check that a Handle refers to a Reserved slot of this map
+    ///
+    /// 检查 Handle 是否指向本 map 中处于 Reserved 状态的 slot
+    #[inline]
+    fn check_handle(&self, handle: &Handle<K>) -> Result<(), DeferredMapError> {
+        let index = handle.index();
+
+        // Sentinel and out-of-bounds indices are never valid
+        // sentinel 和越界索引永远无效
+        if unlikely(index == 0) {
+            return Err(DeferredMapError::InvalidHandle);
+        }
+        let Some(slot) = self.slots.get(index as usize) else {
+            return Err(DeferredMapError::InvalidHandle);
+        };
+
+        if unlikely(slot.generation() != handle.generation()) {
+            return Err(DeferredMapError::GenerationMismatch);
+        }
+        if unlikely(!slot.is_reserved()) {
+            return Err(DeferredMapError::NotReserved);
+        }
+        Ok(())
+    }
+
+    /// Insert value using Handle, validating the Handle first
+    ///
+    /// Unlike `insert`, an invalid Handle is reported as an error instead of a
+    /// panic. On success the key of the inserted value is returned, like `insert`;
+    /// on error the value is dropped.
+    ///
+    /// 使用 Handle 插入值，并先验证 Handle
+    ///
+    /// 与 `insert` 不同，无效的 Handle 会以错误返回而不是 panic。成功时与 `insert`
+    /// 一样返回插入值的 key；出错时值会被丢弃。
+    ///
+    /// # Errors
+    /// - `InvalidHandle`: sentinel or out-of-bounds index
+    /// - `GenerationMismatch`: the slot has moved on to another generation
+    /// - `NotReserved`: the handle was already inserted or released
+    ///
+    /// # 错误
+    /// - `InvalidHandle`：sentinel 或越界索引
+    /// - `GenerationMismatch`：slot 已进入其他代
+    /// - `NotReserved`：handle 已被插入或释放
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let key = map.try_insert(handle, 42).unwrap();
+    /// assert_eq!(map.get(key), Some(&42));
+    /// ```
+    pub fn try_insert(&mut self, handle: Handle<K>, value: T) -> Result<K, DeferredMapError> {
+        #[cfg(debug_assertions)]
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, handle.key.map_id()),
+            "Handle used with wrong map instance"
+        );
+
+        if let Err(err) = self.check_handle(&handle) {
+            handle.into_key();
+            return Err(err);
+        }
+        Ok(self.insert(handle, value))
+    }
+
+    /// Release an unused Handle, validating the Handle first
+    ///
+    /// 释放未使用的 Handle，并先验证 Handle
+    ///
+    /// # Errors
+    /// Same as `try_insert`.
+    ///
+    /// # 错误
+    /// 与 `try_insert` 相同。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::<i32>::new();
+    /// let handle = map.allocate_handle();
+    /// assert!(map.try_release_handle(handle).is_ok());
+    /// ```
+    pub fn try_release_handle(&mut self, handle: Handle<K>) -> Result<(), DeferredMapError> {
+        #[cfg(debug_assertions)]
+        debug_assert!(
+            crate::utils::map_id_matches(self.map_id, handle.key.map_id()),
+            "Handle used with wrong map instance"
+        );
+
+        if let Err(err) = self.check_handle(&handle) {
+            handle.into_key();
+            return Err(err);
+        }
+        self.release_handle(handle);
+        Ok(())
+    }
+
+    /// Check whether a Handle can still be inserted into or released from this map
+    ///
+    /// Takes the Handle by reference, so it can be checked without being consumed.
+    /// Returns `true` only if its slot is in bounds, at the same generation and still
+    /// Reserved; `false` once it has been inserted or released.
+    ///
+    /// 检查 Handle 是否仍可插入本 map 或从本 map 释放
+    ///
+    /// 以引用方式接收 Handle，因此检查时不会消耗它。只有当其 slot 在范围内、
+    /// 代数相同且仍处于 Reserved 状态时才返回 `true`；被插入或释放后返回 `false`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// assert!(map.handle_is_valid(&handle));
+    ///
+    /// map.insert(handle, 1);
+    /// ```
+    #[inline]
+    pub fn handle_is_valid(&self, handle: &Handle<K>) -> bool {
+        #[cfg(debug_assertions)]
+        if !crate::utils::map_id_matches(self.map_id, handle.key().map_id()) {
+            return false;
+        }
+
+        self.check_handle(handle).is_ok()
     }
 
     /// Check if key exists
@@ -578,10 +2135,123 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         self.get(key).is_some()
     }
 
+    /// Check whether every key in `keys` is live
+    ///
+    /// Short-circuits on the first missing key and prefetches upcoming slots like
+    /// `get_many`. Returns `true` for an empty slice.
+    ///
+    /// 检查 `keys` 中的每个 key 是否都存活
+    ///
+    /// 遇到第一个缺失的 key 时立即返回，并像 `get_many` 一样预取后续的 slot。
+    /// 空切片返回 `true`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// assert!(map.contains_all(&keys));
+    /// map.remove(keys[1]);
+    /// assert!(!map.contains_all(&keys));
+    /// ```
+    #[inline]
+    pub fn contains_all(&self, keys: &[K]) -> bool {
+        !self.any_key_matches(keys, false)
+    }
+
+    /// Check whether at least one key in `keys` is live
+    ///
+    /// Short-circuits on the first live key and prefetches upcoming slots like
+    /// `get_many`. Returns `false` for an empty slice.
+    ///
+    /// 检查 `keys` 中是否至少有一个 key 存活
+    ///
+    /// 遇到第一个存活的 key 时立即返回，并像 `get_many` 一样预取后续的 slot。
+    /// 空切片返回 `false`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    /// map.remove(keys[0]);
+    /// assert!(map.contains_any(&keys));
+    /// map.remove(keys[1]);
+    /// assert!(!map.contains_any(&keys));
+    /// ```
+    #[inline]
+    pub fn contains_any(&self, keys: &[K]) -> bool {
+        self.any_key_matches(keys, true)
+    }
+
+    /// Whether `contains_key(key) == live` for some key, stopping at the first match
+    ///
+    /// 是否存在某个 key 满足 `contains_key(key) == live`，在第一次匹配时停止
+    fn any_key_matches(&self, keys: &[K], live: bool) -> bool {
+        let slots = self.slots.as_ptr();
+        for (i, &key) in keys.iter().enumerate() {
+            if let Some(ahead) = keys.get(i + PREFETCH_DISTANCE) {
+                crate::utils::prefetch_read(slots.wrapping_add(ahead.index() as usize));
+            }
+            if self.contains_key(key) == live {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Recover the current key of the occupied slot at `index`
+    ///
+    /// Returns the key encoding `index` and the slot's live generation, or `None` if
+    /// the slot is the sentinel, out of bounds, vacant or only reserved.
+    ///
+    /// 恢复 `index` 处占用 slot 的当前 key
+    ///
+    /// 返回编码了 `index` 和该 slot 当前代数的 key；如果该 slot 是 sentinel、越界、
+    /// 空闲或仅被预留，则返回 `None`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Key};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    /// map.insert(handle, 42);
+    ///
+    /// assert_eq!(map.key_at(key.index()), Some(key));
+    /// assert_eq!(map.key_at(0), None);
+    /// ```
+    #[inline]
+    pub fn key_at(&self, index: u32) -> Option<K> {
+        // The sentinel is never occupied, so index 0 needs no special case
+        // sentinel 永远不会被占用，因此索引 0 无需特殊处理
+        let slot = self.slots.get(index as usize)?;
+        if slot.is_occupied() {
+            Some(K::from_parts(
+                index,
+                slot.generation(),
+                #[cfg(debug_assertions)]
+                self.map_id,
+            ))
+        } else {
+            None
+        }
+    }
+
     /// Return the number of valid elements
     ///
+    /// Only occupied slots are counted: handles that were allocated but not yet
+    /// inserted or released are not included. See `len_including_reserved`.
+    ///
     /// 返回有效元素的数量
     ///
+    /// 只统计占用的 slot：已分配但尚未插入或释放的 handle 不计入其中。
+    /// 参见 `len_including_reserved`。
+    ///
     /// # Examples (示例)
     ///
     /// ```
@@ -601,59 +2271,322 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
 
     /// Check if the map is empty
     ///
+    /// Equivalent to `len() == 0`: a map holding only outstanding reserved handles
+    /// is still empty. Use `len_including_reserved() == 0` to check that no handle
+    /// is outstanding either.
+    ///
     /// 检查是否为空
     ///
+    /// 等价于 `len() == 0`：只持有未完成预留 handle 的 map 仍然为空。
+    /// 使用 `len_including_reserved() == 0` 可同时检查没有未完成的 handle。
+    ///
     /// # Examples (示例)
     ///
     /// ```
     /// use deferred_map::DeferredMap;
     ///
-    /// let map: DeferredMap<i32> = DeferredMap::new();
+    /// let mut map: DeferredMap<i32> = DeferredMap::new();
     /// assert!(map.is_empty());
+    ///
+    /// let _handle = map.allocate_handle();
+    /// assert!(map.is_empty());
+    /// assert_eq!(map.len_including_reserved(), 1);
     /// ```
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.num_elems == 0
     }
 
-    /// Return capacity (number of allocated slots, excluding sentinel)
+    /// Return the number of occupied slots plus outstanding reserved handles
+    ///
+    /// A reserved handle counts until it is inserted (then it counts as an element)
+    /// or released.
+    ///
+    /// 返回占用的 slot 数量加上未完成的预留 handle 数量
     ///
-    /// 返回容量（已分配的 slot 数量，不包括 sentinel）
+    /// 预留的 handle 在被插入（之后计为元素）或释放之前都会被计入。
     ///
     /// # Examples (示例)
     ///
     /// ```
     /// use deferred_map::DeferredMap;
     ///
-    /// let map: DeferredMap<i32> = DeferredMap::with_capacity(10);
-    /// assert!(map.capacity() >= 10);
+    /// let mut map = DeferredMap::new();
+    /// let h1 = map.allocate_handle();
+    /// let h2 = map.allocate_handle();
+    /// assert_eq!(map.len(), 0);
+    /// assert_eq!(map.len_including_reserved(), 2);
+    ///
+    /// map.insert(h1, 1);
+    /// map.release_handle(h2);
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.len_including_reserved(), 1);
     /// ```
     #[inline]
-    pub fn capacity(&self) -> usize {
-        // Subtract sentinel slot
-        // 减去 sentinel slot
-        self.slots.capacity().saturating_sub(1)
+    pub fn len_including_reserved(&self) -> usize {
+        self.num_elems as usize + self.num_reserved as usize
     }
 
-    /// Clear all elements
+    /// Return the number of outstanding reserved slots
     ///
-    /// 清空所有元素
+    /// Counts Handles that were allocated but not yet inserted or released.
+    ///
+    /// 返回未完成的预留 slot 数量
+    ///
+    /// 统计已分配但尚未插入或释放的 Handle。
     ///
     /// # Examples (示例)
     ///
     /// ```
     /// use deferred_map::DeferredMap;
     ///
-    /// let mut map = DeferredMap::new();
+    /// let mut map = DeferredMap::<i32>::new();
     /// let handle = map.allocate_handle();
-    /// map.insert(handle, 42);
+    /// assert_eq!(map.reserved_len(), 1);
     ///
-    /// map.clear();
-    /// assert!(map.is_empty());
+    /// map.insert(handle, 1);
+    /// assert_eq!(map.reserved_len(), 0);
     /// ```
     #[inline]
-    pub fn clear(&mut self) {
-        self.slots.clear();
+    pub fn reserved_len(&self) -> usize {
+        self.num_reserved as usize
+    }
+
+    /// Assert that exactly `outstanding` Handles are still reserved
+    ///
+    /// Pass the number of Handles the caller knowingly holds; any extra reserved
+    /// slot belongs to a Handle that was dropped or forgotten. In debug builds the
+    /// panic message also reports [`dropped_unused_handles`](crate::dropped_unused_handles)
+    /// for the current thread.
+    ///
+    /// 断言恰好有 `outstanding` 个 Handle 仍处于保留状态
+    ///
+    /// 传入调用者明知持有的 Handle 数量；多出的保留 slot 属于被 drop 或 forget 的 Handle。
+    /// 在 debug 构建中，panic 信息还会报告当前线程的
+    /// [`dropped_unused_handles`](crate::dropped_unused_handles)。
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if `reserved_len() != outstanding`.
+    ///
+    /// 当 `reserved_len() != outstanding` 时 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::<i32>::new();
+    /// let pending = map.allocate_handle();
+    /// map.assert_no_leaked_handles(1);
+    ///
+    /// map.insert(pending, 1);
+    /// map.assert_no_leaked_handles(0);
+    /// ```
+    #[track_caller]
+    pub fn assert_no_leaked_handles(&self, outstanding: usize) {
+        let reserved = self.reserved_len();
+        assert!(
+            reserved == outstanding,
+            "leaked handles: {} reserved slots but {} outstanding handles \
+             ({} handles dropped unused on this thread)",
+            reserved,
+            outstanding,
+            crate::dropped_unused_handles()
+        );
+    }
+
+    /// Count slots by state
+    ///
+    /// Scans every slot, so this is O(capacity); intended for diagnostics such as
+    /// capacity tuning or hunting leaked Handles.
+    ///
+    /// 按状态统计 slot
+    ///
+    /// 会扫描所有 slot，因此复杂度为 O(capacity)；用于容量调优或排查泄漏的 Handle
+    /// 等诊断场景。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SlotStats};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.remove(keys[0]);
+    /// let _pending = map.allocate_handle();
+    ///
+    /// assert_eq!(
+    ///     map.slot_stats(),
+    ///     SlotStats { occupied: 2, reserved: 1, vacant: 0, total: 3 }
+    /// );
+    /// ```
+    pub fn slot_stats(&self) -> SlotStats {
+        let mut stats = SlotStats {
+            total: self.slots.len().saturating_sub(1),
+            ..SlotStats::default()
+        };
+
+        for slot in self.slots.iter().skip(1) {
+            if slot.is_occupied() {
+                stats.occupied += 1;
+            } else if slot.is_reserved() {
+                stats.reserved += 1;
+            } else {
+                stats.vacant += 1;
+            }
+        }
+        stats
+    }
+
+    /// Return the share of capacity holding values, `len / capacity`
+    ///
+    /// Uses `capacity()` (heap capacity without the sentinel), treating a zero
+    /// capacity as 1, so an empty unallocated map reports `0.0`.
+    ///
+    /// 返回容量中持有值的比例，即 `len / capacity`
+    ///
+    /// 使用 `capacity()`（不含 sentinel 的堆容量），容量为零时按 1 处理，因此未分配的空 map 返回 `0.0`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map: DeferredMap<i32> = DeferredMap::with_capacity(4);
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, 1);
+    /// assert!(map.utilization() > 0.0 && map.utilization() <= 0.25);
+    /// ```
+    #[inline]
+    pub fn utilization(&self) -> f64 {
+        self.len() as f64 / self.capacity().max(1) as f64
+    }
+
+    /// Return the share of capacity not holding values, `(capacity - len) / capacity`
+    ///
+    /// The complement of `utilization` (for any non-zero capacity); a high value
+    /// suggests compacting or shrinking.
+    ///
+    /// 返回容量中未持有值的比例，即 `(capacity - len) / capacity`
+    ///
+    /// 与 `utilization` 互补（容量非零时）；值较高时建议压缩或收缩。
+    #[inline]
+    pub fn fragmentation(&self) -> f64 {
+        let capacity = self.capacity();
+        capacity.saturating_sub(self.len()) as f64 / capacity.max(1) as f64
+    }
+
+    /// Return the structural mutation counter
+    ///
+    /// The epoch advances on every `insert`, `remove`, `release_handle`, `retain`,
+    /// `clear` and `clone_from`, and stays constant across reads and `get_mut`.
+    /// Comparing epochs is a cheap way to tell whether a cached view of the map
+    /// (e.g. collected keys or raw indices) may be out of date.
+    ///
+    /// 返回结构性修改计数器
+    ///
+    /// 每次 `insert`、`remove`、`release_handle`、`retain`、`clear` 和 `clone_from`
+    /// 都会使 epoch 前进，读取和 `get_mut` 不会改变它。比较 epoch 可以低成本地判断
+    /// map 的缓存视图（例如收集的 key 或原始索引）是否可能已过期。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let before = map.epoch();
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, 1);
+    /// assert_ne!(map.epoch(), before);
+    /// ```
+    #[inline]
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Return capacity (number of slots the map can hold without reallocating,
+    /// excluding sentinel)
+    ///
+    /// Like `Vec::capacity`, this is the heap capacity of the slot storage, not the
+    /// number of slots handed out so far. It is identical to `reserved_capacity`.
+    ///
+    /// 返回容量（无需重新分配即可容纳的 slot 数量，不包括 sentinel）
+    ///
+    /// 与 `Vec::capacity` 一样，这是 slot 存储的堆容量，而不是已分配出去的 slot 数量。
+    /// 与 `reserved_capacity` 相同。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let map: DeferredMap<i32> = DeferredMap::with_capacity(10);
+    /// assert!(map.capacity() >= 10);
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.reserved_capacity()
+    }
+
+    /// Return the heap capacity of the slot storage, excluding sentinel
+    ///
+    /// 返回 slot 存储的堆容量，不包括 sentinel
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map: DeferredMap<i32> = DeferredMap::with_capacity(10);
+    /// assert!(map.reserved_capacity() >= 10);
+    ///
+    /// // Allocating handles within the reserved capacity does not grow it
+    /// // 在预留容量内分配 handle 不会使其增长
+    /// let before = map.reserved_capacity();
+    /// let _handles: Vec<_> = (0..10).map(|_| map.allocate_handle()).collect();
+    /// assert_eq!(map.reserved_capacity(), before);
+    /// ```
+    #[inline]
+    pub fn reserved_capacity(&self) -> usize {
+        // Subtract sentinel slot
+        // 减去 sentinel slot
+        self.slots.capacity().saturating_sub(1)
+    }
+
+    /// Clear all elements
+    ///
+    /// The backing allocation is kept for reuse; see `clear_and_shrink` to release it.
+    ///
+    /// All slots are discarded, so the map starts a fresh key space: generations
+    /// restart at `Generation::MIN`, and a key or Handle from before the clear may
+    /// resolve to an entry inserted afterwards. Drop old keys when clearing, or use
+    /// `retain(|_, _| false)` to empty the map while keeping generations.
+    ///
+    /// 清空所有元素
+    ///
+    /// 底层分配会被保留以供复用；如需释放请参见 `clear_and_shrink`。
+    ///
+    /// 所有 slot 都会被丢弃，因此 map 开启新的 key 空间：代数从 `Generation::MIN`
+    /// 重新开始，清空之前的 key 或 Handle 可能会解析到之后插入的条目。清空时应丢弃旧 key，
+    /// 或使用 `retain(|_, _| false)` 在保留代数的同时清空 map。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, 42);
+    ///
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.slots.clear();
         // Re-add sentinel
         // 重新添加 sentinel
         self.slots.push(Slot {
@@ -662,16 +2595,61 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         });
         self.free_head = 1;
         self.num_elems = 0;
+        self.num_reserved = 0;
+        self.fresh_generation = crate::Generation::MIN;
+        self.bump_epoch();
+    }
+
+    /// Clear all elements and release the backing allocation
+    ///
+    /// Drops every value, resets the map to just the sentinel slot and shrinks the
+    /// slot vector to fit. Unlike `clear`, which keeps the capacity for reuse, this
+    /// returns the memory of a large transient load to the allocator.
+    ///
+    /// 清空所有元素并释放底层分配
+    ///
+    /// drop 所有值，将 map 重置为只有 sentinel slot，并将 slot 向量收缩到合适大小。
+    /// 与保留容量以供复用的 `clear` 不同，它会把大量临时负载占用的内存归还给分配器。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, _) = DeferredMap::<_>::from_values(0..1000);
+    /// map.clear_and_shrink();
+    /// assert!(map.is_empty());
+    /// assert!(map.reserved_capacity() < 1000);
+    /// ```
+    pub fn clear_and_shrink(&mut self) {
+        self.clear();
+        self.slots.shrink_to_fit();
     }
 
     /// Return an iterator over all (key, value) pairs
     ///
+    /// Keys are yielded as the map's typed key `K`, so they can be passed straight
+    /// back to `get`, or used to fill a `SecondaryMap`.
+    ///
+    /// # Iteration order
+    /// Entries are yielded in ascending slot-index order, never in insertion order.
+    /// This is part of the API contract: the order is deterministic and reproducible
+    /// for the same sequence of operations, and any future storage layout must keep it.
+    /// `iter_mut`, `iter_sorted`, `iter_with_index` and `iter_chunks` follow the same order.
+    ///
     /// 返回一个迭代器，遍历所有 (key, value) 对
     ///
+    /// key 以 map 的类型化 key `K` 产出，因此可以直接传回 `get`，或用于填充 `SecondaryMap`。
+    ///
+    /// # 迭代顺序
+    /// 条目按 slot 索引升序产出，而不是按插入顺序。这是 API 约定的一部分：对于相同的操作序列，
+    /// 顺序是确定且可复现的，未来任何存储布局都必须保持这一点。
+    /// `iter_mut`、`iter_sorted`、`iter_with_index` 和 `iter_chunks` 遵循相同的顺序。
+    ///
     /// # Examples (示例)
     ///
     /// ```
-    /// use deferred_map::DeferredMap;
+    /// use deferred_map::{DeferredMap, SecondaryMap};
     ///
     /// let mut map = DeferredMap::new();
     ///
@@ -683,32 +2661,116 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
     ///
     /// let sum: i32 = map.iter().map(|(_, v)| v).sum();
     /// assert_eq!(sum, 3);
+    ///
+    /// let doubled: SecondaryMap<i32> = map.iter().map(|(k, v)| (k, v * 2)).collect();
+    /// for (key, value) in map.iter() {
+    ///     assert_eq!(map.get(key), Some(value));
+    ///     assert_eq!(doubled.get(key), Some(&(value * 2)));
+    /// }
     /// ```
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = (K, &T)> {
+        let dense = self.is_dense();
+
         self.slots
             .iter()
             .enumerate()
             .skip(1)
-            .filter_map(|(index, slot)| {
-                if let Occupied(value) = slot.get() {
+            .filter_map(move |(index, slot)| {
+                if hinted_occupied(dense, slot.is_occupied()) {
                     let key = K::from_parts(
                         index as u32,
                         slot.generation(),
                         #[cfg(debug_assertions)]
                         self.map_id,
                     );
-                    Some((key, value))
+                    // SAFETY: We've checked that slot is occupied
+                    Some((key, unsafe { &*slot.u.value }))
                 } else {
                     None
                 }
             })
     }
 
+    /// Return an iterator over all (key, value) pairs in strictly increasing
+    /// `(index, generation)` order
+    ///
+    /// The order depends only on the keys, never on insertion order or free-list
+    /// history. `iter` guarantees the same ascending-index order, so this is an
+    /// alias that spells the ordering out at the call site.
+    ///
+    /// 返回一个按 `(index, generation)` 严格递增顺序遍历所有 (key, value) 对的迭代器
+    ///
+    /// 顺序只取决于 key，与插入顺序和空闲列表历史无关。`iter` 保证相同的索引升序，
+    /// 因此这是一个在调用处明确表达顺序的别名。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Key};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.remove(keys[0]);
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, 4);
+    ///
+    /// let indices: Vec<_> = map.iter_sorted().map(|(k, _)| k.index()).collect();
+    /// assert_eq!(indices, [1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (K, &T)> {
+        // Slots are stored in index order and each index holds a single generation
+        // slot 按索引顺序存储，且每个索引只持有一个代数
+        self.iter()
+    }
+
+    /// Return an iterator over live (key, value) pairs grouped into batches of `n`
+    ///
+    /// Batches follow `iter` order. Every batch holds exactly `n` pairs except
+    /// possibly the last one, which holds the remainder; no empty batch is yielded.
+    ///
+    /// 返回一个迭代器，将存活的 (key, value) 对按 `n` 个一组分批
+    ///
+    /// 批次遵循 `iter` 的顺序。除最后一批可能只包含剩余元素外，每批恰好包含 `n` 对；
+    /// 不会产出空批次。
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// 如果 `n` 为 0 则 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (map, _) = DeferredMap::<_>::from_values(0..5);
+    ///
+    /// let sizes: Vec<_> = map.iter_chunks(2).map(|chunk| chunk.len()).collect();
+    /// assert_eq!(sizes, [2, 2, 1]);
+    /// ```
+    pub fn iter_chunks(&self, n: usize) -> impl Iterator<Item = Vec<(K, &T)>> {
+        assert!(n != 0, "chunk size must be non-zero");
+
+        let mut iter = self.iter();
+        std::iter::from_fn(move || {
+            let chunk: Vec<_> = iter.by_ref().take(n).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
     /// Return a mutable iterator over all (key, value) pairs
     ///
+    /// Each yielded key is the current key of its slot (index plus live generation),
+    /// so it can be passed straight back to `get`/`get_mut`/`remove`. Entries come
+    /// in ascending slot-index order, as for `iter`.
+    ///
     /// 返回一个可变迭代器，遍历所有 (key, value) 对
     ///
+    /// 每个产出的 key 都是其 slot 的当前 key（index 加上当前 generation），
+    /// 可以直接传回 `get`/`get_mut`/`remove` 使用。条目按 slot 索引升序产出，与 `iter` 相同。
+    ///
     /// # Examples (示例)
     ///
     /// ```
@@ -734,112 +2796,1184 @@ impl<T, K: crate::Key> DeferredMap<T, K> {
         // We need to capture map_id for the closure
         #[cfg(debug_assertions)]
         let map_id = self.map_id;
+        let dense = self.is_dense();
 
         self.slots
             .iter_mut()
             .enumerate()
             .skip(1)
             .filter_map(move |(index, slot)| {
-                let generation = slot.generation();
-                if let OccupiedMut(value) = slot.get_mut() {
+                if hinted_occupied(dense, slot.is_occupied()) {
                     let key = K::from_parts(
                         index as u32,
-                        generation,
+                        slot.generation(),
                         #[cfg(debug_assertions)]
                         map_id,
                     );
-                    Some((key, value))
+                    // SAFETY: We've checked that slot is occupied
+                    Some((key, unsafe { &mut *slot.u.value }))
                 } else {
                     None
                 }
             })
     }
-    /// Reserves capacity for at least `additional` more elements to be inserted in the map.
-    /// The map may reserve more space to speculatively avoid frequent reallocations.
+
+    /// Split mutable iteration into `parts` iterators over disjoint index ranges
     ///
-    /// 预留至少能容纳 `additional` 个额外元素的空间。
-    /// Map 可能会预留更多空间以避免频繁的重新分配。
+    /// The slot storage is cut with `split_at_mut` into `parts` contiguous ranges of
+    /// (nearly) equal length, so the returned iterators never alias and can be handed
+    /// to scoped threads without depending on `rayon`. Each one yields the occupied
+    /// entries of its range in ascending index order, exactly as `iter_mut` would;
+    /// chained together they cover the whole map. Ranges may be empty when `parts`
+    /// exceeds the number of slots.
+    ///
+    /// 将可变迭代拆分为 `parts` 个遍历互不相交索引区间的迭代器
+    ///
+    /// slot 存储通过 `split_at_mut` 切分为 `parts` 个长度（几乎）相等的连续区间，
+    /// 因此返回的迭代器互不别名，无需依赖 `rayon` 即可交给 scoped 线程使用。
+    /// 每个迭代器按索引升序产出其区间内的已占用条目，与 `iter_mut` 完全一致；
+    /// 依次连接起来即覆盖整个 map。当 `parts` 超过 slot 数量时，部分区间可能为空。
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if `parts` is zero.
+    ///
+    /// 当 `parts` 为零时 panic。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, _) = DeferredMap::<u64>::from_values(1..=100);
+    ///
+    /// std::thread::scope(|scope| {
+    ///     for part in map.split_mut(4) {
+    ///         scope.spawn(move || part.for_each(|(_, v)| *v *= 2));
+    ///     }
+    /// });
+    /// assert_eq!(map.iter().map(|(_, v)| v).sum::<u64>(), 10100);
+    /// ```
+    pub fn split_mut(&mut self, parts: usize) -> Vec<IterMutRange<'_, T, K>> {
+        assert!(parts > 0, "split_mut requires at least one part");
+
+        // Skip the sentinel at index 0
+        // 跳过索引 0 处的 sentinel
+        let mut rest = self.slots.get_mut(1..).unwrap_or_default();
+        let chunk = rest.len().div_ceil(parts);
+        let mut base = 1;
+
+        (0..parts)
+            .map(|_| {
+                let len = chunk.min(rest.len());
+                let (head, tail) = std::mem::take(&mut rest).split_at_mut(len);
+                rest = tail;
+                let start = base;
+                base += head.len();
+                IterMutRange {
+                    inner: head.iter_mut(),
+                    index: start,
+                    #[cfg(debug_assertions)]
+                    map_id: self.map_id,
+                    _marker: std::marker::PhantomData,
+                }
+            })
+            .collect()
+    }
+
+    /// Return an iterator over the packed version word of every slot
+    ///
+    /// Yields one `u32` per slot in index order, in the `Version` encoding: the low
+    /// 2 bits are the state (`0b00` vacant, `0b01` reserved, `0b11` occupied) and the
+    /// upper 30 bits the generation. The first item is the sentinel at index 0, which
+    /// is always vacant; skip it when scanning for live entries. Intended for callers
+    /// running their own bulk occupancy scans.
+    ///
+    /// 返回一个迭代器，遍历每个 slot 的打包版本字
+    ///
+    /// 按索引顺序为每个 slot 产出一个 `Version` 编码的 `u32`：最低 2 位为状态
+    /// （`0b00` 空闲、`0b01` 预留、`0b11` 占用），高 30 位为代数。第一项是索引 0 处的
+    /// sentinel，它始终处于空闲状态；扫描存活条目时应跳过它。适用于自行执行批量占用扫描的调用方。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.remove(keys[1]);
+    ///
+    /// let occupied: Vec<usize> = map
+    ///     .versions()
+    ///     .enumerate()
+    ///     .skip(1)
+    ///     .filter(|&(_, v)| v & 0b11 == 0b11)
+    ///     .map(|(i, _)| i)
+    ///     .collect();
+    /// assert_eq!(occupied, [1, 3]);
+    /// ```
+    #[inline]
+    pub fn versions(&self) -> impl ExactSizeIterator<Item = u32> + '_ {
+        self.slots.iter().map(|slot| slot.version.0.get())
+    }
+
+    /// Return an iterator over (index, generation, value) for every occupied slot
+    ///
+    /// Yields the physical slot index instead of a key, in increasing index order.
+    /// The sentinel slot at index 0 is never yielded.
+    ///
+    /// 返回一个迭代器，为每个占用的 slot 产出 (index, generation, value)
+    ///
+    /// 产出物理 slot 索引而不是 key，按索引递增顺序。索引 0 处的 sentinel slot 永远不会产出。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, "a");
+    ///
+    /// let (index, generation, value) = map.iter_with_index().next().unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(generation.get(), 1);
+    /// assert_eq!(*value, "a");
+    /// ```
+    #[inline]
+    pub fn iter_with_index(&self) -> impl Iterator<Item = (u32, crate::Generation, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter_map(|(index, slot)| match slot.get() {
+                Occupied(value) => Some((index as u32, slot.generation(), value)),
+                _ => None,
+            })
+    }
+
+    /// Collect the indices of all occupied slots, in increasing order
+    ///
+    /// The sentinel slot at index 0 is never included. Useful for driving
+    /// external parallel arrays indexed by slot index.
+    ///
+    /// 按递增顺序收集所有占用 slot 的索引
+    ///
+    /// 索引 0 处的 sentinel slot 永远不会包含在内。适用于驱动按 slot 索引寻址的外部并行数组。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.remove(keys[1]);
+    /// assert_eq!(map.occupied_indices(), vec![1, 3]);
+    /// ```
+    pub fn occupied_indices(&self) -> Vec<u32> {
+        let mut indices = Vec::with_capacity(self.num_elems as usize);
+        indices.extend(
+            self.slots
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|(_, slot)| slot.is_occupied())
+                .map(|(index, _)| index as u32),
+        );
+        indices
+    }
+
+    /// Build a bitmap of occupied slots, one bit per slot index
+    ///
+    /// Bit `i % 64` of word `i / 64` is set iff slot `i` is occupied; the sentinel
+    /// bit 0 is always clear and the last word is zero-padded. The bitmap is a
+    /// snapshot, so it goes stale on the next insert or removal. Handy for handing
+    /// occupancy to foreign code or SIMD scans that cannot call back into the map.
+    ///
+    /// 构建已占用 slot 的位图，每个 slot 索引对应一位
+    ///
+    /// 当且仅当 slot `i` 被占用时，第 `i / 64` 个字的第 `i % 64` 位被置位；sentinel 的
+    /// 第 0 位始终为零，最后一个字以零填充。位图只是快照，下一次插入或删除后即过期。
+    /// 便于将占用情况交给无法回调 map 的外部代码或 SIMD 扫描。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.remove(keys[1]);
+    /// assert_eq!(map.occupancy_bitmap(), vec![0b1010]);
+    /// ```
+    pub fn occupancy_bitmap(&self) -> Vec<u64> {
+        let mut bitmap = vec![0u64; self.slots.len().div_ceil(64)];
+        for (index, slot) in self.slots.iter().enumerate().skip(1) {
+            if slot.is_occupied() {
+                bitmap[index / 64] |= 1 << (index % 64);
+            }
+        }
+        bitmap
+    }
+
+    /// Collect the free list from `free_head`, in the order slots will be reused
+    ///
+    /// The free list is LIFO, so the most recently vacated slot comes first (or last,
+    /// for maps built with [`with_fifo_free_list`](Self::with_fifo_free_list)). Once it
+    /// is exhausted, `allocate_handle` appends fresh slots, which are not listed.
+    ///
+    /// 从 `free_head` 开始收集空闲列表，顺序即 slot 被复用的顺序
+    ///
+    /// 空闲列表是 LIFO 的，因此最近空出的 slot 排在最前（对于通过
+    /// [`with_fifo_free_list`](Self::with_fifo_free_list) 创建的 map 则排在最后）。列表耗尽后，
+    /// `allocate_handle` 会追加新 slot，这些 slot 不会列出。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.remove(keys[0]);
+    /// map.remove(keys[2]);
+    /// assert_eq!(map.free_list_indices(), vec![3, 1]);
+    ///
+    /// let handle = map.allocate_handle();
+    /// assert_eq!(handle.index(), 3);
+    /// # map.insert(handle, 4);
+    /// ```
+    pub fn free_list_indices(&self) -> Vec<u32> {
+        let mut indices = Vec::new();
+        let mut current = self.free_head;
+        // A well-formed list visits each slot at most once; the bound stops a corrupt cycle
+        // 格式正确的列表每个 slot 最多访问一次；该上限可以阻止损坏形成的环
+        while (current as usize) < self.slots.len() && indices.len() < self.slots.len() {
+            indices.push(current);
+            // SAFETY: slots on the free list are vacant
+            current = unsafe { self.slots[current as usize].u.next_free };
+        }
+        indices
+    }
+
+    /// Collect all (key, value) pairs into a `HashMap`
+    ///
+    /// 将所有 (key, value) 对收集到 `HashMap` 中
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (map, keys) = DeferredMap::<_>::from_values([10, 20]);
+    /// let hash_map = map.to_hashmap();
+    /// assert_eq!(hash_map[&keys[0]], &10);
+    /// assert_eq!(hash_map[&keys[1]], &20);
+    /// ```
+    pub fn to_hashmap(&self) -> std::collections::HashMap<K, &T> {
+        let mut map = std::collections::HashMap::with_capacity(self.len());
+        map.extend(self.iter());
+        map
+    }
+
+    /// Consume the map and map every value through `f`, keeping all keys valid
+    ///
+    /// The result has the identical slot layout: same indices, generations, free
+    /// list and reserved slots, so every key (and outstanding Handle) of this map
+    /// resolves in the new one. Values are visited in increasing index order.
+    ///
+    /// 消耗 map 并通过 `f` 映射每个值，所有 key 保持有效
+    ///
+    /// 结果具有完全相同的 slot 布局：相同的索引、代数、空闲列表和预留 slot，
+    /// 因此此 map 的每个 key（以及未完成的 Handle）在新 map 中都能解析。值按索引递增顺序访问。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    /// let strings = map.map_values(|v| v.to_string());
+    /// assert_eq!(strings.get(keys[0]), Some(&"1".to_string()));
+    /// assert_eq!(strings.get(keys[1]), Some(&"2".to_string()));
+    /// ```
+    pub fn map_values<U, F>(self, mut f: F) -> DeferredMap<U, K>
+    where
+        F: FnMut(T) -> U,
+    {
+        DeferredMap {
+            slots: self
+                .slots
+                .into_iter()
+                .map(|slot| slot.map(&mut f))
+                .collect(),
+            free_head: self.free_head,
+            num_elems: self.num_elems,
+            num_reserved: self.num_reserved,
+            epoch: self.epoch,
+            growth: self.growth,
+            fresh_generation: self.fresh_generation,
+            fifo: self.fifo,
+            free_tail: self.free_tail,
+            #[cfg(debug_assertions)]
+            map_id: self.map_id,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Consume the map, mapping values through `f` and dropping entries where it returns `None`
+    ///
+    /// Kept entries stay under their original keys. Dropped entries are freed as
+    /// if removed: their generation is bumped, so their keys no longer resolve, and
+    /// their slots are reused by later allocations. Reserved slots are untouched.
+    ///
+    /// 消耗 map，通过 `f` 映射值，并丢弃 `f` 返回 `None` 的条目
+    ///
+    /// 保留的条目仍使用原来的 key。被丢弃的条目像删除一样被释放：代数递增，
+    /// 因此它们的 key 不再能解析，其 slot 会被之后的分配复用。预留的 slot 不受影响。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// let odd = map.filter_map_values(|v| (v % 2 == 1).then(|| v * 10));
+    /// assert_eq!(odd.get(keys[0]), Some(&10));
+    /// assert_eq!(odd.get(keys[1]), None);
+    /// assert_eq!(odd.get(keys[2]), Some(&30));
+    /// assert_eq!(odd.len(), 2);
+    /// ```
+    pub fn filter_map_values<U, F>(self, mut f: F) -> DeferredMap<U, K>
+    where
+        F: FnMut(T) -> Option<U>,
+    {
+        let mut dropped = Vec::new();
+        let slots = self
+            .slots
+            .into_iter()
+            .enumerate()
+            .map(|(index, slot)| {
+                let was_occupied = slot.is_occupied();
+                let slot = slot.filter_map(&mut f);
+                if was_occupied && !slot.is_occupied() {
+                    dropped.push(index as u32);
+                }
+                slot
+            })
+            .collect();
+
+        let mut map = DeferredMap {
+            slots,
+            free_head: self.free_head,
+            num_elems: self.num_elems - dropped.len() as u32,
+            num_reserved: self.num_reserved,
+            epoch: self.epoch,
+            growth: self.growth,
+            fresh_generation: self.fresh_generation,
+            fifo: self.fifo,
+            free_tail: self.free_tail,
+            #[cfg(debug_assertions)]
+            map_id: self.map_id,
+            _marker: std::marker::PhantomData,
+        };
+        for index in dropped {
+            map.push_free(index);
+        }
+        map.bump_epoch();
+        map
+    }
+
+    /// Reserves capacity for at least `additional` more elements to be inserted in the map.
+    /// The map may reserve more space to speculatively avoid frequent reallocations.
+    ///
+    /// The reservation counts usable slots beyond the current `slots.len()` (which
+    /// already includes the sentinel), so `additional` fresh handles can be allocated
+    /// afterwards without reallocating, even if the free list is empty.
+    ///
+    /// 预留至少能容纳 `additional` 个额外元素的空间。
+    /// Map 可能会预留更多空间以避免频繁的重新分配。
+    ///
+    /// 预留的数量是当前 `slots.len()`（已包含 sentinel）之外的可用 slot，
+    /// 因此之后即使空闲列表为空，也可以在不重新分配的情况下分配 `additional` 个新 handle。
+    ///
+    /// # Parameters
+    /// - `additional`: The number of additional elements to reserve space for.
+    ///
+    /// # 参数
+    /// - `additional`: 需要预留的额外元素数量。
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.slots.reserve(additional);
+    }
+
+    /// Reserves capacity for exactly `additional` more elements to be inserted in the map.
+    /// Unlike `reserve`, this does not over-allocate, which is useful when the final
+    /// element count is known.
+    ///
+    /// 精确预留 `additional` 个额外元素的空间。
+    /// 与 `reserve` 不同，它不会过度分配，适用于已知最终元素数量的场景。
+    ///
+    /// # Parameters
+    /// - `additional`: The number of additional elements to reserve space for.
+    ///
+    /// # 参数
+    /// - `additional`: 需要预留的额外元素数量。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map: DeferredMap<i32> = DeferredMap::new();
+    /// map.reserve_exact(100);
+    /// assert!(map.capacity() >= 100);
+    /// ```
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        // slots.len() already counts the sentinel, so `additional` is all usable space
+        // slots.len() 已包含 sentinel，因此 `additional` 全部为可用空间
+        self.slots.reserve_exact(additional);
+    }
+
+    /// Set how `allocate_handle` grows the slot storage when it is full
+    ///
+    /// The default, `GrowthPolicy::Double`, is `Vec`'s amortized doubling.
+    /// `Linear(step)` reserves exactly `step` more slots each time, trading
+    /// more frequent reallocations for smaller, predictable ones; `Exact` grows
+    /// one slot at a time. Explicit `reserve` calls are not affected.
+    ///
+    /// 设置 `allocate_handle` 在 slot 存储已满时的增长方式
+    ///
+    /// 默认的 `GrowthPolicy::Double` 即 `Vec` 的摊还倍增。`Linear(step)` 每次精确预留
+    /// `step` 个额外 slot，以更频繁的重新分配换取更小且可预测的分配；`Exact` 每次只增长一个 slot。
+    /// 显式调用 `reserve` 不受影响。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, GrowthPolicy};
+    ///
+    /// let mut map: DeferredMap<i32> = DeferredMap::new();
+    /// map.set_growth(GrowthPolicy::Linear(64));
+    /// assert_eq!(map.growth(), GrowthPolicy::Linear(64));
+    ///
+    /// let handle = map.allocate_handle();
+    /// map.insert(handle, 1);
+    /// assert!(map.capacity() >= 64);
+    /// ```
+    #[inline]
+    pub fn set_growth(&mut self, policy: GrowthPolicy) {
+        self.growth = policy;
+    }
+
+    /// Return the current growth policy
+    ///
+    /// 返回当前的增长策略
+    #[inline]
+    pub fn growth(&self) -> GrowthPolicy {
+        self.growth
+    }
+
+    /// Reserve slot storage for every index `secondary` can hold without reallocating
+    ///
+    /// Keeps a primary map growing in lockstep with a side-table, so a burst of
+    /// allocations that fits in `secondary` does not reallocate this map either.
+    /// See `SecondaryMap::reserve_like` for the other direction.
+    ///
+    /// 为 `secondary` 在不重新分配的情况下能容纳的每个索引预留 slot 存储
+    ///
+    /// 使主 map 与辅助表同步增长，因此能放入 `secondary` 的一批分配也不会导致此 map 重新分配。
+    /// 反方向请参见 `SecondaryMap::reserve_like`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let mut sec: SecondaryMap<u8> = SecondaryMap::new();
+    /// sec.reserve_exact(64);
+    ///
+    /// let mut map: DeferredMap<i32> = DeferredMap::new();
+    /// map.reserve_like(&sec);
+    /// assert!(map.capacity() + 1 >= sec.capacity());
+    /// ```
+    #[inline]
+    pub fn reserve_like<U>(&mut self, secondary: &crate::SecondaryMap<U, K>) {
+        // Both vectors are indexed by slot index (sentinel included), so match raw capacity
+        // 两个向量都按 slot 索引寻址（包括 sentinel），因此对齐原始容量
+        let additional = secondary.capacity().saturating_sub(self.slots.len());
+        self.slots.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    /// It will drop down as close as possible to the length (number of slots used + free slots).
+    ///
+    /// 尽可能缩小 map 的容量。
+    /// 容量会尽可能降低到接近当前的长度（包含已使用和位于空闲列表中的 slot）。
+    ///
+    /// Only spare heap capacity is released; no slot is ever truncated, even vacant
+    /// or Reserved ones at the tail. Reserved slots must survive so outstanding
+    /// Handles stay insertable, and vacant slots keep their generation so a stale
+    /// key can never be revived by a slot recreated at `Generation::MIN`.
+    ///
+    /// 只释放多余的堆容量；永远不会截断任何 slot，即使是位于末尾的空闲或 Reserved slot。
+    /// Reserved slot 必须保留，以便未完成的 Handle 仍可插入；空闲 slot 保留其代数，
+    /// 因此过期的 key 永远不会因为 slot 以 `Generation::MIN` 重新创建而复活。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let mut map: DeferredMap<i32> = DeferredMap::with_capacity(100);
+    /// let handle = map.allocate_handle();
+    ///
+    /// map.shrink_to_fit();
+    /// assert!(map.capacity() < 100);
+    ///
+    /// map.insert(handle, 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.slots.shrink_to_fit();
+    }
+
+    /// Truncate trailing vacant slots, returning how many were removed
+    ///
+    /// A lighter alternative to compaction: nothing is moved, so every occupied
+    /// entry and outstanding Handle keeps its key. The trimmed slots are dropped
+    /// from the free list, which otherwise keeps its order. Spare heap capacity is
+    /// not released; call `shrink_to_fit` afterwards for that.
+    ///
+    /// No key is invalidated or revived: the map remembers the highest generation
+    /// it trimmed, and slots created later start from that generation instead of
+    /// `Generation::MIN`, so stale keys into the trimmed tail never resolve again
+    /// (and `SecondaryMap` sees the new keys as newer).
+    ///
+    /// 截断末尾的空闲 slot，返回被移除的数量
+    ///
+    /// 比压缩更轻量：不移动任何数据，因此每个占用的条目和未完成的 Handle 都保持其 key。
+    /// 被截断的 slot 会从空闲列表中移除，列表其余部分保持原有顺序。多余的堆容量不会释放；
+    /// 如需释放请随后调用 `shrink_to_fit`。
+    ///
+    /// 不会使任何 key 失效或复活：map 会记住其截断过的最高代数，之后新建的 slot 从该代数
+    /// 而不是 `Generation::MIN` 开始，因此指向被截断末尾的过期 key 永远不会再次解析成功
+    /// （`SecondaryMap` 也会把新 key 视为更新的 key）。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3, 4]);
+    /// map.remove(keys[2]);
+    /// map.remove(keys[3]);
+    ///
+    /// assert_eq!(map.trim(), 2);
+    /// assert_eq!(map.slot_stats().total, 2);
+    /// assert_eq!(map.get(keys[1]), Some(&2));
+    /// assert_eq!(map.trim(), 0);
+    /// ```
+    pub fn trim(&mut self) -> usize {
+        let old_len = self.slots.len();
+        let mut new_len = old_len;
+        while new_len > 1 && self.slots[new_len - 1].is_vacant() {
+            new_len -= 1;
+            // A vacant slot holds the generation its next occupant would get, so
+            // recreating it at the highest such generation keeps stale keys dead
+            // 空闲 slot 持有其下一个占用者将获得的代数，因此以其中最高的代数重建 slot
+            // 可以保证过期 key 依旧失效
+            let generation = self.slots[new_len].generation();
+            self.fresh_generation = self.fresh_generation.max(generation);
+        }
+        if new_len == old_len {
+            return 0;
+        }
+
+        // Keep the free-list order of the surviving vacant slots
+        // 保持存活空闲 slot 在空闲列表中的顺序
+        let mut kept = Vec::new();
+        let mut current = self.free_head;
+        while (current as usize) < old_len {
+            // SAFETY: slots on the free list are vacant, so next_free is the active field
+            let next = unsafe { self.slots[current as usize].u.next_free };
+            if (current as usize) < new_len {
+                kept.push(current);
+            }
+            current = next;
+        }
+
+        // Vacant slots hold no value, so truncating drops nothing
+        // 空闲 slot 不持有值，因此截断不会 drop 任何东西
+        self.slots.truncate(new_len);
+
+        let end = new_len as u32;
+        self.free_head = kept.first().copied().unwrap_or(end);
+        for (i, &index) in kept.iter().enumerate() {
+            self.slots[index as usize].u.next_free = kept.get(i + 1).copied().unwrap_or(end);
+        }
+        if let Some(&last) = kept.last() {
+            self.free_tail = last;
+        }
+        self.bump_epoch();
+        old_len - new_len
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, remove all pairs `(k, v)` for which `f(k, v)` returns `false`.
+    /// The elements are visited in ascending slot-index order, as by `iter`.
+    ///
+    /// 只保留满足谓词的元素。
+    /// 换句话说，移除所有 `f(k, v)` 返回 `false` 的 `(k, v)` 对。
+    /// 元素按 slot 索引升序访问，与 `iter` 相同。
+    ///
+    /// # Parameters
+    /// - `f`: The predicate function.
+    ///
+    /// # Returns
+    /// How many entries were removed and how many were kept.
+    ///
+    /// # 参数
+    /// - `f`: 谓词函数。
+    ///
+    /// # 返回值
+    /// 被移除和被保留的条目数量。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, RetainResult};
+    ///
+    /// let (mut map, _) = DeferredMap::<_>::from_values(1..=5);
+    /// let result = map.retain(|_, v| *v % 2 == 1);
+    /// assert_eq!(result, RetainResult { removed: 2, retained: 3 });
+    /// ```
+    #[inline]
+    pub fn retain<F>(&mut self, f: F) -> RetainResult
+    where
+        F: FnMut(K, &mut T) -> bool,
+    {
+        self.retain_mut(f)
+    }
+
+    /// Retains only the elements specified by the predicate, letting it mutate them.
+    ///
+    /// The predicate receives `&mut T` and may both modify the value and decide
+    /// whether to keep it. Mutations to kept elements persist; elements for which
+    /// it returns `false` are removed (their slots freed and generation bumped) and
+    /// dropped after the predicate returns. Same contract as `Vec::retain_mut`;
+    /// `retain` is an alias kept for compatibility.
+    ///
+    /// 只保留满足谓词的元素，并允许谓词修改它们。
+    ///
+    /// 谓词接收 `&mut T`，既可以修改值，也可以决定是否保留它。对保留元素的修改会
+    /// 持久生效；返回 `false` 的元素会被移除（其 slot 被释放且代数递增），并在谓词
+    /// 返回后被 drop。与 `Vec::retain_mut` 的约定相同；`retain` 是为兼容而保留的别名。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// map.retain_mut(|_, v| {
+    ///     *v *= 10;
+    ///     *v != 20
+    /// });
+    ///
+    /// assert_eq!(map.get(keys[0]), Some(&10));
+    /// assert_eq!(map.get(keys[1]), None);
+    /// assert_eq!(map.get(keys[2]), Some(&30));
+    /// ```
+    pub fn retain_mut<F>(&mut self, mut f: F) -> RetainResult
+    where
+        F: FnMut(K, &mut T) -> bool,
+    {
+        self.bump_epoch();
+        let len_before = self.len();
+        let mut removed = 0;
+
+        // Iterate over all slots skipping sentinel at index 0
+        // 遍历所有 slot，跳过索引 0 的 sentinel
+        for i in 1..self.slots.len() {
+            // SAFETY: Access is bounded by slots.len()
+            let slot = unsafe { self.slots.get_unchecked_mut(i) };
+
+            // Only process Occupied slots.
+            // Reserved slots (waiting for insert) and Vacant slots are ignored.
+            // 只处理 Occupied 的 slot。
+            // Reserved (等待插入) 和 Vacant 的 slot 被忽略。
+            if slot.is_occupied() {
+                let generation = slot.generation();
+                let key = K::from_parts(
+                    i as u32,
+                    generation,
+                    #[cfg(debug_assertions)]
+                    self.map_id,
+                );
+
+                // SAFETY: We checked is_occupied()
+                let value = unsafe { &mut *slot.u.value };
+
+                if !f(key, value) {
+                    // Predicate returned false, remove the element.
+                    // The slot is vacated before the value is dropped.
+                    // 谓词返回 false，移除该元素。
+                    // 先腾空 slot，再 drop 值。
+                    // SAFETY: i is in bounds and the slot is occupied
+                    drop(unsafe { self.vacate(i as u32) });
+                    removed += 1;
+                }
+            }
+        }
+
+        RetainResult {
+            removed,
+            retained: len_before - removed,
+        }
+    }
+
+    /// Retain only the entries whose keys are in `keep`, returning how many were removed
+    ///
+    /// Equivalent to `retain(|k, _| keep.contains(&k))`, without going through a
+    /// closure. Removed entries have their slots freed and generation bumped.
+    /// Reserved slots are left untouched.
+    ///
+    /// 只保留 key 在 `keep` 中的条目，返回被移除的数量
+    ///
+    /// 等价于 `retain(|k, _| keep.contains(&k))`，但无需经过闭包。被移除条目的
+    /// slot 会被释放且代数递增。Reserved 的 slot 不受影响。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    /// use std::collections::HashSet;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    /// let keep: HashSet<_> = [keys[0], keys[2]].into_iter().collect();
+    ///
+    /// assert_eq!(map.retain_keys(&keep), 1);
+    /// assert_eq!(map.get(keys[1]), None);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn retain_keys<S>(&mut self, keep: &std::collections::HashSet<K, S>) -> usize
+    where
+        S: std::hash::BuildHasher,
+    {
+        self.bump_epoch();
+
+        let mut removed = 0;
+        for i in 1..self.slots.len() {
+            // SAFETY: Access is bounded by slots.len()
+            let slot = unsafe { self.slots.get_unchecked(i) };
+            if !slot.is_occupied() {
+                continue;
+            }
+
+            let key = K::from_parts(
+                i as u32,
+                slot.generation(),
+                #[cfg(debug_assertions)]
+                self.map_id,
+            );
+            if !keep.contains(&key) {
+                // SAFETY: i is in bounds and the slot is occupied
+                drop(unsafe { self.vacate(i as u32) });
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Remove values equal to an earlier one, returning each removed key's survivor
+    ///
+    /// Entries are visited in iteration (ascending index) order; the first entry of
+    /// each group of values equal under `eq` is kept, and every later one is removed.
+    /// The returned map sends each removed key to the key of the value it duplicated,
+    /// so stored references can be rewritten. `eq` only needs to be an equivalence,
+    /// not a hash, so this compares each entry against every survivor: O(n · survivors).
+    ///
+    /// 移除与之前的值相等的值，并返回每个被移除 key 对应的保留 key
+    ///
+    /// 按迭代（索引升序）顺序访问条目；在 `eq` 下相等的每组值中保留第一个条目，
+    /// 之后的全部移除。返回的 map 将每个被移除的 key 映射到它所重复的值的 key，
+    /// 以便改写已保存的引用。`eq` 只需是等价关系而不需要哈希，因此每个条目都会与所有保留项比较：
+    /// O(n · 保留数量)。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values(["a", "b", "a", "a"]);
+    /// let remap = map.dedup_values(|x, y| x == y);
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(remap.len(), 2);
+    /// assert_eq!(remap[&keys[2]], keys[0]);
+    /// assert_eq!(remap[&keys[3]], keys[0]);
+    /// ```
+    pub fn dedup_values<F>(&mut self, eq: F) -> std::collections::HashMap<K, K>
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut survivors: Vec<(K, &T)> = Vec::new();
+        let mut duplicates = Vec::new();
+        for (key, value) in self.iter() {
+            match survivors.iter().find(|(_, kept)| eq(kept, value)) {
+                Some(&(canonical, _)) => duplicates.push((key, canonical)),
+                None => survivors.push((key, value)),
+            }
+        }
+
+        // Free in index order so the free list does not depend on hashing
+        // 按索引顺序释放，使空闲列表不依赖于哈希
+        for &(duplicate, _) in &duplicates {
+            // SAFETY: every duplicate key was just read from an occupied slot
+            drop(unsafe { self.vacate(duplicate.index()) });
+        }
+        duplicates.into_iter().collect()
+    }
+
+    /// Move every value out of `other` into `self`, returning the key remap
+    ///
+    /// Each value gets a fresh key in `self`; the returned `Vec` maps its key in
+    /// `other` to its new key in `self`, in increasing old-index order. `other` is
+    /// left empty, with all its old keys invalidated. Reserved slots in `other` are
+    /// not touched, so its outstanding Handles stay usable.
+    ///
+    /// 将 `other` 中的所有值移动到 `self`，并返回 key 的重映射
+    ///
+    /// 每个值在 `self` 中获得新 key；返回的 `Vec` 按旧索引递增顺序将其在 `other` 中的
+    /// key 映射到在 `self` 中的新 key。`other` 被清空，其所有旧 key 均失效。
+    /// `other` 中预留的 slot 不受影响，因此其未完成的 Handle 仍可使用。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut a, _) = DeferredMap::<_>::from_values([1, 2]);
+    /// let (mut b, b_keys) = DeferredMap::<_>::from_values([3]);
+    ///
+    /// let remap = a.append(&mut b);
+    /// assert!(b.is_empty());
+    /// assert_eq!(a.len(), 3);
+    /// assert_eq!(remap[0].0, b_keys[0]);
+    /// assert_eq!(a.get(remap[0].1), Some(&3));
+    /// ```
+    pub fn append(&mut self, other: &mut Self) -> Vec<(K, K)> {
+        self.reserve(other.len());
+        let mut remap = Vec::with_capacity(other.len());
+
+        for i in 1..other.slots.len() {
+            // SAFETY: Access is bounded by slots.len()
+            let slot = unsafe { other.slots.get_unchecked(i) };
+            if !slot.is_occupied() {
+                continue;
+            }
+
+            let old_key = K::from_parts(
+                i as u32,
+                slot.generation(),
+                #[cfg(debug_assertions)]
+                other.map_id,
+            );
+            // SAFETY: i is in bounds and the slot is occupied
+            let value = unsafe { other.vacate(i as u32) };
+            let handle = self.allocate_handle();
+            remap.push((old_key, handle.key()));
+            self.insert(handle, value);
+        }
+
+        remap
+    }
+
+    /// Remove and yield every entry matching `pred`, lazily
+    ///
+    /// Entries are visited in index order; each one for which `pred` returns `true`
+    /// is removed (slot freed, generation bumped) right before it is yielded, and the
+    /// rest are left in place (mutations made by `pred` persist). Dropping the
+    /// iterator early keeps everything removed so far and stops evaluating `pred`
+    /// for the remaining entries.
+    ///
+    /// 惰性地移除并产出所有满足 `pred` 的条目
+    ///
+    /// 按索引顺序访问条目；`pred` 返回 `true` 的条目会在产出前被移除（slot 被释放，
+    /// 代数递增），其余条目保留在原处（`pred` 做出的修改会保留）。提前 drop 迭代器时，
+    /// 已移除的条目保持移除，且不再对剩余条目调用 `pred`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, _) = DeferredMap::<_>::from_values(1..=6);
+    ///
+    /// let evens: Vec<i32> = map.extract_if(|_, v| *v % 2 == 0).map(|(_, v)| v).collect();
+    /// assert_eq!(evens, [2, 4, 6]);
+    /// assert_eq!(map.len(), 3);
+    /// ```
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, K>
+    where
+        F: FnMut(K, &mut T) -> bool,
+    {
+        ExtractIf {
+            map: self,
+            index: 1,
+            pred,
+        }
+    }
+
+    /// Return how many more generations the slot at `index` can take before wrapping
+    ///
+    /// This is `Generation::MAX - current_generation`, whatever the slot's state; a
+    /// slot at `Generation::MAX` has no headroom and its next recycle wraps back to
+    /// `Generation::MIN`, after which very old keys could alias again. Monitoring
+    /// this lets operators spot a pathological hot slot early (see also
+    /// [`normalize_generations`](Self::normalize_generations)). Returns `None` for
+    /// the sentinel at index 0 and for indices out of bounds.
+    ///
+    /// 返回索引 `index` 处的 slot 在回绕之前还能经历多少代
+    ///
+    /// 无论 slot 处于何种状态，其值都为 `Generation::MAX - current_generation`；
+    /// 代数为 `Generation::MAX` 的 slot 没有余量，下次回收时会回绕到 `Generation::MIN`，
+    /// 之后非常旧的 key 可能再次产生别名。监控该值可以让运维人员尽早发现异常的热点 slot
+    /// （另请参见 [`normalize_generations`](Self::normalize_generations)）。
+    /// 对于索引 0 处的 sentinel 以及越界索引返回 `None`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Generation, Key};
     ///
-    /// # Parameters
-    /// - `additional`: The number of additional elements to reserve space for.
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let key = map.insert(handle, 1);
+    /// let full = Generation::MAX.get() - 1;
+    /// assert_eq!(map.generation_headroom(key.index()), Some(full));
     ///
-    /// # 参数
-    /// - `additional`: 需要预留的额外元素数量。
-    #[inline]
-    pub fn reserve(&mut self, additional: usize) {
-        self.slots.reserve(additional);
+    /// map.remove(key);
+    /// assert_eq!(map.generation_headroom(key.index()), Some(full - 1));
+    /// assert_eq!(map.generation_headroom(0), None);
+    /// assert_eq!(map.generation_headroom(99), None);
+    /// ```
+    pub fn generation_headroom(&self, index: u32) -> Option<u32> {
+        if index == 0 {
+            return None;
+        }
+        let slot = self.slots.get(index as usize)?;
+        Some(crate::Generation::MAX.get() - slot.generation().get())
     }
 
-    /// Shrinks the capacity of the map as much as possible.
-    /// It will drop down as close as possible to the length (number of slots used + free slots).
+    /// Reset the generation of every occupied and vacant slot to `Generation::MIN`
     ///
-    /// 尽可能缩小 map 的容量。
-    /// 容量会尽可能降低到接近当前的长度（包含已使用和位于空闲列表中的 slot）。
-    #[inline]
-    pub fn shrink_to_fit(&mut self) {
-        self.slots.shrink_to_fit();
+    /// Returns the `(old_key, new_key)` pair of every live entry, in increasing index
+    /// order. **This is destructive to existing keys**: old keys must be dropped and
+    /// replaced through the remap, because a stale key that happens to carry
+    /// `Generation::MIN` would resolve again after the reset. Intended for
+    /// snapshot-and-reload scenarios where every key is reissued anyway. Reserved
+    /// slots keep their generation so outstanding Handles stay usable.
+    ///
+    /// 将每个占用和空闲 slot 的代数重置为 `Generation::MIN`
+    ///
+    /// 按索引递增顺序返回每个存活条目的 `(old_key, new_key)`。**此操作会破坏现有的 key**：
+    /// 旧 key 必须丢弃并通过重映射替换，因为恰好携带 `Generation::MIN` 的过期 key
+    /// 在重置后会再次解析成功。适用于所有 key 都会被重新发放的快照与重新加载场景。
+    /// Reserved slot 保留其代数，因此未完成的 Handle 仍可使用。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Key};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    /// map.remove(keys[0]);
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    /// map.insert(handle, 3);
+    /// assert_eq!(key.generation().get(), 2);
+    ///
+    /// let remap = map.normalize_generations();
+    /// let new_key = remap[0].1;
+    /// assert_eq!(new_key.generation().get(), 1);
+    /// assert_eq!(map.get(new_key), Some(&3));
+    /// ```
+    pub fn normalize_generations(&mut self) -> Vec<(K, K)> {
+        self.bump_epoch();
+
+        let mut remap = Vec::with_capacity(self.num_elems as usize);
+        for i in 1..self.slots.len() {
+            // SAFETY: Access is bounded by slots.len()
+            let slot = unsafe { self.slots.get_unchecked_mut(i) };
+
+            if slot.is_occupied() {
+                let old_key = K::from_parts(
+                    i as u32,
+                    slot.generation(),
+                    #[cfg(debug_assertions)]
+                    self.map_id,
+                );
+                slot.version = crate::Version::new(crate::Generation::MIN, 0b11);
+                let new_key = K::from_parts(
+                    i as u32,
+                    crate::Generation::MIN,
+                    #[cfg(debug_assertions)]
+                    self.map_id,
+                );
+                remap.push((old_key, new_key));
+            } else if slot.is_vacant() {
+                slot.version = crate::Version::new(crate::Generation::MIN, 0b00);
+            }
+        }
+        remap
     }
 
-    /// Retains only the elements specified by the predicate.
+    /// Move every entry matching `pred` into a new map
     ///
-    /// In other words, remove all pairs `(k, v)` for which `f(k, v)` returns `false`.
-    /// The elements are visited in unsorted (internal) order.
+    /// Matching entries are removed from `self` (their slots are freed and their
+    /// generation bumped, so the old keys become invalid) and inserted into a fresh
+    /// map, which assigns them new keys. The returned `Vec` maps each old key to its
+    /// new key, in increasing old-index order.
     ///
-    /// 只保留满足谓词的元素。
-    /// 换句话说，移除所有 `f(k, v)` 返回 `false` 的 `(k, v)` 对。
-    /// 元素按未排序（内部）顺序访问。
+    /// 将所有满足 `pred` 的条目移动到一个新 map
     ///
-    /// # Parameters
-    /// - `f`: The predicate function.
+    /// 匹配的条目会从 `self` 中移除（其 slot 被释放且代数递增，因此旧 key 失效），
+    /// 并插入到一个新 map 中，由新 map 分配新的 key。返回的 `Vec` 按旧索引递增顺序
+    /// 将每个旧 key 映射到新 key。
     ///
-    /// # 参数
-    /// - `f`: 谓词函数。
-    pub fn retain<F>(&mut self, mut f: F)
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values(1..=4);
+    /// let (evens, remap) = map.extract_into(|_, v| v % 2 == 0);
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(evens.len(), 2);
+    /// assert_eq!(map.get(keys[1]), None);
+    /// assert_eq!(remap[0].0, keys[1]);
+    /// assert_eq!(evens.get(remap[0].1), Some(&2));
+    /// ```
+    pub fn extract_into<F>(&mut self, mut pred: F) -> (Self, Vec<(K, K)>)
     where
-        F: FnMut(K, &mut T) -> bool,
+        F: FnMut(K, &T) -> bool,
     {
-        // Iterate over all slots skipping sentinel at index 0
-        // 遍历所有 slot，跳过索引 0 的 sentinel
+        let mut extracted = Self::with_capacity(0);
+        let mut remap = Vec::new();
+
         for i in 1..self.slots.len() {
             // SAFETY: Access is bounded by slots.len()
-            let slot = unsafe { self.slots.get_unchecked_mut(i) };
+            let slot = unsafe { self.slots.get_unchecked(i) };
 
-            // Only process Occupied slots.
-            // Reserved slots (waiting for insert) and Vacant slots are ignored.
-            // 只处理 Occupied 的 slot。
-            // Reserved (等待插入) 和 Vacant 的 slot 被忽略。
-            if slot.is_occupied() {
-                let generation = slot.generation();
+            if let Occupied(value) = slot.get() {
                 let key = K::from_parts(
                     i as u32,
-                    generation,
+                    slot.generation(),
                     #[cfg(debug_assertions)]
                     self.map_id,
                 );
 
-                // SAFETY: We checked is_occupied()
-                let value = unsafe { &mut *slot.u.value };
+                if pred(key, value) {
+                    // SAFETY: i is in bounds and the slot is occupied
+                    let value = unsafe { self.vacate(i as u32) };
+                    let handle = extracted.allocate_handle();
+                    remap.push((key, handle.key()));
+                    extracted.insert(handle, value);
+                }
+            }
+        }
 
-                if !f(key, value) {
-                    // Predicate returned false, remove the element.
-                    // 谓词返回 false，移除该元素。
+        (extracted, remap)
+    }
+}
+
+/// Lookups by raw `u64` key
+///
+/// These mirror `get`, `get_mut`, `remove` and `contains_key` for callers that
+/// store keys as plain integers (e.g. across FFI or in packed buffers). A raw
+/// value behaves exactly like the key it came from via `Key::raw`; raw values
+/// with a zero generation never resolve. In debug builds a raw key is not bound
+/// to any map instance, so the wrong-map assertion is skipped.
+///
+/// 通过原始 `u64` key 查找
+///
+/// 这些方法对应 `get`、`get_mut`、`remove` 和 `contains_key`，供以普通整数保存 key 的调用者使用
+/// （例如跨 FFI 或在紧凑缓冲区中）。原始值的行为与通过 `Key::raw` 得到它的 key 完全相同；
+/// 代数为零的原始值永远无法解析。在 debug 构建中原始 key 不绑定任何 map 实例，因此会跳过错误 map 断言。
+impl<T, K: crate::Key<Raw = u64>> DeferredMap<T, K> {
+    /// Get immutable reference to value by raw key
+    ///
+    /// This is the minimal-overhead lookup: a bounds check plus the generation
+    /// check, nothing else. Typed `get` runs the same code but, in debug builds,
+    /// first asserts that the key belongs to this map; `get_raw` never does.
+    ///
+    /// 通过原始 key 获取值的不可变引用
+    ///
+    /// 这是开销最小的查找：只有边界检查和代数检查。带类型的 `get` 执行相同的代码，
+    /// 但在 debug 构建中会先断言 key 属于此 map；`get_raw` 从不这样做。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, Key};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let raw = handle.key().raw();
+    /// map.insert(handle, 42);
+    ///
+    /// assert_eq!(map.get_raw(raw), Some(&42));
+    /// assert_eq!(map.get_raw(0), None);
+    /// ```
+    #[inline]
+    pub fn get_raw(&self, raw: u64) -> Option<&T> {
+        self.lookup(raw as u32, (raw >> 32) as u32)
+    }
 
-                    // 1. Drop the value
-                    unsafe { ManuallyDrop::drop(&mut slot.u.value) };
+    /// Get mutable reference to value by raw key
+    ///
+    /// 通过原始 key 获取值的可变引用
+    #[inline]
+    pub fn get_mut_raw(&mut self, raw: u64) -> Option<&mut T> {
+        self.get_mut(Self::key_from_raw(raw)?)
+    }
 
-                    // 2. Add to free list (LIFO insert to head)
-                    slot.u.next_free = self.free_head;
-                    self.free_head = i as u32;
+    /// Remove value by raw key
+    ///
+    /// 通过原始 key 移除值
+    #[inline]
+    pub fn remove_raw(&mut self, raw: u64) -> Option<T> {
+        self.remove(Self::key_from_raw(raw)?)
+    }
 
-                    // 3. Update version: Occupied(0b11) -> Vacant(0b00) of NEXT generation
-                    // Incrementing by 1 changes 0b...11 to 0b...00 (next gen due to carry)
-                    // 状态转换：Occupied -> Vacant（下一代）
-                    slot.version.occupied_to_vacant();
+    /// Check if a raw key exists
+    ///
+    /// 检查原始 key 是否存在
+    #[inline]
+    pub fn contains_key_raw(&self, raw: u64) -> bool {
+        Self::key_from_raw(raw).is_some_and(|key| self.contains_key(key))
+    }
 
-                    self.num_elems -= 1;
-                }
-            }
+    /// Rebuild an unbound key from a raw value, rejecting a zero generation
+    ///
+    /// 从原始值重建未绑定的 key，拒绝代数为零的值
+    #[inline(always)]
+    fn key_from_raw(raw: u64) -> Option<K> {
+        if unlikely((raw >> 32) == 0) {
+            return None;
         }
+        // SAFETY: the generation (upper 32 bits) was just checked to be non-zero
+        Some(unsafe {
+            K::from_raw(
+                raw,
+                #[cfg(debug_assertions)]
+                crate::utils::UNBOUND_MAP_ID,
+            )
+        })
     }
 }
 
@@ -850,6 +3984,12 @@ impl<T: Clone, K: crate::Key> Clone for DeferredMap<T, K> {
             slots: self.slots.clone(),
             free_head: self.free_head,
             num_elems: self.num_elems,
+            num_reserved: self.num_reserved,
+            epoch: self.epoch,
+            growth: self.growth,
+            fresh_generation: self.fresh_generation,
+            fifo: self.fifo,
+            free_tail: self.free_tail,
             #[cfg(debug_assertions)]
             map_id: NEXT_MAP_ID.fetch_add(1, Ordering::Relaxed),
             _marker: std::marker::PhantomData,
@@ -858,9 +3998,19 @@ impl<T: Clone, K: crate::Key> Clone for DeferredMap<T, K> {
 
     #[inline]
     fn clone_from(&mut self, source: &Self) {
+        // Vec::clone_from keeps the allocation and calls Slot::clone_from on the
+        // common prefix, so values occupied in both maps are cloned in place
+        // Vec::clone_from 保留已有分配，并对公共前缀调用 Slot::clone_from，
+        // 因此两个 map 中都占用的值会被原地克隆
         self.slots.clone_from(&source.slots);
         self.free_head = source.free_head;
         self.num_elems = source.num_elems;
+        self.num_reserved = source.num_reserved;
+        self.growth = source.growth;
+        self.fresh_generation = source.fresh_generation;
+        self.fifo = source.fifo;
+        self.free_tail = source.free_tail;
+        self.bump_epoch();
         #[cfg(debug_assertions)]
         {
             self.map_id = NEXT_MAP_ID.fetch_add(1, Ordering::Relaxed);
@@ -868,13 +4018,266 @@ impl<T: Clone, K: crate::Key> Clone for DeferredMap<T, K> {
     }
 }
 
-impl<T> Default for DeferredMap<T> {
+impl<T, K: crate::Key> Default for DeferredMap<T, K> {
     #[inline]
     fn default() -> Self {
-        Self::new()
+        Self::with_key()
+    }
+}
+
+/// A value removed with [`DeferredMap::remove_guarded`] that can still be put back
+///
+/// `restore` re-occupies the original slot under the original key, undoing the
+/// generation bump of the removal; `commit` finalizes the removal and hands the
+/// value out. Dropping the guard is the same as committing and dropping the value.
+///
+/// 通过 [`DeferredMap::remove_guarded`] 删除、但仍可放回的值
+///
+/// `restore` 以原始 key 重新占用原 slot，撤销删除时的代数递增；
+/// `commit` 确认删除并交出值。drop 该守卫等同于提交并 drop 值。
+#[derive(Debug)]
+#[must_use = "the removed value is dropped unless it is restored or committed"]
+pub struct RemovedGuard<T, K: crate::Key = crate::DefaultKey> {
+    key: K,
+    value: T,
+}
+
+impl<T, K: crate::Key> RemovedGuard<T, K> {
+    /// Key the value was stored under
+    ///
+    /// 值原来所在的 key
+    #[inline]
+    pub fn key(&self) -> K {
+        self.key
+    }
+
+    /// Get a shared reference to the removed value
+    ///
+    /// 获取被删除值的共享引用
+    #[inline]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Get a mutable reference to the removed value
+    ///
+    /// 获取被删除值的可变引用
+    #[inline]
+    pub fn value_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Finalize the removal and return the value
+    ///
+    /// 确认删除并返回值
+    #[inline]
+    pub fn commit(self) -> T {
+        self.value
+    }
+
+    /// Put the value back into `map` under its original key
+    ///
+    /// Succeeds only while the slot is untouched since the removal: still vacant
+    /// and exactly one generation ahead. If the slot has been handed out again in
+    /// the meantime (even if that Handle was later released), the original key
+    /// cannot be revived and the value is returned in `Err`.
+    ///
+    /// 将值以原始 key 放回 `map`
+    ///
+    /// 仅当 slot 自删除后未被触碰（仍为空闲且恰好领先一个代数）时才会成功。
+    /// 如果 slot 在此期间已被再次分配（即使对应的 Handle 之后被释放），
+    /// 原始 key 无法恢复，值将通过 `Err` 返回。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::DeferredMap;
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    ///
+    /// let guard = map.remove_guarded(keys[0]).unwrap();
+    /// assert_eq!(map.get(keys[0]), None);
+    ///
+    /// assert_eq!(guard.restore(&mut map), Ok(keys[0]));
+    /// assert_eq!(map.get(keys[0]), Some(&1));
+    /// ```
+    pub fn restore(self, map: &mut DeferredMap<T, K>) -> Result<K, T> {
+        #[cfg(debug_assertions)]
+        debug_assert!(
+            crate::utils::map_id_matches(map.map_id, self.key.map_id()),
+            "Key used with wrong map instance"
+        );
+
+        let index = self.key.index();
+        let mut expected = crate::Version::new(self.key.generation(), 0b11);
+        expected.occupied_to_vacant();
+
+        match map.slots.get(index as usize) {
+            Some(slot) if slot.version == expected => {}
+            _ => return Err(self.value),
+        }
+
+        map.unlink_free(index);
+
+        let slot = &mut map.slots[index as usize];
+        slot.u.value = ManuallyDrop::new(self.value);
+        slot.version = crate::Version::new(self.key.generation(), 0b11);
+
+        map.num_elems += 1;
+        map.bump_epoch();
+        Ok(self.key)
+    }
+}
+
+/// Result of [`DeferredMap::get2_mut_or_same`]
+///
+/// [`DeferredMap::get2_mut_or_same`] 的结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum OneOrTwo<'a, T> {
+    /// Both keys name the same entry
+    ///
+    /// 两个 key 指向同一个条目
+    One(&'a mut T),
+
+    /// The keys name two distinct entries, in argument order
+    ///
+    /// 两个 key 指向两个不同的条目，按参数顺序排列
+    Two(&'a mut T, &'a mut T),
+}
+
+/// Iterator returned by [`DeferredMap::extract_if`]
+///
+/// [`DeferredMap::extract_if`] 返回的迭代器
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ExtractIf<'a, T, F, K: crate::Key = crate::DefaultKey> {
+    map: &'a mut DeferredMap<T, K>,
+    index: usize, // Next slot to examine | 下一个要检查的 slot
+    pred: F,
+}
+
+impl<T, F, K: crate::Key> Iterator for ExtractIf<'_, T, F, K>
+where
+    F: FnMut(K, &mut T) -> bool,
+{
+    type Item = (K, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.map.slots.len() {
+            let i = self.index;
+            self.index += 1;
+
+            // SAFETY: Access is bounded by slots.len()
+            let slot = unsafe { self.map.slots.get_unchecked_mut(i) };
+            if !slot.is_occupied() {
+                continue;
+            }
+
+            let key = K::from_parts(
+                i as u32,
+                slot.generation(),
+                #[cfg(debug_assertions)]
+                self.map.map_id,
+            );
+            // SAFETY: We've checked that slot is occupied
+            if (self.pred)(key, unsafe { &mut *slot.u.value }) {
+                // SAFETY: i is in bounds and the slot is occupied
+                return Some((key, unsafe { self.map.vacate(i as u32) }));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.len()))
+    }
+}
+
+impl<T, F, K: crate::Key> FusedIterator for ExtractIf<'_, T, F, K> where F: FnMut(K, &mut T) -> bool {}
+
+/// Iterator over the occupied entries of one index range, returned by [`DeferredMap::split_mut`]
+///
+/// [`DeferredMap::split_mut`] 返回的迭代器，遍历一个索引区间内的已占用条目
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IterMutRange<'a, T, K: crate::Key = crate::DefaultKey> {
+    inner: std::slice::IterMut<'a, Slot<T>>,
+    index: usize, // Slot index of the next item of `inner` | `inner` 下一项的 slot 索引
+    #[cfg(debug_assertions)]
+    map_id: u64,
+    _marker: std::marker::PhantomData<K>,
+}
+
+impl<'a, T, K: crate::Key> Iterator for IterMutRange<'a, T, K> {
+    type Item = (K, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for slot in self.inner.by_ref() {
+            let index = self.index;
+            self.index += 1;
+            if slot.is_occupied() {
+                let key = K::from_parts(
+                    index as u32,
+                    slot.generation(),
+                    #[cfg(debug_assertions)]
+                    self.map_id,
+                );
+                // SAFETY: We've checked that slot is occupied
+                return Some((key, unsafe { &mut *slot.u.value }));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.inner.len()))
+    }
+}
+
+impl<T, K: crate::Key> FusedIterator for IterMutRange<'_, T, K> {}
+
+/// Default for `fresh_generation` when deserializing data written without it
+///
+/// 反序列化不含 `fresh_generation` 的数据时使用的默认值
+#[cfg(feature = "serde")]
+fn min_generation() -> crate::Generation {
+    crate::Generation::MIN
+}
+
+/// Occupancy check hinted towards the common case for the map's density
+///
+/// Dense maps mostly see occupied slots and sparse maps mostly vacant ones, so the
+/// hint follows `dense` instead of being fixed at compile time.
+///
+/// 按 map 的密度偏向常见情况的占用检查
+///
+/// 稠密的 map 大多遇到占用的 slot，稀疏的 map 大多遇到空闲的 slot，
+/// 因此提示跟随 `dense`，而不是在编译期固定。
+#[inline(always)]
+fn hinted_occupied(dense: bool, occupied: bool) -> bool {
+    if dense {
+        likely(occupied)
+    } else {
+        unlikely(occupied)
     }
 }
 
+/// Index of a slot pushed onto a slot vector of length `len`
+///
+/// 在长度为 `len` 的 slot 向量末尾追加的 slot 的索引
+///
+/// # Panics
+/// Panics if the new slot would push `slots.len()` past `u32::MAX`.
+///
+/// 如果新 slot 会使 `slots.len()` 超过 `u32::MAX` 则 panic。
+#[inline(always)]
+fn fresh_index(len: usize) -> u32 {
+    if unlikely(len >= u32::MAX as usize) {
+        panic!("DeferredMap index space exhausted");
+    }
+    len as u32
+}
+
 #[cfg(test)]
 mod basic_tests {
     use super::*;
@@ -1148,4 +4551,67 @@ mod basic_tests {
         // New key is valid | 新 key 有效
         assert_eq!(map.get(new_key), Some(&100));
     }
+
+    #[test]
+    fn test_double_remove_does_not_corrupt_free_list() {
+        let mut map = DeferredMap::new();
+
+        let h1 = map.allocate_handle();
+        let k1 = h1.key();
+        map.insert(h1, 1);
+
+        // Second remove of the same key is rejected by the generation check,
+        // so the slot is only pushed onto the free list once
+        // 同一 key 的第二次删除会被 generation 检查拒绝，slot 只会进入空闲列表一次
+        assert_eq!(map.remove(k1), Some(1));
+        assert_eq!(map.remove(k1), None);
+
+        let h2 = map.allocate_handle();
+        let h3 = map.allocate_handle();
+        assert_ne!(h2.index(), h3.index());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Free list corrupted")]
+    fn test_allocate_detects_corrupt_free_list() {
+        let mut map = DeferredMap::new();
+
+        let h1 = map.allocate_handle();
+        let index = h1.index();
+        map.insert(h1, 1);
+
+        // Point free_head at an occupied slot
+        // 让 free_head 指向一个被占用的 slot
+        map.free_head = index;
+        let _ = map.allocate_handle();
+    }
+
+    #[test]
+    fn test_fresh_index_near_limit() {
+        assert_eq!(fresh_index(1), 1);
+        assert_eq!(fresh_index(u32::MAX as usize - 1), u32::MAX - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "DeferredMap index space exhausted")]
+    fn test_fresh_index_at_limit() {
+        fresh_index(u32::MAX as usize);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    #[should_panic(expected = "DeferredMap index space exhausted")]
+    fn test_fresh_index_past_limit() {
+        fresh_index(u32::MAX as usize + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid index: index space exhausted")]
+    fn test_insert_at_rejects_last_index() {
+        let mut map = DeferredMap::<u32>::new();
+        // Rejected before any slot is materialized
+        // 在实例化任何 slot 之前就被拒绝
+        unsafe { map.insert_at(u32::MAX, crate::Generation::MIN, 1) };
+    }
 }
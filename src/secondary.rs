@@ -1,5 +1,59 @@
+//! `SecondaryMap` and its iterator types
+//!
+//! `SecondaryMap` 及其迭代器类型
+
+use crate::map::DeferredMap;
 use crate::utils::unlikely;
 use std::fmt;
+use std::iter::FusedIterator;
+
+/// How `SecondaryMap::insert` treats a key whose generation differs from the stored entry
+///
+/// A key with the same generation always replaces the value in place, and an
+/// empty slot always accepts the key; the policy only decides generation conflicts.
+/// Set it with [`SecondaryMap::with_policy`].
+///
+/// `SecondaryMap::insert` 如何处理代数与已存储条目不同的 key
+///
+/// 代数相同的 key 总是就地替换值，空 slot 总是接受 key；策略只决定代数冲突的处理方式。
+/// 通过 [`SecondaryMap::with_policy`] 设置。
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ConflictPolicy {
+    /// A newer key overwrites the stored entry, an older key is ignored (the default)
+    ///
+    /// 较新的 key 覆盖已存储条目，较旧的 key 被忽略（默认）
+    #[default]
+    OverwriteStale,
+
+    /// Any generation mismatch leaves the stored entry untouched
+    ///
+    /// `insert` ignores the key and `try_insert` reports `GenerationMismatch`.
+    ///
+    /// 任何代数不匹配都保持已存储条目不变
+    ///
+    /// `insert` 忽略该 key，`try_insert` 报告 `GenerationMismatch`。
+    Strict,
+
+    /// The incoming key always wins, even if it is older than the stored entry
+    ///
+    /// 传入的 key 总是胜出，即使它比已存储条目更旧
+    AlwaysOverwrite,
+}
+
+impl ConflictPolicy {
+    /// Whether a key of generation `incoming` may replace an entry of generation `stored`
+    ///
+    /// 代数为 `incoming` 的 key 是否可以替换代数为 `stored` 的条目
+    #[inline]
+    fn overwrites(self, stored: crate::Generation, incoming: crate::Generation) -> bool {
+        match self {
+            ConflictPolicy::OverwriteStale => stored < incoming,
+            ConflictPolicy::Strict => false,
+            ConflictPolicy::AlwaysOverwrite => true,
+        }
+    }
+}
 
 /// 存储值及其所属的代数（generation）。
 /// Internal slot storage for SecondaryMap.
@@ -8,6 +62,12 @@ use std::fmt;
 ///
 /// SecondaryMap 的内部 slot 存储。
 /// 存储值及其所属的代数（generation）。
+///
+/// `generation` is a `NonZeroU32`, so `Option<Slot<T>>` is the same size as
+/// `Slot<T>` for every `T`: `None` uses the zero generation (or a niche of `T`).
+///
+/// `generation` 是 `NonZeroU32`，因此对任意 `T`，`Option<Slot<T>>` 与 `Slot<T>`
+/// 大小相同：`None` 使用零代数（或 `T` 的 niche）表示。
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Slot<T> {
@@ -72,6 +132,10 @@ pub struct SecondaryMap<T, K: crate::Key = crate::DefaultKey> {
     // Even if Some is present, we must check generation matching.
     slots: Vec<Option<Slot<T>>>,
     num_elems: usize,
+    // Missing in data written before the policy existed, which means the default
+    // 在策略出现之前写入的数据中缺失，此时即为默认值
+    #[cfg_attr(feature = "serde", serde(default))]
+    policy: ConflictPolicy,
     #[cfg(debug_assertions)]
     map_id: Option<u64>,
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -93,6 +157,7 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         Self {
             slots: Vec::new(),
             num_elems: 0,
+            policy: ConflictPolicy::OverwriteStale,
             #[cfg(debug_assertions)]
             map_id: None,
             _marker: std::marker::PhantomData,
@@ -107,21 +172,102 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         Self {
             slots: Vec::with_capacity(capacity),
             num_elems: 0,
+            policy: ConflictPolicy::OverwriteStale,
             #[cfg(debug_assertions)]
             map_id: None,
             _marker: std::marker::PhantomData,
         }
     }
 
+    /// Create an empty SecondaryMap that resolves generation conflicts with `policy`
+    ///
+    /// 创建一个用 `policy` 解决代数冲突的空 SecondaryMap
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{ConflictPolicy, DeferredMap, SecondaryMap};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let h = map.allocate_handle();
+    /// let old_key = map.insert(h, ());
+    /// map.remove(old_key);
+    /// let h = map.allocate_handle();
+    /// let new_key = map.insert(h, ());
+    ///
+    /// let mut sec = SecondaryMap::with_policy(ConflictPolicy::AlwaysOverwrite);
+    /// sec.insert(new_key, "new");
+    /// sec.insert(old_key, "old");
+    /// assert_eq!(sec.get(old_key), Some(&"old"));
+    /// assert_eq!(sec.get(new_key), None);
+    /// ```
+    #[inline]
+    pub fn with_policy(policy: ConflictPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::new()
+        }
+    }
+
+    /// Return the generation-conflict policy used by `insert`
+    ///
+    /// 返回 `insert` 使用的代数冲突策略
+    #[inline]
+    pub fn policy(&self) -> ConflictPolicy {
+        self.policy
+    }
+
+    /// Create a SecondaryMap sized and bound for `primary`
+    ///
+    /// Pre-reserves a slot for every index `primary` can hold without reallocating.
+    /// In debug builds the map is bound to `primary` right away, so keys from any
+    /// other map are rejected from the first insert on.
+    ///
+    /// 创建一个按 `primary` 分配容量并与之绑定的 SecondaryMap
+    ///
+    /// 为 `primary` 在不重新分配的情况下能容纳的每个索引预留 slot。
+    /// 在 debug 构建中，map 会立即与 `primary` 绑定，因此从第一次插入起就会拒绝
+    /// 来自其他 map 的 key。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let mut map: DeferredMap<&str> = DeferredMap::with_capacity(16);
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    /// map.insert(handle, "primary");
+    ///
+    /// let mut sec = SecondaryMap::for_primary(&map);
+    /// assert!(sec.capacity() > 16);
+    /// sec.insert(key, 1);
+    /// assert_eq!(sec.get(key), Some(&1));
+    /// ```
+    pub fn for_primary<U>(primary: &DeferredMap<U, K>) -> Self {
+        Self {
+            // Primary indices start after the sentinel, so cover the raw slot capacity
+            // 主 map 的索引从 sentinel 之后开始，因此覆盖原始 slot 容量
+            slots: Vec::with_capacity(primary.slots.capacity()),
+            num_elems: 0,
+            policy: ConflictPolicy::OverwriteStale,
+            #[cfg(debug_assertions)]
+            map_id: Some(primary.map_id),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
     /// Insert a value for a specific key
     ///
     /// If the key belongs to an older generation than what is currently stored, the insertion is ignored.
-    /// If the key is newer, it overwrites the existing value.
+    /// If the key is newer, it overwrites the existing value. Maps built with
+    /// [`with_policy`](Self::with_policy) resolve such mismatches by their [`ConflictPolicy`] instead.
     ///
     /// 为特定 Key 插入值
     ///
     /// 如果 Key 的代数（generation）老于当前存储的代数，插入将被忽略。
-    /// 如果 Key 是新的，它将覆盖现有值。
+    /// 如果 Key 是新的，它将覆盖现有值。通过 [`with_policy`](Self::with_policy)
+    /// 创建的 map 则按其 [`ConflictPolicy`] 处理此类不匹配。
     ///
     /// # Returns
     /// - `Some(old_value)` if a value existed for the EXACT same key (same index and generation).
@@ -130,12 +276,11 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         #[cfg(debug_assertions)]
         {
             if let Some(id) = self.map_id {
-                debug_assert_eq!(
-                    id,
-                    key.map_id(),
+                debug_assert!(
+                    crate::utils::map_id_matches(id, key.map_id()),
                     "Key used with wrong map instance in SecondaryMap"
                 );
-            } else {
+            } else if key.map_id() != crate::utils::UNBOUND_MAP_ID {
                 self.map_id = Some(key.map_id());
             }
         }
@@ -146,11 +291,7 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
 
         // Ensure we have enough slots
         // 确保有足够的 slot
-        if index >= self.slots.len() {
-            let required_additional = index - self.slots.len() + 1;
-            self.slots.reserve(required_additional);
-            self.slots.resize_with(index + 1, || None);
-        }
+        self.ensure_index(index as u32);
 
         let slot_opt = unsafe { self.slots.get_unchecked_mut(index) };
 
@@ -160,15 +301,16 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
                     // Exact match, replace value
                     // 完全匹配，替换值
                     Some(std::mem::replace(&mut slot.value, value))
-                } else if slot.generation() < generation {
-                    // Stale slot (older generation), overwrite with new data
-                    // 槽位过期（旧代数），用新数据覆盖
-                    // Note: We don't return the old value because it belongs to a different entity (older gen)
+                } else if self.policy.overwrites(slot.generation(), generation) {
+                    // Generation conflict resolved in favor of the incoming key
+                    // (by default: the stored entry is stale)
+                    // 代数冲突以传入的 key 胜出（默认情况：已存储条目已过期）
+                    // Note: We don't return the old value because it belongs to a different entity
                     *slot = Slot::new(value, generation);
                     None
                 } else {
-                    // Incoming key is older than stored data, ignore insert
-                    // 传入的 Key 比存储的数据旧，忽略插入
+                    // Stored entry wins (by default: incoming key is older), ignore insert
+                    // 已存储条目胜出（默认情况：传入的 Key 更旧），忽略插入
                     None
                 }
             }
@@ -182,6 +324,157 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         }
     }
 
+    /// Insert a value for a specific key, rejecting keys older than the stored data
+    ///
+    /// Unlike `insert`, a key whose generation is older than the stored entry's is
+    /// reported as an error instead of being silently ignored. More generally, any
+    /// key the map's [`ConflictPolicy`] refuses is an error: every mismatch under
+    /// `Strict`, none under `AlwaysOverwrite`.
+    ///
+    /// 为特定 Key 插入值，拒绝比已存储数据更旧的 Key
+    ///
+    /// 与 `insert` 不同，代数比已存储条目更旧的 Key 会作为错误报告，而不是被静默忽略。
+    /// 更一般地，map 的 [`ConflictPolicy`] 拒绝的任何 key 都会报错：`Strict` 下的
+    /// 所有不匹配，`AlwaysOverwrite` 下则没有。
+    ///
+    /// # Returns
+    /// - `Ok(Some(old_value))` if a value existed for the exact same key.
+    /// - `Ok(None)` if the slot was empty or held an older generation (overwritten).
+    /// - `Err(DeferredMapError::GenerationMismatch)` if the stored generation is newer;
+    ///   the map is left unchanged.
+    ///
+    /// # 返回值
+    /// - `Ok(Some(old_value))`：完全相同的 Key 已有值。
+    /// - `Ok(None)`：slot 为空或持有更旧的代数（被覆盖）。
+    /// - `Err(DeferredMapError::GenerationMismatch)`：已存储的代数更新；map 保持不变。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, DeferredMapError, SecondaryMap};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let old_key = handle.key();
+    /// map.insert(handle, ());
+    /// map.remove(old_key);
+    /// let handle = map.allocate_handle();
+    /// let new_key = handle.key();
+    /// map.insert(handle, ());
+    ///
+    /// let mut sec = SecondaryMap::new();
+    /// assert_eq!(sec.try_insert(new_key, 1), Ok(None));
+    /// assert_eq!(sec.try_insert(new_key, 2), Ok(Some(1)));
+    /// assert_eq!(sec.try_insert(old_key, 3), Err(DeferredMapError::GenerationMismatch));
+    /// ```
+    pub fn try_insert(&mut self, key: K, value: T) -> Result<Option<T>, crate::DeferredMapError> {
+        if let Some(Some(slot)) = self.slots.get(key.index() as usize)
+            && slot.generation() != key.generation()
+            && !self.policy.overwrites(slot.generation(), key.generation())
+        {
+            return Err(crate::DeferredMapError::GenerationMismatch);
+        }
+        Ok(self.insert(key, value))
+    }
+
+    /// Merge every entry of `other` into `self`, resolving exact-generation conflicts with `conflict`
+    ///
+    /// For each entry of `other`, following the default (`OverwriteStale`) generation
+    /// rules of `insert`, whatever the policy of either map:
+    /// - absent in `self`, or `self` holds an older generation: the entry is moved in;
+    /// - same generation in both: `conflict(key, &mut mine, theirs)` decides the result in place;
+    /// - `self` holds a newer generation: the incoming entry is stale and dropped.
+    ///
+    /// 将 `other` 的所有条目合并到 `self`，用 `conflict` 解决相同代数的冲突
+    ///
+    /// 对 `other` 中的每个条目，无论两个 map 的策略如何，都遵循 `insert` 的默认
+    /// （`OverwriteStale`）代数规则：
+    /// - `self` 中不存在，或 `self` 持有更旧的代数：移入该条目；
+    /// - 两者代数相同：由 `conflict(key, &mut mine, theirs)` 就地决定结果；
+    /// - `self` 持有更新的代数：传入的条目已过期并被 drop。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values([(), ()]);
+    ///
+    /// let mut a = SecondaryMap::new();
+    /// a.insert(keys[0], 1);
+    /// let mut b = SecondaryMap::new();
+    /// b.insert(keys[0], 10);
+    /// b.insert(keys[1], 20);
+    ///
+    /// a.merge_from(b, |_, mine, theirs| *mine += theirs);
+    /// assert_eq!(a.get(keys[0]), Some(&11));
+    /// assert_eq!(a.get(keys[1]), Some(&20));
+    /// ```
+    pub fn merge_from<F>(&mut self, other: Self, mut conflict: F)
+    where
+        F: FnMut(K, &mut T, T),
+    {
+        #[cfg(debug_assertions)]
+        match (self.map_id, other.map_id) {
+            (Some(mine), Some(theirs)) => debug_assert!(
+                mine == theirs,
+                "SecondaryMap merged with a map bound to another primary map"
+            ),
+            (None, Some(theirs)) => self.map_id = Some(theirs),
+            _ => {}
+        }
+
+        if let Some(last) = other.slots.len().checked_sub(1) {
+            self.ensure_index(last as u32);
+        }
+
+        for (index, incoming) in other.slots.into_iter().enumerate() {
+            let Some(incoming) = incoming else {
+                continue;
+            };
+
+            // SAFETY: ensure_index grew slots to cover every index of `other`
+            let slot_opt = unsafe { self.slots.get_unchecked_mut(index) };
+            match slot_opt {
+                None => {
+                    *slot_opt = Some(incoming);
+                    self.num_elems += 1;
+                }
+                Some(slot) if slot.generation() < incoming.generation() => *slot = incoming,
+                Some(slot) if slot.generation() == incoming.generation() => {
+                    let key = K::from_parts(
+                        index as u32,
+                        slot.generation(),
+                        #[cfg(debug_assertions)]
+                        self.map_id.unwrap_or(crate::utils::UNBOUND_MAP_ID),
+                    );
+                    conflict(key, &mut slot.value, incoming.value);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Take the value out by key, same as `remove` (mirrors `DeferredMap::take`)
+    ///
+    /// 通过 key 取出值，与 `remove` 相同（对应 `DeferredMap::take`）
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values([()]);
+    /// let mut sec: SecondaryMap<_> = keys.iter().map(|&k| (k, 5)).collect();
+    ///
+    /// assert_eq!(sec.take(keys[0]), Some(5));
+    /// assert_eq!(sec.take(keys[0]), None);
+    /// ```
+    #[inline]
+    pub fn take(&mut self, key: K) -> Option<T> {
+        self.remove(key)
+    }
+
     /// Remove value by key
     ///
     /// Only removes if both index and generation match.
@@ -192,9 +485,8 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
     pub fn remove(&mut self, key: K) -> Option<T> {
         #[cfg(debug_assertions)]
         if let Some(id) = self.map_id {
-            debug_assert_eq!(
-                id,
-                key.map_id(),
+            debug_assert!(
+                crate::utils::map_id_matches(id, key.map_id()),
                 "Key used with wrong map instance in SecondaryMap"
             );
         }
@@ -209,11 +501,11 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
 
         let slot_opt = unsafe { self.slots.get_unchecked_mut(index) };
 
-        if let Some(slot) = slot_opt {
-            if slot.generation() == generation {
-                self.num_elems -= 1;
-                return slot_opt.take().map(|s| s.value);
-            }
+        if let Some(slot) = slot_opt
+            && slot.generation() == generation
+        {
+            self.num_elems -= 1;
+            return slot_opt.take().map(|s| s.value);
         }
 
         None
@@ -226,9 +518,8 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
     pub fn get(&self, key: K) -> Option<&T> {
         #[cfg(debug_assertions)]
         if let Some(id) = self.map_id {
-            debug_assert_eq!(
-                id,
-                key.map_id(),
+            debug_assert!(
+                crate::utils::map_id_matches(id, key.map_id()),
                 "Key used with wrong map instance in SecondaryMap"
             );
         }
@@ -255,9 +546,8 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
     pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
         #[cfg(debug_assertions)]
         if let Some(id) = self.map_id {
-            debug_assert_eq!(
-                id,
-                key.map_id(),
+            debug_assert!(
+                crate::utils::map_id_matches(id, key.map_id()),
                 "Key used with wrong map instance in SecondaryMap"
             );
         }
@@ -277,6 +567,117 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         }
     }
 
+    /// Get a mutable reference to the value for `key`, inserting `f()` first if absent
+    ///
+    /// An entry of the same generation is returned as is. An empty slot, or a
+    /// stale entry that `insert` would overwrite (by default: an older generation),
+    /// is replaced by `f()`; `f` is only called in that case.
+    ///
+    /// 获取 `key` 对应值的可变引用，若不存在则先插入 `f()`
+    ///
+    /// 代数相同的条目原样返回。空 slot，或 `insert` 会覆盖的过期条目（默认情况：更旧的代数）
+    /// 会被替换为 `f()`；只有这种情况下才会调用 `f`。
+    ///
+    /// # Panics (恐慌)
+    ///
+    /// Panics if the slot holds an entry of another generation that the map's
+    /// [`ConflictPolicy`] refuses to overwrite (by default: `key` is older than the
+    /// stored entry), since `insert` would ignore the key and there is no entry to return.
+    ///
+    /// 如果 slot 持有另一个代数的条目，且 map 的 [`ConflictPolicy`] 拒绝覆盖它
+    /// （默认情况：`key` 比已存储条目更旧），则 panic，因为 `insert` 会忽略该 key，没有可返回的条目。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values(["a", "b"]);
+    /// let mut names: SecondaryMap<Vec<&str>> = SecondaryMap::new();
+    ///
+    /// names.get_or_insert_with(keys[0], Vec::new).push("x");
+    /// names.get_or_insert_with(keys[0], || unreachable!()).push("y");
+    /// assert_eq!(names.get(keys[0]), Some(&vec!["x", "y"]));
+    /// ```
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        #[cfg(debug_assertions)]
+        {
+            if let Some(id) = self.map_id {
+                debug_assert!(
+                    crate::utils::map_id_matches(id, key.map_id()),
+                    "Key used with wrong map instance in SecondaryMap"
+                );
+            } else if key.map_id() != crate::utils::UNBOUND_MAP_ID {
+                self.map_id = Some(key.map_id());
+            }
+        }
+
+        let generation = key.generation();
+        let index = key.index();
+        self.ensure_index(index);
+
+        // SAFETY: ensure_index grew slots to cover index
+        let slot_opt = unsafe { self.slots.get_unchecked_mut(index as usize) };
+        match slot_opt {
+            Some(slot) if slot.generation() == generation => {}
+            Some(slot) => {
+                assert!(
+                    self.policy.overwrites(slot.generation(), generation),
+                    "SecondaryMap entry of generation {:?} refuses key of generation {:?}",
+                    slot.generation(),
+                    generation
+                );
+                *slot = Slot::new(f(), generation);
+            }
+            None => {
+                *slot_opt = Some(Slot::new(f(), generation));
+                self.num_elems += 1;
+            }
+        }
+
+        // Every arm above leaves an entry of this generation in the slot
+        // 上面的每个分支都会在 slot 中留下该代数的条目
+        match slot_opt {
+            Some(slot) => &mut slot.value,
+            None => unreachable!(),
+        }
+    }
+
+    /// Get a mutable reference to the value for `key`, inserting `T::default()` if absent
+    ///
+    /// Same as [`get_or_insert_with`](Self::get_or_insert_with)`(key, T::default)`,
+    /// including its panics; the natural fit for accumulators and histograms.
+    ///
+    /// 获取 `key` 对应值的可变引用，若不存在则插入 `T::default()`
+    ///
+    /// 等同于 [`get_or_insert_with`](Self::get_or_insert_with)`(key, T::default)`，
+    /// panic 条件也相同；非常适合累加器和直方图。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values(["a", "b"]);
+    /// let mut hits: SecondaryMap<u32> = SecondaryMap::new();
+    ///
+    /// for key in [keys[0], keys[1], keys[0]] {
+    ///     *hits.entry_or_default(key) += 1;
+    /// }
+    /// assert_eq!(hits.get(keys[0]), Some(&2));
+    /// assert_eq!(hits.get(keys[1]), Some(&1));
+    /// ```
+    #[inline]
+    pub fn entry_or_default(&mut self, key: K) -> &mut T
+    where
+        T: Default,
+    {
+        self.get_or_insert_with(key, T::default)
+    }
+
     /// Check if key exists
     ///
     /// 检查 Key 是否存在
@@ -309,6 +710,125 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         self.slots.capacity()
     }
 
+    /// Reserve capacity for exactly `additional` more slots, without over-allocating
+    ///
+    /// 精确预留 `additional` 个额外 slot 的空间，不会过度分配
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.slots.reserve_exact(additional);
+    }
+
+    /// Reserve room for every index `primary` can hold without reallocating
+    ///
+    /// After this, inserting keys from `primary` does not reallocate as long as
+    /// `primary` itself does not grow. See `DeferredMap::reserve_like` for the
+    /// other direction.
+    ///
+    /// 为 `primary` 在不重新分配的情况下能容纳的每个索引预留空间
+    ///
+    /// 此后只要 `primary` 本身不增长，插入来自 `primary` 的 key 就不会重新分配。
+    /// 反方向请参见 `DeferredMap::reserve_like`。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let map: DeferredMap<i32> = DeferredMap::with_capacity(32);
+    /// let mut sec: SecondaryMap<u8> = SecondaryMap::new();
+    /// sec.reserve_like(&map);
+    /// assert!(sec.capacity() > 32);
+    /// ```
+    #[inline]
+    pub fn reserve_like<U>(&mut self, primary: &DeferredMap<U, K>) {
+        // Primary indices start after the sentinel, so cover the raw slot capacity
+        // 主 map 的索引从 sentinel 之后开始，因此覆盖原始 slot 容量
+        let additional = primary.slots.capacity().saturating_sub(self.slots.len());
+        self.slots.reserve(additional);
+    }
+
+    /// Materialize empty slots up to and including `index` without inserting anything
+    ///
+    /// Unlike `reserve_exact`, which only reserves heap space, this actually grows the
+    /// slot vector with empty entries, so later `insert`s at indices up to `index`
+    /// take the in-bounds path and never reallocate. Does nothing if `index` is
+    /// already in range.
+    ///
+    /// 在不插入任何内容的情况下，实际创建直到 `index`（含）的空 slot
+    ///
+    /// 与只预留堆空间的 `reserve_exact` 不同，此方法会用空条目真正扩展 slot 向量，
+    /// 因此之后在不超过 `index` 的索引处 `insert` 会走边界内路径且不会重新分配。
+    /// 如果 `index` 已在范围内，则不做任何事。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::SecondaryMap;
+    ///
+    /// let mut sec: SecondaryMap<u32> = SecondaryMap::new();
+    /// sec.ensure_index(63);
+    ///
+    /// assert!(sec.capacity() >= 64);
+    /// assert!(sec.is_empty());
+    /// ```
+    pub fn ensure_index(&mut self, index: u32) {
+        let index = index as usize;
+        if index >= self.slots.len() {
+            let required_additional = index - self.slots.len() + 1;
+            self.slots.reserve(required_additional);
+            self.slots.resize_with(index + 1, || None);
+        }
+    }
+
+    /// Truncate the map to indices `0..=max_index` and release the excess memory
+    ///
+    /// Meant to be called after the primary map has been compacted or shrunk, so the
+    /// secondary map stops carrying slots sized for the old index range.
+    /// Entries stored at indices above `max_index` are dropped and lost.
+    ///
+    /// 将 map 截断为索引 `0..=max_index` 并释放多余内存
+    ///
+    /// 用于在主 map 压缩或收缩之后调用，使辅助 map 不再保留按旧索引范围分配的 slot。
+    /// 存储在 `max_index` 之上的条目会被 drop 并丢失。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let keys: Vec<_> = (0..4)
+    ///     .map(|i| {
+    ///         let handle = map.allocate_handle();
+    ///         let key = handle.key();
+    ///         map.insert(handle, i);
+    ///         key
+    ///     })
+    ///     .collect();
+    ///
+    /// let mut sec = SecondaryMap::new();
+    /// for &key in &keys {
+    ///     sec.insert(key, ());
+    /// }
+    ///
+    /// sec.shrink_to_len(2);
+    /// assert_eq!(sec.len(), 2);
+    /// assert!(sec.contains_key(keys[1]));
+    /// assert!(!sec.contains_key(keys[2]));
+    /// ```
+    pub fn shrink_to_len(&mut self, max_index: u32) {
+        let new_len = max_index as usize + 1;
+        if new_len < self.slots.len() {
+            let removed = self.slots[new_len..]
+                .iter()
+                .filter(|slot| slot.is_some())
+                .count();
+            self.slots.truncate(new_len);
+            self.num_elems -= removed;
+        }
+        self.slots.shrink_to_fit();
+    }
+
     /// Clear all elements
     ///
     /// Does not deallocate memory, but clears validity.
@@ -327,7 +847,24 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
     /// Retains only the elements specified by the predicate.
     ///
     /// 只保留满足谓词的元素。
-    pub fn retain<F>(&mut self, mut f: F)
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(K, &mut T) -> bool,
+    {
+        self.retain_mut(f);
+    }
+
+    /// Retains only the elements specified by the predicate, letting it mutate them.
+    ///
+    /// Mutations to kept elements persist; elements for which the predicate returns
+    /// `false` are removed and dropped. Same contract as `Vec::retain_mut`.
+    ///
+    /// 只保留满足谓词的元素，并允许谓词修改它们。
+    ///
+    /// 对保留元素的修改会持久生效；谓词返回 `false` 的元素会被移除并 drop。
+    /// 与 `Vec::retain_mut` 的约定相同。
+    pub fn retain_mut<F>(&mut self, mut f: F)
     where
         F: FnMut(K, &mut T) -> bool,
     {
@@ -337,7 +874,7 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
                     index as u32,
                     slot.generation,
                     #[cfg(debug_assertions)]
-                    self.map_id.unwrap_or(0),
+                    self.map_id.unwrap_or(crate::utils::UNBOUND_MAP_ID),
                 );
 
                 if !f(key, &mut slot.value) {
@@ -348,48 +885,430 @@ impl<T, K: crate::Key> SecondaryMap<T, K> {
         }
     }
 
+    /// Remove and return every entry whose key is no longer live in `primary`
+    ///
+    /// Like a `retain` that keeps live keys, but hands the removed entries back so the
+    /// caller can run teardown logic (closing files, releasing resources, ...).
+    ///
+    /// 移除并返回所有 key 在 `primary` 中已不再存活的条目
+    ///
+    /// 类似于保留存活 key 的 `retain`，但会把移除的条目交还给调用者，
+    /// 以便执行清理逻辑（关闭文件、释放资源等）。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let mut map = DeferredMap::new();
+    /// let handle = map.allocate_handle();
+    /// let key = handle.key();
+    /// map.insert(handle, "entity");
+    ///
+    /// let mut files = SecondaryMap::new();
+    /// files.insert(key, "file.txt");
+    ///
+    /// map.remove(key);
+    /// assert_eq!(files.gc_stale(&map), vec![(key, "file.txt")]);
+    /// assert!(files.is_empty());
+    /// ```
+    pub fn gc_stale<U>(&mut self, primary: &DeferredMap<U, K>) -> Vec<(K, T)> {
+        #[cfg(debug_assertions)]
+        if let Some(id) = self.map_id {
+            debug_assert!(
+                id == primary.map_id,
+                "SecondaryMap used with wrong primary map instance"
+            );
+        }
+
+        let mut removed = Vec::new();
+        for (index, slot_opt) in self.slots.iter_mut().enumerate() {
+            if let Some(slot) = slot_opt {
+                let key = K::from_parts(
+                    index as u32,
+                    slot.generation,
+                    #[cfg(debug_assertions)]
+                    primary.map_id,
+                );
+
+                if !primary.contains_key(key) {
+                    let slot = slot_opt.take().unwrap();
+                    self.num_elems -= 1;
+                    removed.push((key, slot.value));
+                }
+            }
+        }
+        removed
+    }
+
     /// Iterator over all (key, value) pairs
     ///
     /// 遍历所有 (key, value) 对的迭代器
-    pub fn iter(&self) -> impl Iterator<Item = (K, &T)> {
+    pub fn iter(&self) -> Iter<'_, T, K> {
+        Iter {
+            inner: self.slots.iter().enumerate(),
+            remaining: self.num_elems,
+            #[cfg(debug_assertions)]
+            map_id: self.map_id.unwrap_or(crate::utils::UNBOUND_MAP_ID),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Iterator over (index, generation, value) for every stored entry, in increasing index order
+    ///
+    /// 按索引递增顺序遍历每个已存储条目的 (index, generation, value)
+    pub fn iter_with_index(&self) -> impl Iterator<Item = (u32, crate::Generation, &T)> {
         self.slots
             .iter()
             .enumerate()
-            .filter_map(move |(index, slot_opt)| {
-                slot_opt.as_ref().map(|slot| {
-                    let key = K::from_parts(
-                        index as u32,
-                        slot.generation,
-                        #[cfg(debug_assertions)]
-                        self.map_id.unwrap_or(0),
-                    );
-                    (key, &slot.value)
-                })
+            .filter_map(|(index, slot_opt)| {
+                slot_opt
+                    .as_ref()
+                    .map(|slot| (index as u32, slot.generation, &slot.value))
             })
     }
 
-    /// Mutable iterator over all (key, value) pairs
+    /// Iterator over the (key, value) pairs whose key is still live in `primary`
     ///
-    /// 遍历所有 (key, value) 对的可变迭代器
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (K, &mut T)> {
+    /// Entries left behind by removed primary entries are skipped (but not removed;
+    /// see `gc_stale` for that).
+    ///
+    /// 遍历 key 在 `primary` 中仍然存活的 (key, value) 对
+    ///
+    /// 由已删除的主条目遗留的条目会被跳过（但不会被移除；移除请参见 `gc_stale`）。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (mut map, keys) = DeferredMap::<_>::from_values(["a", "b"]);
+    /// let sec: SecondaryMap<i32> = keys.iter().map(|&k| (k, 1)).collect();
+    ///
+    /// map.remove(keys[0]);
+    /// let live: Vec<_> = sec.iter_live(&map).map(|(k, _)| k).collect();
+    /// assert_eq!(live, [keys[1]]);
+    /// ```
+    pub fn iter_live<'a, U>(
+        &'a self,
+        primary: &'a DeferredMap<U, K>,
+    ) -> impl Iterator<Item = (K, &'a T)> {
         #[cfg(debug_assertions)]
-        let map_id = self.map_id;
+        if let Some(id) = self.map_id {
+            debug_assert!(
+                id == primary.map_id,
+                "SecondaryMap used with wrong primary map instance"
+            );
+        }
 
         self.slots
-            .iter_mut()
+            .iter()
             .enumerate()
             .filter_map(move |(index, slot_opt)| {
-                slot_opt.as_mut().map(|slot| {
-                    let key = K::from_parts(
-                        index as u32,
-                        slot.generation,
-                        #[cfg(debug_assertions)]
-                        map_id.unwrap_or(0),
-                    );
-                    (key, &mut slot.value)
-                })
+                let slot = slot_opt.as_ref()?;
+                let key = K::from_parts(
+                    index as u32,
+                    slot.generation,
+                    #[cfg(debug_assertions)]
+                    primary.map_id,
+                );
+                primary.contains_key(key).then_some((key, &slot.value))
             })
     }
+
+    /// Mutable iterator over all (key, value) pairs
+    ///
+    /// 遍历所有 (key, value) 对的可变迭代器
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, K> {
+        IterMut {
+            remaining: self.num_elems,
+            #[cfg(debug_assertions)]
+            map_id: self.map_id.unwrap_or(crate::utils::UNBOUND_MAP_ID),
+            inner: self.slots.iter_mut().enumerate(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Return an iterator over all keys
+    ///
+    /// 返回一个遍历所有 key 的迭代器
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values([(), ()]);
+    /// let sec: SecondaryMap<_> = keys.iter().map(|&k| (k, 0)).collect();
+    ///
+    /// assert_eq!(sec.keys().count(), 2);
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Return a mutable iterator over all values
+    ///
+    /// 返回一个遍历所有值的可变迭代器
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values([()]);
+    /// let mut sec: SecondaryMap<_> = keys.iter().map(|&k| (k, 1)).collect();
+    ///
+    /// for value in sec.values_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(sec.get(keys[0]), Some(&2));
+    /// ```
+    #[inline]
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Consume the map and map every value through `f`, keeping indices and generations
+    ///
+    /// Every key that resolves in this map resolves in the result, which is handy
+    /// for deriving one side-table from another.
+    ///
+    /// 消耗 map 并通过 `f` 映射每个值，保持索引和代数不变
+    ///
+    /// 在此 map 中能解析的每个 key 在结果中也能解析，便于从一个辅助表派生另一个。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values([(), ()]);
+    /// let speeds: SecondaryMap<_> = keys.iter().map(|&k| (k, 2.0f32)).collect();
+    ///
+    /// let labels = speeds.map_values(|v| format!("{v} m/s"));
+    /// assert_eq!(labels.get(keys[1]).map(String::as_str), Some("2 m/s"));
+    /// ```
+    pub fn map_values<U, F>(self, mut f: F) -> SecondaryMap<U, K>
+    where
+        F: FnMut(T) -> U,
+    {
+        SecondaryMap {
+            slots: self
+                .slots
+                .into_iter()
+                .map(|slot| slot.map(|slot| Slot::new(f(slot.value), slot.generation)))
+                .collect(),
+            num_elems: self.num_elems,
+            policy: self.policy,
+            #[cfg(debug_assertions)]
+            map_id: self.map_id,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Get mutable references to `N` values without checking presence or disjointness
+    ///
+    /// 获取 `N` 个值的可变引用，不检查是否存在或是否互不相交
+    ///
+    /// # Safety
+    /// Every key must be present in the map (`get` would return `Some`), and no two
+    /// keys may share an index.
+    ///
+    /// 每个 key 都必须存在于 map 中（`get` 会返回 `Some`），且任意两个 key 的索引都不能相同。
+    ///
+    /// # Examples (示例)
+    ///
+    /// ```
+    /// use deferred_map::{DeferredMap, SecondaryMap};
+    ///
+    /// let (_map, keys) = DeferredMap::<_>::from_values([(), ()]);
+    /// let mut sec: SecondaryMap<_> = keys.iter().map(|&k| (k, 1)).collect();
+    ///
+    /// // SAFETY: both keys are present and distinct
+    /// let [a, b] = unsafe { sec.get_disjoint_unchecked_mut([keys[0], keys[1]]) };
+    /// std::mem::swap(a, b);
+    /// ```
+    pub unsafe fn get_disjoint_unchecked_mut<const N: usize>(
+        &mut self,
+        keys: [K; N],
+    ) -> [&mut T; N] {
+        debug_assert!(
+            keys.iter().all(|&key| self.contains_key(key)),
+            "get_disjoint_unchecked_mut called with a missing key"
+        );
+        debug_assert!(
+            keys.iter()
+                .enumerate()
+                .all(|(i, key)| keys[..i].iter().all(|k| k.index() != key.index())),
+            "get_disjoint_unchecked_mut called with aliasing keys"
+        );
+
+        let slots = self.slots.as_mut_ptr();
+        // SAFETY: the caller guarantees every key is present and the indices are distinct
+        keys.map(|key| unsafe {
+            match &mut *slots.add(key.index() as usize) {
+                Some(slot) => &mut slot.value,
+                None => std::hint::unreachable_unchecked(),
+            }
+        })
+    }
+}
+
+/// Iterator over the (key, value) pairs of a SecondaryMap, in increasing index order
+///
+/// Created by `SecondaryMap::iter`.
+///
+/// 按索引递增顺序遍历 SecondaryMap 中 (key, value) 对的迭代器
+///
+/// 由 `SecondaryMap::iter` 创建。
+pub struct Iter<'a, T, K: crate::Key = crate::DefaultKey> {
+    inner: std::iter::Enumerate<std::slice::Iter<'a, Option<Slot<T>>>>,
+    remaining: usize, // Entries not yet yielded | 尚未产出的条目数
+    #[cfg(debug_assertions)]
+    map_id: u64,
+    _marker: std::marker::PhantomData<K>,
+}
+
+impl<'a, T, K: crate::Key> Iterator for Iter<'a, T, K> {
+    type Item = (K, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot_opt) in self.inner.by_ref() {
+            if let Some(slot) = slot_opt {
+                self.remaining -= 1;
+                let key = K::from_parts(
+                    index as u32,
+                    slot.generation,
+                    #[cfg(debug_assertions)]
+                    self.map_id,
+                );
+                return Some((key, &slot.value));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, K: crate::Key> ExactSizeIterator for Iter<'_, T, K> {}
+
+impl<T, K: crate::Key> FusedIterator for Iter<'_, T, K> {}
+
+impl<T, K: crate::Key> Clone for Iter<'_, T, K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            remaining: self.remaining,
+            #[cfg(debug_assertions)]
+            map_id: self.map_id,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Mutable iterator over the (key, value) pairs of a SecondaryMap, in increasing index order
+///
+/// Created by `SecondaryMap::iter_mut`.
+///
+/// 按索引递增顺序遍历 SecondaryMap 中 (key, value) 对的可变迭代器
+///
+/// 由 `SecondaryMap::iter_mut` 创建。
+pub struct IterMut<'a, T, K: crate::Key = crate::DefaultKey> {
+    inner: std::iter::Enumerate<std::slice::IterMut<'a, Option<Slot<T>>>>,
+    remaining: usize, // Entries not yet yielded | 尚未产出的条目数
+    #[cfg(debug_assertions)]
+    map_id: u64,
+    _marker: std::marker::PhantomData<K>,
+}
+
+impl<'a, T, K: crate::Key> Iterator for IterMut<'a, T, K> {
+    type Item = (K, &'a mut T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot_opt) in self.inner.by_ref() {
+            if let Some(slot) = slot_opt {
+                self.remaining -= 1;
+                let key = K::from_parts(
+                    index as u32,
+                    slot.generation,
+                    #[cfg(debug_assertions)]
+                    self.map_id,
+                );
+                return Some((key, &mut slot.value));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, K: crate::Key> ExactSizeIterator for IterMut<'_, T, K> {}
+
+impl<T, K: crate::Key> FusedIterator for IterMut<'_, T, K> {}
+
+impl<'a, T, K: crate::Key> IntoIterator for &'a SecondaryMap<T, K> {
+    type Item = (K, &'a T);
+    type IntoIter = Iter<'a, T, K>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, K: crate::Key> IntoIterator for &'a mut SecondaryMap<T, K> {
+    type Item = (K, &'a mut T);
+    type IntoIter = IterMut<'a, T, K>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Collect (key, value) pairs into a SecondaryMap
+///
+/// Pairs are inserted in order with the same rules as `insert`: for keys sharing an
+/// index, the newer generation wins regardless of iteration order.
+///
+/// 将 (key, value) 对收集为 SecondaryMap
+///
+/// 按顺序以与 `insert` 相同的规则插入：对于共享同一索引的 key，无论迭代顺序如何，
+/// 较新的代数胜出。
+///
+/// # Examples (示例)
+///
+/// ```
+/// use deferred_map::{DeferredMap, SecondaryMap};
+///
+/// let mut map = DeferredMap::new();
+/// let handle = map.allocate_handle();
+/// let key = handle.key();
+/// map.insert(handle, "primary");
+///
+/// let sec: SecondaryMap<i32> = map.iter().map(|(k, _)| (k, 1)).collect();
+/// assert_eq!(sec.get(key), Some(&1));
+/// ```
+impl<T, K: crate::Key> FromIterator<(K, T)> for SecondaryMap<T, K> {
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for SecondaryMap<T> {
@@ -407,8 +1326,7 @@ mod tests {
     fn test_slot_niche_optimization() {
         // Slot<u32> layout:
         // value: u32 (4 bytes)
-        // value: u32 (4 bytes)
-        // generation_p1: Generation (4 bytes)
+        // generation: Generation (4 bytes)
         // Total: 8 bytes
         // Option<Slot<u32>>: Should be 8 bytes because strict NonZeroU32 allows 0 to be None.
         assert_eq!(size_of::<Slot<u32>>(), 8);
@@ -431,4 +1349,38 @@ mod tests {
             "Niche optimization failed for Slot<u64>"
         );
     }
+
+    #[test]
+    fn test_slot_niche_optimization_more_types() {
+        #[repr(C)]
+        #[allow(dead_code)]
+        struct Small {
+            a: u8,
+            b: u16,
+        }
+
+        fn assert_niche<T>(name: &str) {
+            assert_eq!(
+                size_of::<Option<Slot<T>>>(),
+                size_of::<Slot<T>>(),
+                "Niche optimization failed for Slot<{name}>"
+            );
+        }
+
+        // Whether or not `T` has a niche of its own, the Generation niche is
+        // always there, so `None` never needs an extra tag
+        // 无论 `T` 本身是否有 niche，Generation 的 niche 始终存在，因此 `None` 不需要额外的标签
+        assert_niche::<Box<u64>>("Box<u64>");
+        assert_niche::<&'static str>("&'static str");
+        assert_niche::<&'static u8>("&'static u8");
+        assert_niche::<std::num::NonZeroU32>("NonZeroU32");
+        assert_niche::<Small>("Small");
+        assert_niche::<()>("()");
+        assert_niche::<u8>("u8");
+        assert_niche::<[u64; 3]>("[u64; 3]");
+
+        assert_eq!(size_of::<Slot<Small>>(), 8);
+        assert_eq!(size_of::<Slot<()>>(), 4);
+        assert_eq!(size_of::<Slot<Box<u64>>>(), 16);
+    }
 }
@@ -8,6 +8,15 @@ use std::mem::ManuallyDrop;
 /// Slot 内部存储，使用 union 优化内存
 ///
 /// 占用时存储值，空闲时存储下一个空闲 slot 的索引
+///
+/// Which field is live is decided solely by the slot's `version` state, never by
+/// the union bytes. For zero-sized `T` the union is still 4 bytes wide (sized by
+/// `next_free`); writing or reading a ZST value touches no bytes, so it cannot
+/// clobber `next_free`, and vacating a slot writes `next_free` afresh.
+///
+/// 当前有效的字段只由 slot 的 `version` 状态决定，而不是 union 的字节。
+/// 对于零大小的 `T`，union 仍为 4 字节宽（由 `next_free` 决定）；
+/// 读写 ZST 值不会触及任何字节，因此不会破坏 `next_free`，并且腾空 slot 时会重新写入 `next_free`。
 pub(crate) union SlotUnion<T> {
     pub(crate) value: ManuallyDrop<T>,
     pub(crate) next_free: u32,
@@ -139,6 +148,59 @@ impl<T> Slot<T> {
     }
 }
 
+impl<T> Slot<T> {
+    /// Convert into a slot of another type, mapping the value if occupied
+    ///
+    /// The version (state and generation) is carried over unchanged.
+    ///
+    /// 转换为另一类型的 slot，占用时映射其值
+    ///
+    /// version（状态和代数）保持不变。
+    #[inline]
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Slot<U> {
+        self.filter_map(|value| Some(f(value)))
+    }
+
+    /// Convert into a slot of another type, mapping the value if occupied
+    ///
+    /// If `f` returns `None` the slot becomes vacant with the next generation; its
+    /// `next_free` is left for the caller to link into a free list. Otherwise the
+    /// version is carried over unchanged.
+    ///
+    /// 转换为另一类型的 slot，占用时映射其值
+    ///
+    /// 如果 `f` 返回 `None`，slot 变为下一代的空闲状态；其 `next_free` 由调用者链接到空闲列表。
+    /// 否则 version 保持不变。
+    #[inline]
+    pub(crate) fn filter_map<U>(self, f: impl FnOnce(T) -> Option<U>) -> Slot<U> {
+        let mut version = self.version;
+        // Skip Slot::drop: the value (if any) is moved out below
+        // 跳过 Slot::drop：值（如果有）会在下面被移出
+        let mut this = ManuallyDrop::new(self);
+        if !version.is_occupied() {
+            // SAFETY: not occupied, so next_free is the live field (or unused for reserved)
+            let next_free = unsafe { this.u.next_free };
+            return Slot {
+                u: SlotUnion { next_free },
+                version,
+            };
+        }
+
+        // SAFETY: occupied, and `this` is never dropped, so the value is read once
+        let value = unsafe { ManuallyDrop::take(&mut this.u.value) };
+        let u = match f(value) {
+            Some(value) => SlotUnion {
+                value: ManuallyDrop::new(value),
+            },
+            None => {
+                version.occupied_to_vacant();
+                SlotUnion { next_free: 0 }
+            }
+        };
+        Slot { u, version }
+    }
+}
+
 impl<T> Drop for Slot<T> {
     #[inline]
     fn drop(&mut self) {
@@ -166,6 +228,9 @@ impl<T: Clone> Clone for Slot<T> {
         }
     }
 
+    /// Clone `source` into this slot, reusing the existing value when both are occupied
+    ///
+    /// 将 `source` 克隆到此 slot，两者都占用时复用已有的值
     #[inline]
     fn clone_from(&mut self, source: &Self) {
         match (self.get_mut(), source.get()) {
@@ -173,12 +238,20 @@ impl<T: Clone> Clone for Slot<T> {
             (VacantMut(self_next_free), Vacant(&source_next_free)) => {
                 *self_next_free = source_next_free
             }
-            (_, Occupied(value)) => {
+            (VacantMut(_), Occupied(value)) => {
                 self.u = SlotUnion {
                     value: ManuallyDrop::new(value.clone()),
                 }
             }
-            (_, Vacant(&next_free)) => self.u = SlotUnion { next_free },
+            (OccupiedMut(_), Vacant(&next_free)) => {
+                // Take the old value out and leave the occupied state before dropping it,
+                // so a panicking Drop cannot cause a double drop
+                // 先取出旧值并离开占用状态再 drop，避免 Drop panic 时重复 drop
+                let old = unsafe { ManuallyDrop::take(&mut self.u.value) };
+                self.u = SlotUnion { next_free };
+                self.version = source.version;
+                drop(old);
+            }
         }
         self.version = source.version;
     }
@@ -0,0 +1,111 @@
+// Multithreaded tests for ConcurrentAllocator
+// ConcurrentAllocator 的多线程测试
+
+use crate::{DeferredMap, Key};
+use std::collections::HashSet;
+
+#[test]
+fn test_concurrent_allocate_reuses_free_list_first() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..4);
+    map.remove(keys[1]);
+    map.remove(keys[3]);
+
+    let (h1, h2, h3) = {
+        let allocator = map.concurrent_allocator();
+        (
+            allocator.allocate_handle(),
+            allocator.allocate_handle(),
+            allocator.allocate_handle(),
+        )
+    };
+
+    // LIFO reuse of the two freed slots, then a fresh slot at the end
+    // 先以 LIFO 顺序复用两个被释放的 slot，再在末尾分配新 slot
+    assert_eq!(h1.index(), keys[3].index());
+    assert_eq!(h2.index(), keys[1].index());
+    assert_eq!(h3.index(), 5);
+
+    map.insert(h1, 10);
+    map.insert(h2, 11);
+    map.insert(h3, 12);
+    assert_eq!(map.len(), 5);
+
+    // Regular allocation continues after the concurrently allocated slots
+    // 常规分配在并发分配的 slot 之后继续
+    let h4 = map.allocate_handle();
+    assert_eq!(h4.index(), 6);
+    map.insert(h4, 13);
+}
+
+#[test]
+fn test_concurrent_allocate_stress() {
+    const THREADS: usize = 8;
+    const PER_THREAD: usize = 2000;
+
+    // Seed a fragmented free list
+    // 预先构造一个碎片化的空闲列表
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..1000);
+    for key in keys.iter().step_by(3) {
+        map.remove(*key);
+    }
+    let live_before = map.len();
+
+    let handles: Vec<_> = {
+        let allocator = map.concurrent_allocator();
+        std::thread::scope(|s| {
+            let workers: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    s.spawn(|| {
+                        (0..PER_THREAD)
+                            .map(|_| allocator.allocate_handle())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap())
+                .collect()
+        })
+    };
+    assert_eq!(map.validate(), Ok(()));
+
+    // Every handle got a distinct slot
+    // 每个 handle 都获得了不同的 slot
+    let indices: HashSet<u32> = handles.iter().map(|h| h.index()).collect();
+    assert_eq!(indices.len(), THREADS * PER_THREAD);
+    assert!(!indices.contains(&0));
+    assert_eq!(
+        map.len_including_reserved(),
+        live_before + THREADS * PER_THREAD
+    );
+
+    let new_keys: Vec<_> = handles.iter().map(|h| h.key()).collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        map.insert(handle, 10_000 + i);
+    }
+
+    assert_eq!(map.len(), live_before + THREADS * PER_THREAD);
+    for (i, key) in new_keys.into_iter().enumerate() {
+        assert_eq!(map.get(key), Some(&(10_000 + i)));
+    }
+    assert_eq!(map.validate(), Ok(()));
+}
+
+#[test]
+fn test_concurrent_allocator_without_allocations() {
+    let mut map = DeferredMap::<i32>::new();
+    let h = map.allocate_handle();
+    let k = h.key();
+    map.insert(h, 1);
+    map.remove(k);
+
+    drop(map.concurrent_allocator());
+
+    // Free list untouched
+    // 空闲列表保持不变
+    let h = map.allocate_handle();
+    assert_eq!(h.index(), k.index());
+    map.insert(h, 2);
+    assert_eq!(map.len(), 1);
+}
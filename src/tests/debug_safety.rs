@@ -37,3 +37,19 @@ fn test_insert_on_cloned_map_fails() {
     // handle1 has map1's ID, so this should panic
     map2.insert(handle1, 42);
 }
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Key used with wrong map instance in SecondaryMap")]
+fn test_secondary_for_primary_rejects_foreign_key() {
+    let primary = DeferredMap::<i32>::new();
+    let mut other = DeferredMap::<i32>::new();
+    let handle = other.allocate_handle();
+    let foreign = handle.key();
+    other.insert(handle, 1);
+
+    // Bound before any insert, so the very first foreign key is caught
+    // 在任何插入之前就已绑定，因此第一个外来 key 就会被捕获
+    let mut sec = crate::SecondaryMap::for_primary(&primary);
+    sec.insert(foreign, 1);
+}
@@ -213,6 +213,65 @@ fn test_clone_from() {
     assert_eq!(map2.get(k2), Some(&1));
 }
 
+#[test]
+fn test_clone_from_matches_source_after_churn() {
+    use crate::Key;
+
+    let (mut a, a_keys) = DeferredMap::<_>::from_values((0..20).map(|i| format!("a{}", i)));
+    for key in a_keys.iter().step_by(3) {
+        a.remove(*key);
+    }
+
+    let (mut b, b_keys) = DeferredMap::<_>::from_values((0..12).map(|i| format!("b{}", i)));
+    for key in b_keys.iter().step_by(2) {
+        b.remove(*key);
+    }
+
+    a.clone_from(&b);
+
+    // Same occupied (index, generation) pairs and values
+    // 相同的占用 (index, generation) 对和值
+    let entries = |m: &DeferredMap<String>| -> Vec<_> {
+        m.iter()
+            .map(|(k, v)| (k.index(), k.generation(), v.clone()))
+            .collect()
+    };
+    assert_eq!(entries(&a), entries(&b));
+    assert_eq!(a.len(), b.len());
+
+    // Both maps hand out the same next slot
+    // 两个 map 分配相同的下一个 slot
+    let ha = a.allocate_handle();
+    let hb = b.allocate_handle();
+    assert_eq!(ha.index(), hb.index());
+    assert_eq!(ha.generation(), hb.generation());
+}
+
+#[test]
+fn test_clone_from_drops_overwritten_values() {
+    use std::rc::Rc;
+
+    let tracker = Rc::new(());
+
+    let mut a = DeferredMap::new();
+    for _ in 0..5 {
+        let h = a.allocate_handle();
+        a.insert(h, Rc::clone(&tracker));
+    }
+
+    // Source has vacant slots where `a` is occupied
+    // 源 map 在 `a` 占用的位置上是空闲 slot
+    let (mut b, keys) = DeferredMap::<_>::from_values((0..5).map(|_| Rc::new(())));
+    for key in &keys[1..] {
+        b.remove(*key);
+    }
+
+    a.clone_from(&b);
+
+    assert_eq!(a.len(), 1);
+    assert_eq!(Rc::strong_count(&tracker), 1);
+}
+
 #[test]
 fn test_default_creates_empty_map() {
     let map: DeferredMap<i32> = DeferredMap::default();
@@ -415,3 +474,107 @@ fn test_map_with_result_type() {
     assert_eq!(map.get(k1), Some(&Ok(42)));
     assert_eq!(map.get(k2), Some(&Err("error".to_string())));
 }
+
+#[test]
+fn test_validate_through_mixed_operations() {
+    let mut map = DeferredMap::new();
+    assert_eq!(map.validate(), Ok(()));
+
+    let mut keys = Vec::new();
+    let mut pending = Vec::new();
+    for round in 0..50u32 {
+        for i in 0..8 {
+            let handle = map.allocate_handle();
+            if (round + i) % 5 == 0 {
+                pending.push(handle);
+            } else {
+                keys.push(handle.key());
+                map.insert(handle, round * 8 + i);
+            }
+        }
+        // Remove a spread of entries, release some reservations
+        // 删除分散的条目，释放部分预留
+        let removed: Vec<_> = keys
+            .iter()
+            .copied()
+            .skip(round as usize % 3)
+            .step_by(3)
+            .collect();
+        for key in removed {
+            map.remove(key);
+        }
+        keys.retain(|&k| map.contains_key(k));
+        if round % 2 == 0
+            && let Some(handle) = pending.pop()
+        {
+            map.release_handle(handle);
+        }
+        map.retain(|_, v| *v % 7 != 0);
+        keys.retain(|&k| map.contains_key(k));
+        assert_eq!(map.validate(), Ok(()), "round {round}");
+    }
+
+    for handle in pending {
+        map.insert(handle, 0);
+    }
+    assert_eq!(map.validate(), Ok(()));
+
+    map.clear();
+    assert_eq!(map.validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_reports_invalid_state_bits() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<u32>::from_values(0..4);
+    map.remove(keys[2]);
+    let index = keys[2].index() as usize;
+
+    // 0b10 is not a state any transition produces
+    // 0b10 不是任何状态转换会产生的状态
+    let vacant = map.slots[index].version;
+    map.slots[index].version = crate::Version::new(vacant.generation(), 0b10);
+    assert_eq!(
+        map.validate(),
+        Err(format!("slot {index} has invalid state bits 0b10"))
+    );
+
+    map.slots[index].version = vacant;
+    assert_eq!(map.validate(), Ok(()));
+}
+
+#[test]
+fn test_get_mut_never_invalidates_keys() {
+    let (mut map, keys) = DeferredMap::<u32>::from_values(0..32);
+    for key in keys.iter().step_by(5) {
+        map.remove(*key);
+    }
+    let pending = map.allocate_handle();
+    let live: Vec<_> = map.iter().map(|(k, _)| k).collect();
+
+    let versions: Vec<u32> = map.versions().collect();
+    let epoch = map.epoch();
+    let (len, reserved) = (map.len(), map.reserved_len());
+
+    for &key in &live {
+        *map.get_mut(key).unwrap() += 100;
+    }
+    // Removed and reserved keys miss without side effects
+    // 已删除和预留的 key 查找失败且无副作用
+    for key in keys.iter().step_by(5) {
+        assert!(map.get_mut(*key).is_none());
+    }
+    assert!(map.get_mut(pending.key()).is_none());
+
+    assert_eq!(map.versions().collect::<Vec<_>>(), versions);
+    assert_eq!(map.epoch(), epoch);
+    assert_eq!((map.len(), map.reserved_len()), (len, reserved));
+    for &key in &live {
+        assert!(map.contains_key(key));
+        assert!(*map.get(key).unwrap() >= 100);
+    }
+    map.validate().unwrap();
+
+    map.release_handle(pending);
+}
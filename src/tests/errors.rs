@@ -0,0 +1,329 @@
+// Error reporting tests for fallible handle operations
+// 可失败 handle 操作的错误报告测试
+
+use crate::{DefaultKey, DeferredMap, DeferredMapError, GetFailure, Handle, Key};
+
+#[test]
+fn test_try_insert_success() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+
+    assert_eq!(map.try_insert(handle, 42), Ok(key));
+    assert_eq!(map.get(key), Some(&42));
+}
+
+#[test]
+fn test_try_insert_already_used_handle() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 1);
+
+    // Forge a second handle for the same key
+    // 为同一个 key 伪造第二个 handle
+    let forged = Handle::new(key);
+    assert_eq!(
+        map.try_insert(forged, 2),
+        Err(DeferredMapError::NotReserved)
+    );
+    assert_eq!(map.get(key), Some(&1));
+}
+
+#[test]
+fn test_try_release_handle_after_release() {
+    let mut map = DeferredMap::<i32>::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.release_handle(handle);
+
+    // Released slot moved on to the next generation
+    // 被释放的 slot 已进入下一代
+    let forged = Handle::new(key);
+    assert_eq!(
+        map.try_release_handle(forged),
+        Err(DeferredMapError::GenerationMismatch)
+    );
+}
+
+#[test]
+fn test_try_release_handle_on_vacant_slot() {
+    let mut map = DeferredMap::<i32>::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.release_handle(handle);
+
+    // Same index and generation as the vacant slot, but not reserved
+    // 与空闲 slot 的 index 和 generation 相同，但未预留
+    let vacant_key = DefaultKey::new(
+        key.index(),
+        unsafe { crate::Generation::new_unchecked(key.generation().get() + 1) },
+        #[cfg(debug_assertions)]
+        key.map_id,
+    );
+    assert_eq!(
+        map.try_release_handle(Handle::new(vacant_key)),
+        Err(DeferredMapError::NotReserved)
+    );
+    assert_eq!(
+        map.try_insert(Handle::new(vacant_key), 1),
+        Err(DeferredMapError::NotReserved)
+    );
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_try_insert_out_of_bounds() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+
+    let forged = Handle::new(DefaultKey::new(
+        100,
+        key.generation(),
+        #[cfg(debug_assertions)]
+        key.map_id,
+    ));
+    assert_eq!(
+        map.try_insert(forged, 1),
+        Err(DeferredMapError::InvalidHandle)
+    );
+    map.insert(handle, 0);
+}
+
+#[test]
+fn test_error_display() {
+    assert_eq!(
+        DeferredMapError::GenerationMismatch.to_string(),
+        "generation mismatch"
+    );
+    assert!(
+        DeferredMapError::NotReserved
+            .to_string()
+            .contains("not reserved")
+    );
+}
+
+fn sentinel_handle(map: &mut DeferredMap<i32>) -> Handle {
+    // The key is only needed for its map id, which release builds do not track
+    // 只需要该 key 的 map id，而 release 构建不跟踪它
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    let key = map.allocate_handle().key();
+    Handle::new(DefaultKey::new(
+        0,
+        crate::Generation::MIN,
+        #[cfg(debug_assertions)]
+        key.map_id,
+    ))
+}
+
+#[test]
+fn test_invalid_handle_with_zero_index() {
+    let mut map = DeferredMap::<i32>::new();
+    let handle = sentinel_handle(&mut map);
+    assert_eq!(
+        map.try_insert(handle, 1),
+        Err(DeferredMapError::InvalidHandle)
+    );
+
+    let handle = sentinel_handle(&mut map);
+    assert_eq!(
+        map.try_release_handle(handle),
+        Err(DeferredMapError::InvalidHandle)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid handle: sentinel")]
+fn test_insert_sentinel_handle_panics() {
+    let mut map = DeferredMap::<i32>::new();
+    let handle = sentinel_handle(&mut map);
+    map.insert(handle, 1);
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn test_sentinel_handle_is_rejected_in_release() {
+    let mut map = DeferredMap::<i32>::new();
+
+    let handle = sentinel_handle(&mut map);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.insert(handle, 1)));
+    assert!(result.is_err());
+    let handle = sentinel_handle(&mut map);
+    map.release_handle(handle);
+    assert!(map.is_empty());
+
+    // The free list is still intact: fresh handles never land on the sentinel
+    // 空闲列表仍然完好：新的 handle 永远不会落在 sentinel 上
+    for i in 0..10 {
+        let handle = map.allocate_handle();
+        assert_ne!(handle.index(), 0);
+        map.insert(handle, i);
+    }
+    assert_eq!(map.len(), 10);
+}
+
+#[test]
+fn test_into_io_error() {
+    use std::io;
+
+    for err in [
+        DeferredMapError::InvalidHandle,
+        DeferredMapError::GenerationMismatch,
+        DeferredMapError::NotReserved,
+    ] {
+        let io_err: io::Error = err.into();
+        assert_eq!(io_err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(io_err.to_string(), err.to_string());
+        // The original error is kept as the source
+        // 原始错误被保留为 source
+        let inner = io_err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<DeferredMapError>());
+        assert_eq!(inner, Some(&err));
+    }
+}
+
+#[test]
+fn test_get_or_reason_categories() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 1);
+    let mut reason = GetFailure::NotOccupied;
+
+    // Success leaves the reason untouched
+    // 成功时原因保持不变
+    assert_eq!(map.get_or_reason(key, &mut reason), Some(&1));
+    assert_eq!(reason, GetFailure::NotOccupied);
+
+    // Out of bounds
+    // 越界
+    let far = DefaultKey::new(
+        100,
+        key.generation(),
+        #[cfg(debug_assertions)]
+        key.map_id,
+    );
+    assert_eq!(map.get_or_reason(far, &mut reason), None);
+    assert_eq!(reason, GetFailure::OutOfBounds);
+
+    // Stale generation
+    // 过期代数
+    map.remove(key);
+    assert_eq!(map.get_or_reason(key, &mut reason), None);
+    assert_eq!(reason, GetFailure::StaleGeneration);
+
+    // Reserved but not yet inserted
+    // 已预留但尚未插入
+    let pending = map.allocate_handle();
+    assert_eq!(map.get_or_reason(pending.key(), &mut reason), None);
+    assert_eq!(reason, GetFailure::NotOccupied);
+    map.release_handle(pending);
+
+    assert_eq!(
+        GetFailure::StaleGeneration.to_string(),
+        "stale key generation"
+    );
+}
+
+#[test]
+fn test_try_get_disjoint_mut_reports_position() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..3);
+
+    // Aliasing is reported at the second occurrence
+    // 别名在第二次出现的位置报告
+    assert_eq!(
+        map.try_get_disjoint_mut([keys[0], keys[1], keys[1]])
+            .unwrap_err(),
+        (2, DeferredMapError::Aliased)
+    );
+
+    // Stale key
+    // 过期 key
+    map.remove(keys[2]);
+    assert_eq!(
+        map.try_get_disjoint_mut([keys[0], keys[2]]).unwrap_err(),
+        (1, DeferredMapError::GenerationMismatch)
+    );
+
+    // A stale key is reported before it could alias a live one
+    // 过期 key 在可能与存活 key 形成别名之前就被报告
+    let handle = map.allocate_handle();
+    let reused = handle.key();
+    map.insert(handle, 20);
+    assert_eq!(
+        map.try_get_disjoint_mut([reused, keys[2]]).unwrap_err(),
+        (1, DeferredMapError::GenerationMismatch)
+    );
+
+    // Out of bounds
+    // 越界
+    let far = DefaultKey::new(
+        100,
+        keys[0].generation(),
+        #[cfg(debug_assertions)]
+        keys[0].map_id,
+    );
+    assert_eq!(
+        map.try_get_disjoint_mut([far]).unwrap_err(),
+        (0, DeferredMapError::InvalidHandle)
+    );
+
+    // Success
+    // 成功
+    let [a, b] = map.try_get_disjoint_mut([reused, keys[0]]).unwrap();
+    std::mem::swap(a, b);
+    assert_eq!(map.get(reused), Some(&0));
+    assert_eq!(map.get(keys[0]), Some(&20));
+
+    assert_eq!(
+        DeferredMapError::Aliased.to_string(),
+        "keys alias the same slot"
+    );
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn test_stale_handle_is_rejected_in_release() {
+    let mut map = DeferredMap::<i32>::new();
+
+    let stale = map.allocate_handle();
+    let stale_released = map.allocate_handle();
+    let stale_index = stale.index();
+    assert_eq!(map.release_all_reserved(), 2);
+
+    // Both slots are reused by live entries before the stale handles come back
+    // 在过期 handle 回来之前，两个 slot 都已被存活条目重用
+    let h1 = map.allocate_handle();
+    let k1 = map.insert(h1, 1);
+    let h2 = map.allocate_handle();
+    let k2 = map.insert(h2, 2);
+    assert!(k1.index() == stale_index || k2.index() == stale_index);
+
+    let epoch = map.epoch();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.insert(stale, 100)));
+    assert!(result.is_err());
+    map.release_handle(stale_released);
+    assert_eq!(map.epoch(), epoch);
+    assert_eq!(map.get(k1), Some(&1));
+    assert_eq!(map.get(k2), Some(&2));
+    assert_eq!(map.len(), 2);
+    map.validate().unwrap();
+
+    // A handle for a slot that went back to the free list is rejected as well
+    // 指向已回到空闲列表的 slot 的 handle 同样会被拒绝
+    let reserved = map.allocate_handle();
+    let stale = map.allocate_handle();
+    map.release_all_reserved();
+    assert_eq!(
+        map.try_insert(stale, 3),
+        Err(DeferredMapError::GenerationMismatch)
+    );
+    map.release_handle(reserved);
+    assert_eq!(map.len(), 2);
+    map.validate().unwrap();
+    let h = map.allocate_handle();
+    map.insert(h, 4);
+    assert_eq!(map.len(), 3);
+}
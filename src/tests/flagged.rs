@@ -0,0 +1,77 @@
+// FlaggedDeferredMap tests
+// FlaggedDeferredMap 的测试
+
+use crate::{FlaggedDeferredMap, Key};
+
+#[test]
+fn test_set_clear_get_flag() {
+    let mut map = FlaggedDeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 1);
+
+    assert_eq!(map.flags(key), Some(0));
+    assert!(map.set_flag(key, 0));
+    assert!(map.set_flag(key, 7));
+    assert_eq!(map.get_flag(key, 0), Some(true));
+    assert_eq!(map.get_flag(key, 3), Some(false));
+    assert_eq!(map.flags(key), Some(0b1000_0001));
+
+    assert!(map.clear_flag(key, 0));
+    assert_eq!(map.get_flag(key, 0), Some(false));
+    assert_eq!(map.flags(key), Some(0b1000_0000));
+}
+
+#[test]
+fn test_flags_reset_when_slot_recycled() {
+    let mut map = FlaggedDeferredMap::new();
+    let handle = map.allocate_handle();
+    let old_key = handle.key();
+    map.insert(handle, "a");
+    map.set_flag(old_key, 1);
+
+    assert_eq!(map.remove(old_key), Some("a"));
+    assert_eq!(map.get_flag(old_key, 1), None);
+    assert!(!map.set_flag(old_key, 1));
+
+    // The recycled slot starts with clean flags
+    // 复用的 slot 以干净的标志开始
+    let handle = map.allocate_handle();
+    let new_key = handle.key();
+    map.insert(handle, "b");
+    assert_eq!(new_key.index(), old_key.index());
+    assert_eq!(map.flags(new_key), Some(0));
+    assert_eq!(map.flags(old_key), None);
+}
+
+#[test]
+fn test_flags_independent_between_entries() {
+    let mut map = FlaggedDeferredMap::new();
+    let keys: Vec<_> = (0..4)
+        .map(|i| {
+            let handle = map.allocate_handle();
+            let key = handle.key();
+            map.insert(handle, i);
+            key
+        })
+        .collect();
+
+    map.set_flag(keys[2], 4);
+    for (i, &key) in keys.iter().enumerate() {
+        assert_eq!(map.get_flag(key, 4), Some(i == 2));
+    }
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.flags(keys[2]), None);
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_flag_bit_out_of_range() {
+    let mut map = FlaggedDeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, ());
+    map.set_flag(key, 8);
+}
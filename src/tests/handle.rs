@@ -31,7 +31,7 @@ fn test_handle_generation_extraction() {
     let generation = handle.generation();
     // First generation should be 1 (started at 0b101)
     // 第一次 generation 应该是 1（从 0b101 开始）
-    assert_eq!(generation.get(), 1);
+    assert_eq!(generation, 1);
 }
 
 #[test]
@@ -98,7 +98,7 @@ fn test_handle_generation_after_reuse() {
 
     // Generation (high 30 bits) should be incremented by 1
     // Generation（高 30 位）应该递增 1
-    assert_eq!(gen2.get(), gen1.get() + 1);
+    assert_eq!(gen2, gen1 + 1);
 }
 
 #[test]
@@ -184,7 +184,7 @@ fn test_handle_generation_consistency_after_multiple_reuses() {
         assert_eq!(handle.index(), index); // Should reuse same slot | 应该复用相同的 slot
 
         let curr_gen = handle.generation();
-        assert_eq!(curr_gen.get(), prev_gen.get() + 1); // Generation (high 30 bits) should increment by 1 | Generation（高 30 位）应该递增 1
+        assert_eq!(curr_gen, prev_gen + 1); // Generation (high 30 bits) should increment by 1 | Generation（高 30 位）应该递增 1
         prev_gen = curr_gen;
 
         current_key = handle.key();
@@ -199,7 +199,6 @@ fn test_handle_with_max_index() {
     let max_index = u32::MAX;
     let generation = 1u32;
     let key = (generation as u64) << 32 | max_index as u64;
-    #[cfg(debug_assertions)]
     let key = crate::DefaultKey {
         raw: key,
         #[cfg(debug_assertions)]
@@ -208,7 +207,7 @@ fn test_handle_with_max_index() {
     let handle = Handle::new(key);
 
     assert_eq!(handle.index(), max_index);
-    assert_eq!(handle.generation().get(), generation); // generation is stored directly in high 32 bits
+    assert_eq!(handle.generation(), generation); // generation is stored directly in high 32 bits
 }
 
 #[test]
@@ -218,7 +217,6 @@ fn test_handle_with_max_generation() {
     let index = 1u32;
     let max_generation = u32::MAX; // Now generation is full 32 bits
     let key = (max_generation as u64) << 32 | index as u64;
-    #[cfg(debug_assertions)]
     let key = crate::DefaultKey {
         raw: key,
         #[cfg(debug_assertions)]
@@ -227,7 +225,7 @@ fn test_handle_with_max_generation() {
     let handle = Handle::new(key);
 
     assert_eq!(handle.index(), index);
-    assert_eq!(handle.generation().get(), max_generation);
+    assert_eq!(handle.generation(), max_generation);
 }
 
 // ============================================================================
@@ -287,7 +285,7 @@ fn test_release_handle_increments_generation() {
 
     // Generation (high 30 bits) should increment by 1
     // Generation（高 30 位）应该递增 1
-    assert_eq!(gen2.get(), gen1.get() + 1);
+    assert_eq!(gen2, gen1 + 1);
 }
 
 #[test]
@@ -478,3 +476,112 @@ fn test_release_handle_mixed_with_removal() {
     assert_eq!(map.get(k4), Some(&4));
     assert_eq!(map.len(), 2);
 }
+
+#[test]
+fn test_handle_raw_round_trip() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    let index = handle.index();
+    let generation = handle.generation();
+
+    let raw = handle.into_raw();
+    assert_eq!(raw, key.raw);
+
+    let handle: Handle = unsafe { Handle::from_raw(raw) };
+    assert_eq!(handle.index(), index);
+    assert_eq!(handle.generation(), generation);
+
+    // A rebuilt handle is accepted by the map that allocated it
+    // 重建的 handle 可被分配它的 map 接受
+    map.insert(handle, 7);
+    assert_eq!(map.get(key), Some(&7));
+}
+
+#[test]
+fn test_handle_is_valid_states() {
+    let mut map = DeferredMap::new();
+
+    // Valid while reserved
+    // 预留期间有效
+    let handle = map.allocate_handle();
+    let raw = handle.key().raw;
+    assert!(map.handle_is_valid(&handle));
+
+    // Inserted: a copy of the handle is no longer valid
+    // 已插入：handle 的副本不再有效
+    map.insert(handle, 1);
+    let copy: Handle = unsafe { Handle::from_raw(raw) };
+    assert!(!map.handle_is_valid(&copy));
+    assert!(map.try_release_handle(copy).is_err());
+
+    // Released: the slot is vacant at a newer generation
+    // 已释放：slot 以更新的代数处于空闲状态
+    let handle = map.allocate_handle();
+    let raw = handle.key().raw;
+    map.release_handle(handle);
+    let copy: Handle = unsafe { Handle::from_raw(raw) };
+    assert!(!map.handle_is_valid(&copy));
+
+    // The slot is reserved again, but under a newer generation
+    // slot 再次被预留，但处于更新的代数
+    let fresh = map.allocate_handle();
+    assert_eq!(fresh.index(), copy.index());
+    assert!(map.handle_is_valid(&fresh));
+    assert!(!map.handle_is_valid(&copy));
+    assert!(map.try_release_handle(copy).is_err());
+    map.insert(fresh, 2);
+
+    // Out of bounds
+    // 越界
+    let far: Handle = unsafe { Handle::from_raw((1 << 32) | 100) };
+    assert!(!map.handle_is_valid(&far));
+    assert!(map.try_release_handle(far).is_err());
+}
+
+#[test]
+fn test_generation_compares_with_u32() {
+    let mut map = DeferredMap::<i32>::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+
+    assert_eq!(handle.generation(), 1);
+    assert_eq!(1, handle.generation());
+    assert_ne!(handle.generation(), 2);
+    assert_eq!(handle.generation(), key.generation());
+    map.insert(handle, 1);
+}
+
+#[test]
+fn test_dropped_handle_is_detected_as_leak() {
+    use crate::dropped_unused_handles;
+
+    let mut map = DeferredMap::<i32>::new();
+    let before = dropped_unused_handles();
+
+    // Consumed handles are not counted
+    // 被消耗的 Handle 不计入
+    let inserted = map.allocate_handle();
+    map.insert(inserted, 1);
+    let released = map.allocate_handle();
+    map.release_handle(released);
+    let raw = map.allocate_handle().into_raw();
+    map.release_handle(unsafe { Handle::from_raw(raw) });
+    assert_eq!(dropped_unused_handles(), before);
+    map.assert_no_leaked_handles(0);
+
+    // Dropping a handle leaves its slot reserved
+    // drop Handle 会使其 slot 保持保留状态
+    let _ = map.allocate_handle();
+    if cfg!(debug_assertions) {
+        assert_eq!(dropped_unused_handles(), before + 1);
+    }
+
+    let leaked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.assert_no_leaked_handles(0);
+    }));
+    assert!(leaked.is_err());
+
+    assert_eq!(map.release_all_reserved(), 1);
+    map.assert_no_leaked_handles(0);
+}
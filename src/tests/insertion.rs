@@ -14,6 +14,26 @@ fn test_basic_insertion() {
     assert_eq!(map.len(), 1);
 }
 
+#[test]
+fn test_insert_returns_handle_key() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let expected = handle.key();
+    let key = map.insert(handle, "a");
+    assert_eq!(key, expected);
+    assert_eq!(map.get(key), Some(&"a"));
+
+    // Reused slots return the bumped key
+    // 复用的 slot 返回代数递增后的 key
+    map.remove(key);
+    let handle = map.allocate_handle();
+    let reused = map.insert(handle, "b");
+    assert_eq!(reused.index(), key.index());
+    assert_eq!(reused.generation(), key.generation() + 1);
+    assert_eq!(map.get(key), None);
+    assert_eq!(map.get(reused), Some(&"b"));
+}
+
 #[test]
 fn test_multiple_sequential_insertions() {
     let mut map = DeferredMap::new();
@@ -73,6 +93,87 @@ fn test_insertion_with_zero_sized_type() {
     assert_eq!(map.len(), 1);
 }
 
+#[test]
+fn test_zero_sized_type_reuse() {
+    // The free list lives in the same union as the ZST value and must survive
+    // ZST 值与空闲列表共享同一个 union，空闲列表必须保持完好
+    assert_eq!(
+        std::mem::size_of::<crate::slot::Slot<()>>(),
+        std::mem::size_of::<crate::slot::Slot<u32>>()
+    );
+
+    let (mut map, keys) = DeferredMap::<_>::from_values((0..100).map(|_| ()));
+    assert_eq!(map.len(), 100);
+    assert_eq!(map.iter().count(), 100);
+
+    // Remove every other entry
+    // 删除每隔一个条目
+    for key in keys.iter().step_by(2) {
+        assert_eq!(map.remove(*key), Some(()));
+    }
+    assert_eq!(map.len(), 50);
+    assert_eq!(map.iter().count(), 50);
+    assert!(map.iter().all(|(k, _)| k.index() % 2 == 0));
+
+    // Reuse follows the free list exactly: LIFO over the removed indices
+    // 复用严格遵循空闲列表：按删除索引后进先出
+    let mut reused = Vec::new();
+    for _ in 0..50 {
+        let handle = map.allocate_handle();
+        let key = handle.key();
+        map.insert(handle, ());
+        reused.push(key);
+    }
+    let mut expected: Vec<_> = keys.iter().step_by(2).map(|k| k.index()).collect();
+    expected.reverse();
+    assert_eq!(
+        reused.iter().map(|k| k.index()).collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(map.slot_stats().total, 100);
+
+    // Generations advanced and old keys stay invalid
+    // 代数已递增，旧 key 保持失效
+    for (old, new) in keys.iter().step_by(2).rev().zip(&reused) {
+        assert_eq!(new.generation(), old.generation() + 1);
+        assert_eq!(map.get(*old), None);
+        assert_eq!(map.get(*new), Some(&()));
+    }
+    assert_eq!(map.len(), 100);
+    assert_eq!(map.iter().count(), 100);
+}
+
+#[test]
+fn test_zero_sized_type_with_drop() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static DROPS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Token;
+    impl Drop for Token {
+        fn drop(&mut self) {
+            DROPS.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    let (mut map, keys) = DeferredMap::<_>::from_values((0..10).map(|_| Token));
+
+    drop(map.remove(keys[3]));
+    assert_eq!(DROPS.with(Cell::get), 1);
+
+    // The freed slot is reused without dropping anything spurious
+    // 复用释放的 slot 时不会产生多余的 drop
+    let handle = map.allocate_handle();
+    assert_eq!(handle.index(), keys[3].index());
+    map.insert(handle, Token);
+    assert_eq!(DROPS.with(Cell::get), 1);
+
+    drop(map);
+    assert_eq!(DROPS.with(Cell::get), 11);
+}
+
 #[test]
 fn test_insertion_returns_correct_key() {
     let mut map = DeferredMap::new();
@@ -277,3 +378,225 @@ fn test_insertion_with_box() {
 
     assert_eq!(map.get(k), Some(&Box::new(42)));
 }
+
+#[test]
+fn test_insert_and_get_mut_aliases_stored_value() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let expected_key = handle.key();
+
+    let (key, value) = map.insert_and_get_mut(handle, 1);
+    assert_eq!(key, expected_key);
+    *value = 2;
+    let ptr: *const i32 = value;
+
+    // The returned reference pointed at the stored value
+    // 返回的引用指向存储的值
+    assert_eq!(map.get(key), Some(&2));
+    assert!(std::ptr::eq(ptr, map.get(key).unwrap()));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_insert_at_reconstructs_layout() {
+    // Build a churned source map
+    // 构建一个经过 churn 的源 map
+    let (mut source, keys) = DeferredMap::<_>::from_values(0..10);
+    for key in keys.iter().step_by(2) {
+        source.remove(*key);
+    }
+    for i in 0..3 {
+        let h = source.allocate_handle();
+        source.insert(h, 100 + i);
+    }
+
+    // Mirror it into an empty map, in reverse index order
+    // 以逆序索引将其复制到一个空 map
+    let layout: Vec<_> = source
+        .iter_with_index()
+        .map(|(index, generation, value)| (index, generation, *value))
+        .collect();
+    let mut rebuilt = DeferredMap::new();
+    for &(index, generation, value) in layout.iter().rev() {
+        let key = unsafe { rebuilt.insert_at(index, generation, value) };
+        assert_eq!(key.index(), index);
+        assert_eq!(key.generation(), generation);
+    }
+
+    let rebuilt_layout: Vec<_> = rebuilt
+        .iter_with_index()
+        .map(|(index, generation, value)| (index, generation, *value))
+        .collect();
+    assert_eq!(rebuilt_layout, layout);
+    assert_eq!(rebuilt.len(), source.len());
+
+    // The free list skips every occupied index and still hands out unique slots
+    // 空闲列表跳过所有占用的索引，并仍然分配唯一的 slot
+    let occupied: Vec<_> = layout.iter().map(|&(index, _, _)| index).collect();
+    let mut seen = std::collections::HashSet::new();
+    for i in 0..20 {
+        let h = rebuilt.allocate_handle();
+        assert!(!occupied.contains(&h.index()));
+        assert!(seen.insert(h.index()));
+        rebuilt.insert(h, 1000 + i);
+    }
+    assert_eq!(rebuilt.len(), source.len() + 20);
+    for &(index, _, value) in &layout {
+        assert!(
+            rebuilt
+                .iter_with_index()
+                .any(|(i, _, v)| i == index && *v == value)
+        );
+    }
+}
+
+#[test]
+fn test_insert_at_into_free_list_and_end() {
+    use crate::Generation;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..4);
+    map.remove(keys[1]);
+    map.remove(keys[2]);
+
+    // Take a slot from the middle of the free list with a newer generation
+    // 以更新的代数从空闲列表中间取出一个 slot
+    let generation = unsafe { Generation::new_unchecked(5) };
+    let key = unsafe { map.insert_at(keys[1].index(), generation, 10) };
+    assert_eq!(map.get(key), Some(&10));
+    assert_eq!(map.get(keys[1]), None);
+
+    // Exactly at the end
+    // 恰好在末尾
+    let end = unsafe { map.insert_at(5, Generation::MIN, 50) };
+    assert_eq!(map.get(end), Some(&50));
+
+    // The only remaining free slot is keys[2]'s, then fresh slots follow
+    // 唯一剩余的空闲 slot 是 keys[2] 的，之后是新的 slot
+    let h = map.allocate_handle();
+    assert_eq!(h.index(), keys[2].index());
+    map.insert(h, 20);
+    let h = map.allocate_handle();
+    assert_eq!(h.index(), 6);
+    map.insert(h, 60);
+    assert_eq!(map.len(), 6);
+}
+
+#[test]
+#[should_panic(expected = "sentinel")]
+fn test_insert_at_rejects_sentinel() {
+    let mut map = DeferredMap::new();
+    unsafe {
+        map.insert_at(0, crate::Generation::MIN, 1);
+    }
+}
+
+#[test]
+fn test_allocate_handles_reserves_once() {
+    let mut map = DeferredMap::<usize>::new();
+
+    // One reservation covers the whole batch
+    // 一次预留覆盖整个批次
+    let handles = map.allocate_handles(10_000);
+    assert_eq!(handles.len(), 10_000);
+    assert!(map.capacity() >= 10_000);
+    assert_eq!(map.reserved_len(), 10_000);
+
+    // Filling the handles never touches the allocation again
+    // 填充这些 handle 不会再触及分配
+    let (ptr, capacity) = (map.slots.as_ptr(), map.capacity());
+    for (i, handle) in handles.into_iter().enumerate() {
+        map.insert(handle, i);
+    }
+    assert_eq!((map.slots.as_ptr(), map.capacity()), (ptr, capacity));
+
+    // A batch served from the free list does not reallocate at all
+    // 由空闲列表满足的批次完全不会重新分配
+    let keys: Vec<_> = map.iter().map(|(k, _)| k).take(5_000).collect();
+    for key in keys {
+        map.remove(key);
+    }
+    let handles = map.allocate_handles(5_000);
+    assert_eq!((map.slots.as_ptr(), map.capacity()), (ptr, capacity));
+
+    // Handles are distinct and all reserved
+    // Handle 互不相同且均处于预留状态
+    let mut indices: Vec<_> = handles.iter().map(|h| h.index()).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    assert_eq!(indices.len(), 5_000);
+    assert!(handles.iter().all(|h| map.handle_is_valid(h)));
+    for handle in handles {
+        map.release_handle(handle);
+    }
+}
+
+#[test]
+fn test_growth_policy_linear_and_exact() {
+    use crate::GrowthPolicy;
+
+    let mut map: DeferredMap<u32> = DeferredMap::new();
+    assert_eq!(map.growth(), GrowthPolicy::Double);
+
+    // The sentinel fills the initial allocation, so the first fresh slot grows by the step
+    // sentinel 占满初始分配，因此第一个新 slot 按步长增长
+    map.set_growth(GrowthPolicy::Linear(10));
+    let before = map.slots.capacity();
+    let h = map.allocate_handle();
+    map.insert(h, 0);
+    assert_eq!(map.slots.capacity(), before + 10);
+
+    // No reallocation until the step is used up, then exactly one more step
+    // 在步长用完前不会重新分配，用完后恰好再增长一个步长
+    while map.slots.len() < map.slots.capacity() {
+        let h = map.allocate_handle();
+        map.insert(h, 0);
+        assert_eq!(map.slots.capacity(), before + 10);
+    }
+    let h = map.allocate_handle();
+    map.insert(h, 0);
+    assert_eq!(map.slots.capacity(), before + 20);
+
+    // Reused slots never grow the storage
+    // 复用的 slot 永远不会增长存储
+    let key = map.iter().next().unwrap().0;
+    map.remove(key);
+    let h = map.allocate_handle();
+    map.insert(h, 0);
+    assert_eq!(map.slots.capacity(), before + 20);
+
+    let mut exact: DeferredMap<u32> = DeferredMap::new();
+    exact.set_growth(GrowthPolicy::Exact);
+    for i in 0..5 {
+        let h = exact.allocate_handle();
+        exact.insert(h, i);
+        assert_eq!(exact.slots.capacity(), exact.slots.len());
+    }
+
+    // Clones keep the policy
+    // 克隆会保留策略
+    assert_eq!(map.clone().growth(), GrowthPolicy::Linear(10));
+    assert!(map.validate().is_ok());
+}
+
+#[test]
+fn test_with_capacity_allocations_do_not_reallocate() {
+    // with_capacity(n) covers the sentinel plus n slots, so n fresh handles fit
+    // with_capacity(n) 覆盖 sentinel 加 n 个 slot，因此可以容纳 n 个新 handle
+    let mut map: DeferredMap<u32> = DeferredMap::with_capacity(1000);
+    assert!(map.slots.capacity() > 1000);
+    let ptr = map.slots.as_ptr();
+
+    let handles: Vec<_> = (0..1000)
+        .map(|_| {
+            let handle = map.allocate_handle();
+            assert_eq!(map.slots.as_ptr(), ptr);
+            handle
+        })
+        .collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        map.insert(handle, i as u32);
+    }
+
+    assert_eq!(map.slots.as_ptr(), ptr);
+    assert_eq!(map.len(), 1000);
+}
@@ -0,0 +1,96 @@
+// Custom key type tests
+// 自定义 key 类型的测试
+
+use crate::{DefaultKey, DeferredMap, Key, KeyedMap, SecondaryMap};
+
+new_key_type! {
+    struct EntityKey;
+    /// Documented key
+    pub(crate) struct ItemKey;
+}
+
+#[test]
+fn test_typed_map_round_trip() {
+    let mut map = DeferredMap::<&str, EntityKey>::with_key();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, "entity");
+
+    assert_eq!(map.get(key), Some(&"entity"));
+    assert_eq!(map.iter().next().map(|(k, _)| k), Some(key));
+    assert_eq!(map.remove(key), Some("entity"));
+    assert_eq!(map.get(key), None);
+}
+
+#[test]
+fn test_typed_key_matches_default_key_encoding() {
+    let mut map = DeferredMap::<u32, ItemKey>::with_capacity_and_key(4);
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 7);
+
+    let default: DefaultKey = key.into();
+    assert_eq!(key.index(), default.index());
+    assert_eq!(key.generation(), default.generation());
+    assert_eq!(key.raw(), default.raw());
+    assert_eq!(size_of::<ItemKey>(), size_of::<DefaultKey>());
+}
+
+#[test]
+fn test_typed_constructors_without_turbofish() {
+    // Inference flows from the annotated binding through Default
+    // 类型推断通过 Default 从标注的绑定中获得
+    let mut map: DeferredMap<i32, EntityKey> = Default::default();
+    let mut sec: SecondaryMap<i32, EntityKey> = SecondaryMap::default();
+    let mut keyed: KeyedMap<&str, i32, EntityKey> = KeyedMap::default();
+
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 1);
+    sec.insert(key, 2);
+    *keyed.get_or_allocate("a", || 3) += 1;
+
+    assert_eq!(map.get(key), Some(&1));
+    assert_eq!(sec.get(key), Some(&2));
+    assert_eq!(keyed.get("a"), Some(&4));
+}
+
+#[test]
+fn test_default_key_u64_conversions() {
+    use crate::DeferredMapError;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    let raw = u64::from(keys[1]);
+    assert_eq!(raw, keys[1].raw());
+
+    let key = DefaultKey::try_from(raw).unwrap();
+    assert_eq!(key.decode(), keys[1].decode());
+    assert_eq!(map.get(key), Some(&2));
+    assert_eq!(map.remove(key), Some(2));
+    assert_eq!(map.get(keys[1]), None);
+
+    // Only the index half is set: the generation is zero
+    // 只设置了 index 部分：代数为零
+    assert_eq!(
+        DefaultKey::try_from(0u64),
+        Err(DeferredMapError::ZeroGeneration)
+    );
+    assert_eq!(
+        DefaultKey::try_from(u64::from(u32::MAX)),
+        Err(DeferredMapError::ZeroGeneration)
+    );
+    assert!(DefaultKey::try_from(1u64 << 32).is_ok());
+
+    // The generation does not fit in the 30 bits a slot stores
+    // 代数超出 slot 存储的 30 位
+    let max = u64::from(crate::Generation::MAX.get());
+    assert!(DefaultKey::try_from(max << 32 | 1).is_ok());
+    assert_eq!(
+        DefaultKey::try_from((max + 1) << 32 | 1),
+        Err(DeferredMapError::GenerationOutOfRange)
+    );
+    assert_eq!(
+        DefaultKey::try_from(u64::MAX),
+        Err(DeferredMapError::GenerationOutOfRange)
+    );
+}
@@ -0,0 +1,76 @@
+// KeyedMap tests
+// KeyedMap 的测试
+
+use crate::{Key, KeyedMap};
+
+#[test]
+fn test_get_or_allocate_inserts_once() {
+    let mut map = KeyedMap::new();
+    let mut calls = 0;
+
+    for _ in 0..3 {
+        *map.get_or_allocate("a", || {
+            calls += 1;
+            0
+        }) += 1;
+    }
+
+    assert_eq!(calls, 1);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get("a"), Some(&3));
+}
+
+#[test]
+fn test_relookup_after_slot_reuse() {
+    let mut map = KeyedMap::new();
+
+    map.get_or_allocate("alice".to_string(), || 1);
+    let alice_key = map.key_of("alice").unwrap();
+    assert_eq!(map.remove("alice"), Some(1));
+
+    // "bob" reuses alice's slot with a newer generation
+    // "bob" 以更新的代数复用 alice 的 slot
+    map.get_or_allocate("bob".to_string(), || 2);
+    let bob_key = map.key_of("bob").unwrap();
+    assert_eq!(bob_key.index(), alice_key.index());
+    assert_ne!(bob_key.generation(), alice_key.generation());
+
+    assert_eq!(map.get("alice"), None);
+    assert_eq!(map.get("bob"), Some(&2));
+    assert_eq!(map.user_key(alice_key), None);
+    assert_eq!(map.user_key(bob_key).map(String::as_str), Some("bob"));
+
+    // Re-mapping alice allocates a fresh entry
+    // 重新映射 alice 会分配新的条目
+    assert_eq!(*map.get_or_allocate("alice".to_string(), || 3), 3);
+    assert_eq!(map.get("alice"), Some(&3));
+    assert_eq!(map.get("bob"), Some(&2));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_remove_by_key_cleans_user_index() {
+    let mut map = KeyedMap::new();
+    map.get_or_allocate(10u32, || "ten");
+    map.get_or_allocate(20u32, || "twenty");
+
+    let key = map.key_of(&10).unwrap();
+    assert_eq!(map.remove_by_key(key), Some((10, "ten")));
+    assert!(!map.contains(&10));
+    assert_eq!(map.remove_by_key(key), None);
+    assert_eq!(map.get(&20), Some(&"twenty"));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_get_mut_and_clear() {
+    let mut map = KeyedMap::new();
+    map.get_or_allocate('x', Vec::new).push(1);
+    map.get_mut(&'x').unwrap().push(2);
+    assert_eq!(map.get(&'x'), Some(&vec![1, 2]));
+
+    map.clear();
+    assert!(map.is_empty());
+    assert!(map.get(&'x').is_none());
+    assert!(map.as_map().is_empty());
+}
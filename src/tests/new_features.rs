@@ -1,4 +1,4 @@
-use crate::DeferredMap;
+use crate::{DeferredMap, Key};
 
 #[test]
 fn test_reserve() {
@@ -9,6 +9,26 @@ fn test_reserve() {
     assert!(map.capacity() >= 100);
 }
 
+#[test]
+fn test_reserve_covers_fresh_allocations() {
+    for initial in [0, 1, 37] {
+        let (mut map, _) = DeferredMap::<usize>::from_values(0..initial);
+        map.reserve(100);
+
+        // Allocating `additional` fresh slots must not move the slot buffer
+        // 分配 `additional` 个新 slot 不能移动 slot 缓冲区
+        let ptr = map.slots.as_ptr();
+        let capacity = map.capacity();
+        for i in 0..100 {
+            let h = map.allocate_handle();
+            map.insert(h, i);
+        }
+        assert_eq!(map.slots.as_ptr(), ptr);
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.len(), initial + 100);
+    }
+}
+
 #[test]
 fn test_shrink_to_fit() {
     let mut map: DeferredMap<i32> = DeferredMap::with_capacity(100);
@@ -24,6 +44,37 @@ fn test_shrink_to_fit() {
     assert!(map.capacity() >= 1);
 }
 
+#[test]
+fn test_shrink_to_fit_keeps_trailing_reserved_and_vacant_slots() {
+    let mut map: DeferredMap<i32> = DeferredMap::with_capacity(100);
+    let keys = map.seed(0..4);
+
+    // Tail: one vacant slot (removed) and one reserved slot
+    // 末尾：一个空闲 slot（已删除）和一个预留 slot
+    map.remove(keys[3]);
+    let tail_handle = map.allocate_handle();
+    assert_eq!(tail_handle.index(), keys[3].index());
+    map.remove(keys[2]);
+
+    map.shrink_to_fit();
+    assert!(map.capacity() < 100);
+    assert_eq!(map.slot_stats().total, 4);
+
+    // The reserved handle is still insertable
+    // 预留的 handle 仍可插入
+    let tail_key = tail_handle.key();
+    map.insert(tail_handle, 30);
+    assert_eq!(map.get(tail_key), Some(&30));
+
+    // The vacant slot kept its generation, so the stale key stays dead
+    // 空闲 slot 保留了代数，因此过期 key 保持失效
+    let handle = map.allocate_handle();
+    assert_eq!(handle.index(), keys[2].index());
+    assert_ne!(handle.generation(), keys[2].generation());
+    map.insert(handle, 20);
+    assert_eq!(map.get(keys[2]), None);
+}
+
 #[test]
 fn test_retain() {
     let mut map: DeferredMap<i32> = DeferredMap::new();
@@ -76,3 +127,1157 @@ fn test_retain_reuse_slots() {
 
     assert_eq!(map.len(), 5);
 }
+
+#[test]
+fn test_iter_mut_keys_round_trip() {
+    let mut map: DeferredMap<i32> = DeferredMap::new();
+    let mut keys = Vec::new();
+    for i in 0..4 {
+        let handle = map.allocate_handle();
+        keys.push(handle.key());
+        map.insert(handle, i);
+    }
+
+    // Recycle one slot so its generation differs from the others
+    // 回收一个 slot，使其 generation 与其他 slot 不同
+    map.remove(keys[1]);
+    let handle = map.allocate_handle();
+    let recycled = handle.key();
+    map.insert(handle, 100);
+    map.remove(keys[2]);
+
+    let yielded: Vec<_> = map.iter_mut().map(|(k, _)| k).collect();
+    assert_eq!(yielded, vec![keys[0], recycled, keys[3]]);
+
+    for key in yielded {
+        let value = map.get_mut(key).expect("iter_mut key must resolve");
+        *value += 1;
+    }
+    assert_eq!(map.get(recycled), Some(&101));
+    assert_eq!(map.get(keys[1]), None);
+}
+
+#[test]
+fn test_reserved_capacity_matches_heap_capacity() {
+    let map: DeferredMap<i32> = DeferredMap::with_capacity(10);
+    assert!(map.reserved_capacity() >= 10);
+    assert_eq!(map.capacity(), map.reserved_capacity());
+    assert_eq!(map.len(), 0);
+}
+
+#[test]
+fn test_reserve_exact() {
+    let mut map: DeferredMap<i32> = DeferredMap::with_capacity(0);
+    map.reserve_exact(100);
+    assert!(map.capacity() >= 100);
+    // Not rounded up to a power of two
+    // 不会向上取整到 2 的幂
+    assert!(map.capacity() < 128);
+
+    let mut sec: crate::SecondaryMap<i32> = crate::SecondaryMap::new();
+    sec.reserve_exact(100);
+    assert!(sec.capacity() >= 100);
+    assert!(sec.capacity() < 128);
+}
+
+#[test]
+fn test_iter_with_index() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..6);
+    map.remove(keys[0]);
+    map.remove(keys[3]);
+    // Reuse a slot so a non-initial generation shows up
+    // 复用一个 slot，使非初始代数出现
+    let h = map.allocate_handle();
+    let reused = h.key();
+    map.insert(h, 30);
+
+    let entries: Vec<_> = map.iter_with_index().collect();
+    assert_eq!(entries.len(), map.len());
+    assert!(entries.iter().all(|&(index, _, _)| index != 0));
+    assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+    for (k, v) in map.iter() {
+        assert!(entries.contains(&(k.index(), k.generation(), v)));
+    }
+    assert!(entries.contains(&(reused.index(), reused.generation(), &30)));
+
+    let mut sec = crate::SecondaryMap::new();
+    for (k, v) in map.iter() {
+        sec.insert(k, *v);
+    }
+    let sec_entries: Vec<_> = sec.iter_with_index().collect();
+    assert_eq!(sec_entries, entries);
+}
+
+#[test]
+fn test_get_many_matches_get() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..100);
+    for key in keys.iter().step_by(3) {
+        map.remove(*key);
+    }
+
+    // Scattered order, duplicates and stale keys included
+    // 包含分散顺序、重复和过期的 key
+    let queries: Vec<_> = (0..250).map(|i| keys[(i * 37) % keys.len()]).collect();
+
+    let mut out = vec![None; 3];
+    map.get_many(&queries, &mut out);
+    assert_eq!(out.len(), queries.len());
+    for (&key, result) in queries.iter().zip(&out) {
+        assert_eq!(*result, map.get(key));
+    }
+
+    map.get_many(&[], &mut out);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_get_many_into_matches_get() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..64);
+    for key in keys.iter().step_by(4) {
+        map.remove(*key);
+    }
+
+    let queries: Vec<_> = (0..100).map(|i| keys[(i * 29) % keys.len()]).collect();
+    let mut out = vec![Some(&-1); queries.len() + 2];
+    assert_eq!(map.get_many_into(&queries, &mut out), queries.len());
+    for (&key, result) in queries.iter().zip(&out) {
+        assert_eq!(*result, map.get(key));
+    }
+    // The tail beyond keys.len() is untouched
+    // 超出 keys.len() 的尾部保持不变
+    assert_eq!(out[queries.len()..], [Some(&-1), Some(&-1)]);
+
+    assert_eq!(map.get_many_into(&[], &mut []), 0);
+}
+
+#[test]
+#[should_panic(expected = "output buffer too small")]
+fn test_get_many_into_short_buffer_panics() {
+    let mut map = DeferredMap::new();
+    let h = map.allocate_handle();
+    let key = map.insert(h, 1);
+    let mut out = [None; 1];
+    map.get_many_into(&[key, key], &mut out);
+}
+
+#[test]
+fn test_from_values_round_trip() {
+    let values = vec![5, 3, 5, 8, 1, 3];
+    let (map, keys): (DeferredMap<i32>, _) = DeferredMap::from_values(values.clone());
+
+    assert_eq!(map.len(), values.len());
+    assert_eq!(keys.len(), values.len());
+    for (key, value) in keys.iter().zip(&values) {
+        assert_eq!(map.get(*key), Some(value));
+    }
+
+    // Same multiset of values
+    // 相同的值多重集
+    let mut expected = values.clone();
+    expected.sort();
+    let mut actual: Vec<_> = map.iter().map(|(_, v)| *v).collect();
+    actual.sort();
+    assert_eq!(actual, expected);
+
+    // Round trip through a HashMap and back
+    // 经由 HashMap 往返
+    let hash_map = map.to_hashmap();
+    assert_eq!(hash_map.len(), values.len());
+    for key in &keys {
+        assert_eq!(hash_map.get(key).copied(), map.get(*key));
+    }
+    let (again, _): (DeferredMap<i32>, _) =
+        DeferredMap::from_values(hash_map.into_values().copied());
+    let mut round_trip: Vec<_> = again.iter().map(|(_, v)| *v).collect();
+    round_trip.sort();
+    assert_eq!(round_trip, expected);
+}
+
+#[test]
+fn test_from_values_empty() {
+    let (map, keys) = DeferredMap::<i32>::from_values(std::iter::empty());
+    assert!(map.is_empty());
+    assert!(keys.is_empty());
+    assert!(map.to_hashmap().is_empty());
+}
+
+#[test]
+fn test_epoch_advances_on_mutation() {
+    let mut map = DeferredMap::new();
+    let mut last = map.epoch();
+    let mut advanced = |map: &DeferredMap<i32>| {
+        let changed = map.epoch() != last;
+        last = map.epoch();
+        changed
+    };
+
+    let h = map.allocate_handle();
+    let k = h.key();
+    map.insert(h, 1);
+    assert!(advanced(&map));
+
+    // Reads and get_mut leave the epoch untouched
+    // 读取和 get_mut 不改变 epoch
+    assert_eq!(map.get(k), Some(&1));
+    *map.get_mut(k).unwrap() = 2;
+    assert!(map.contains_key(k));
+    assert_eq!(map.iter().count(), 1);
+    assert!(!advanced(&map));
+
+    let h = map.allocate_handle();
+    map.release_handle(h);
+    assert!(advanced(&map));
+
+    map.remove(k);
+    assert!(advanced(&map));
+
+    let h = map.allocate_handle();
+    map.insert(h, 3);
+    advanced(&map);
+    map.retain(|_, _| true);
+    assert!(advanced(&map));
+
+    map.clear();
+    assert!(advanced(&map));
+
+    let source = map.clone();
+    assert!(!advanced(&source));
+    map.clone_from(&source);
+    assert!(advanced(&map));
+}
+
+#[test]
+fn test_len_including_reserved() {
+    let mut map = DeferredMap::new();
+    let handles: Vec<_> = (0..3).map(|_| map.allocate_handle()).collect();
+
+    assert_eq!(map.len(), 0);
+    assert!(map.is_empty());
+    assert_eq!(map.len_including_reserved(), 3);
+
+    let mut handles = handles.into_iter();
+    let k = {
+        let h = handles.next().unwrap();
+        let k = h.key();
+        map.insert(h, 1);
+        k
+    };
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.len_including_reserved(), 3);
+
+    map.release_handle(handles.next().unwrap());
+    assert_eq!(map.len_including_reserved(), 2);
+
+    map.remove(k);
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.len_including_reserved(), 1);
+
+    // Reusing a freed slot counts as a reservation again
+    // 复用已释放的 slot 同样计为一次预留
+    let reused = map.allocate_handle();
+    assert_eq!(map.len_including_reserved(), 2);
+    map.insert(reused, 2);
+    assert_eq!(map.len_including_reserved(), 2);
+
+    map.clear();
+    assert_eq!(map.len_including_reserved(), 0);
+}
+
+#[test]
+fn test_iter_sorted_is_monotonic() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<usize>::from_values(0..50);
+    // Free slots in a scattered order so the free list is shuffled
+    // 以分散的顺序释放 slot，使空闲列表被打乱
+    for i in [7, 3, 41, 19, 0, 28, 33] {
+        map.remove(keys[i]);
+    }
+    for i in 0..5 {
+        let h = map.allocate_handle();
+        map.insert(h, 100 + i);
+    }
+
+    let sorted: Vec<_> = map
+        .iter_sorted()
+        .map(|(k, _)| (k.index(), k.generation()))
+        .collect();
+    assert_eq!(sorted.len(), map.len());
+    assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_retain_mut_keep_and_drop() {
+    use crate::Key;
+    use std::rc::Rc;
+
+    let tracker = Rc::new(());
+    let (mut map, keys) =
+        DeferredMap::<(i32, Rc<()>)>::from_values((0..6).map(|i| (i, Rc::clone(&tracker))));
+
+    // Mutate every element; keep the even ones, drop the odd ones
+    // 修改每个元素；保留偶数，丢弃奇数
+    map.retain_mut(|_, (v, _)| {
+        *v += 100;
+        *v % 2 == 0
+    });
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(Rc::strong_count(&tracker), 4);
+    for (i, &key) in keys.iter().enumerate() {
+        if i % 2 == 0 {
+            // Mutation to a kept element persists
+            // 对保留元素的修改持久生效
+            assert_eq!(map.get(key).map(|(v, _)| *v), Some(i as i32 + 100));
+        } else {
+            assert!(map.get(key).is_none());
+        }
+    }
+
+    // Removed slots come back with a bumped generation
+    // 被移除的 slot 以递增的代数重新使用
+    let h = map.allocate_handle();
+    let removed = keys[5];
+    assert_eq!(h.index(), removed.index());
+    assert!(h.generation() > removed.generation());
+    map.insert(h, (0, Rc::clone(&tracker)));
+}
+
+#[test]
+fn test_secondary_retain_mut() {
+    let (map, keys) = DeferredMap::<i32>::from_values(0..6);
+    let mut sec: crate::SecondaryMap<i32> = map.iter().map(|(k, v)| (k, *v)).collect();
+
+    sec.retain_mut(|_, v| {
+        *v *= 2;
+        *v < 6
+    });
+
+    assert_eq!(sec.len(), 3);
+    assert_eq!(sec.get(keys[0]), Some(&0));
+    assert_eq!(sec.get(keys[2]), Some(&4));
+    assert_eq!(sec.get(keys[3]), None);
+}
+
+#[test]
+fn test_key_at() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<i32>::from_values(0..4);
+    for &key in &keys {
+        assert_eq!(map.key_at(key.index()), Some(key));
+    }
+    assert_eq!(map.key_at(0), None);
+    assert_eq!(map.key_at(100), None);
+    assert_eq!(map.key_at(u32::MAX), None);
+
+    // Vacant slot, then the same index reused with a newer generation
+    // 空闲 slot，然后以更新的代数复用相同的索引
+    map.remove(keys[2]);
+    assert_eq!(map.key_at(keys[2].index()), None);
+
+    let h = map.allocate_handle();
+    // Reserved slots have no key yet
+    // 预留的 slot 还没有 key
+    assert_eq!(map.key_at(h.index()), None);
+    let new_key = h.key();
+    map.insert(h, 9);
+    assert_eq!(map.key_at(keys[2].index()), Some(new_key));
+    assert_eq!(map.get(map.key_at(new_key.index()).unwrap()), Some(&9));
+}
+
+#[test]
+fn test_clear_and_shrink_releases_memory() {
+    let (mut map, keys) = DeferredMap::<String>::from_values((0..10_000).map(|i| i.to_string()));
+    let _pending = map.allocate_handle();
+    assert!(map.reserved_capacity() >= 10_000);
+
+    let mut cleared = DeferredMap::<String>::from_values((0..10_000).map(|i| i.to_string())).0;
+    cleared.clear();
+    assert!(cleared.reserved_capacity() >= 10_000);
+
+    map.clear_and_shrink();
+    assert!(map.is_empty());
+    assert_eq!(map.len_including_reserved(), 0);
+    assert!(map.reserved_capacity() < 16);
+    assert!(map.get(keys[0]).is_none());
+
+    // The map is fully usable afterwards
+    // 之后 map 完全可用
+    let h = map.allocate_handle();
+    assert_eq!(h.index(), 1);
+    let k = h.key();
+    map.insert(h, "again".to_string());
+    assert_eq!(map.get(k).map(String::as_str), Some("again"));
+}
+
+#[test]
+fn test_contains_all_and_any() {
+    let (mut map, keys) = DeferredMap::<i32>::from_values(0..20);
+    assert!(map.contains_all(&keys));
+    assert!(map.contains_any(&keys));
+    assert!(map.contains_all(&[]));
+    assert!(!map.contains_any(&[]));
+
+    // Make one key stale; its slot gets reused by a newer generation
+    // 使一个 key 过期；其 slot 被更新的代数复用
+    map.remove(keys[13]);
+    let h = map.allocate_handle();
+    let fresh = h.key();
+    map.insert(h, 100);
+
+    assert!(!map.contains_all(&keys));
+    assert!(map.contains_any(&keys));
+    assert!(map.contains_all(&keys[..13]));
+    assert!(map.contains_all(&[fresh, keys[0]]));
+
+    let stale_only = [keys[13], keys[13]];
+    assert!(!map.contains_any(&stale_only));
+
+    let mut mixed = stale_only.to_vec();
+    mixed.push(keys[19]);
+    assert!(map.contains_any(&mixed));
+    assert!(!map.contains_all(&mixed));
+}
+
+#[test]
+fn test_iter_chunks() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..10);
+    map.remove(keys[3]);
+    map.remove(keys[7]);
+
+    let chunks: Vec<_> = map.iter_chunks(3).collect();
+    assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [3, 3, 2]);
+
+    // Concatenated chunks equal iter()
+    // 拼接后的批次等于 iter()
+    let flat: Vec<_> = chunks.into_iter().flatten().collect();
+    let all: Vec<_> = map.iter().collect();
+    assert_eq!(flat, all);
+
+    // Exact multiple and oversized chunks
+    // 恰好整除与超大批次
+    assert_eq!(
+        map.iter_chunks(4).map(|c| c.len()).collect::<Vec<_>>(),
+        [4, 4]
+    );
+    assert_eq!(map.iter_chunks(100).count(), 1);
+
+    // Empty map yields no chunks
+    // 空 map 不产出批次
+    let empty = DeferredMap::<i32>::new();
+    assert_eq!(empty.iter_chunks(2).count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "chunk size must be non-zero")]
+fn test_iter_chunks_zero() {
+    let map = DeferredMap::<i32>::new();
+    let _ = map.iter_chunks(0);
+}
+
+#[test]
+fn test_retain_keys_matches_retain() {
+    use std::collections::HashSet;
+
+    let (mut a, keys) = DeferredMap::<_>::from_values(0..20);
+    let mut b = a.clone();
+    let pending = a.allocate_handle();
+
+    let keep: HashSet<_> = keys
+        .iter()
+        .copied()
+        .filter(|k| k.index() % 3 == 0)
+        .collect();
+
+    let removed = a.retain_keys(&keep);
+    // `b` is a clone with its own map id, so compare on the same rule instead
+    // `b` 是拥有独立 map id 的克隆，因此用相同规则进行比较
+    b.retain(|k, _| k.index() % 3 == 0);
+
+    assert_eq!(removed, 20 - keep.len());
+    assert_eq!(a.len(), b.len());
+    assert_eq!(
+        a.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+        b.iter().map(|(_, v)| *v).collect::<Vec<_>>()
+    );
+
+    // Removed keys are invalid; the reserved handle survives
+    // 被移除的 key 失效；保留的 handle 仍然有效
+    for key in &keys {
+        assert_eq!(a.contains_key(*key), keep.contains(key));
+    }
+    a.insert(pending, 100);
+    assert_eq!(a.len(), keep.len() + 1);
+
+    // Freed slots are recycled
+    // 释放的 slot 被复用
+    let handle = a.allocate_handle();
+    assert!(handle.index() <= 20);
+    a.release_handle(handle);
+}
+
+#[test]
+fn test_slot_stats() {
+    use crate::SlotStats;
+
+    let mut map = DeferredMap::new();
+    assert_eq!(map.slot_stats(), SlotStats::default());
+
+    let mut keys = Vec::new();
+    for i in 0..6 {
+        let handle = map.allocate_handle();
+        keys.push(handle.key());
+        map.insert(handle, i);
+    }
+    map.remove(keys[1]);
+    map.remove(keys[4]);
+    let _a = map.allocate_handle();
+    let _b = map.allocate_handle();
+    let _c = map.allocate_handle();
+    map.remove(keys[0]);
+
+    let stats = map.slot_stats();
+    assert_eq!(stats.occupied + stats.reserved + stats.vacant, stats.total);
+    assert_eq!(stats.occupied, map.len());
+    assert_eq!(stats.reserved, map.reserved_len());
+    assert_eq!(stats.total, map.capacity());
+    assert_eq!(
+        stats,
+        SlotStats {
+            occupied: 3,
+            reserved: 3,
+            vacant: 1,
+            total: 7,
+        }
+    );
+}
+
+#[test]
+fn test_get_pair_mut() {
+    let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+
+    // Both valid
+    // 两者均有效
+    let (a, b) = map.get_pair_mut(keys[0], keys[2]).unwrap();
+    *a += 10;
+    *b += 20;
+    assert_eq!(map.get(keys[0]), Some(&11));
+    assert_eq!(map.get(keys[2]), Some(&23));
+
+    // Aliasing is rejected
+    // 拒绝别名
+    assert!(map.get_pair_mut(keys[1], keys[1]).is_none());
+
+    // Stale key in the same slot as a live key is rejected too
+    // 与存活 key 位于同一 slot 的过期 key 同样被拒绝
+    map.remove(keys[1]);
+    let handle = map.allocate_handle();
+    let reused = handle.key();
+    map.insert(handle, 5);
+    assert!(map.get_pair_mut(keys[1], reused).is_none());
+    assert!(map.get_pair_mut(reused, keys[1]).is_none());
+
+    // Out of bounds
+    // 越界
+    // SAFETY: generation 1 is non-zero
+    let far = unsafe { crate::Handle::<crate::DefaultKey>::from_raw((1 << 32) | 100) }.key();
+    assert!(map.get_pair_mut(keys[0], far).is_none());
+
+    assert_eq!(map.get(keys[0]), Some(&11));
+    assert_eq!(map.get(reused), Some(&5));
+}
+
+#[test]
+fn test_get_disjoint_mut() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..5);
+
+    let refs = map.get_disjoint_mut([keys[4], keys[0], keys[2]]).unwrap();
+    for r in refs {
+        *r *= 100;
+    }
+    assert_eq!(
+        map.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+        [0, 1, 200, 3, 400]
+    );
+
+    assert!(map.get_disjoint_mut([keys[0], keys[1], keys[0]]).is_none());
+    assert!(map.get_disjoint_mut::<0>([]).is_some());
+}
+
+#[test]
+fn test_versions_scan_matches_iter() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..16);
+    for key in keys.iter().step_by(3) {
+        map.remove(*key);
+    }
+    let _pending = map.allocate_handle();
+
+    let versions: Vec<u32> = map.versions().collect();
+    assert_eq!(versions.len(), 17);
+    assert_eq!(versions[0] & 0b11, 0b00);
+
+    // External scan: occupied slots with their generations
+    // 外部扫描：占用的 slot 及其代数
+    let scanned: Vec<(u32, u32)> = versions
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|&(_, v)| v & 0b11 == 0b11)
+        .map(|(i, v)| (i as u32, v >> 2))
+        .collect();
+    let expected: Vec<(u32, u32)> = map
+        .iter()
+        .map(|(k, _)| (k.index(), k.generation().get()))
+        .collect();
+    assert_eq!(scanned, expected);
+
+    // The reserved slot is visible as state 0b01
+    // 预留的 slot 显示为状态 0b01
+    assert_eq!(versions.iter().filter(|&&v| v & 0b11 == 0b01).count(), 1);
+}
+
+#[test]
+fn test_append_moves_all_values() {
+    let (mut a, a_keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+    let (mut b, b_keys) = DeferredMap::<_>::from_values([4, 5, 5, 6]);
+    a.remove(a_keys[1]);
+    b.remove(b_keys[0]);
+    let pending = b.allocate_handle();
+
+    let remap = a.append(&mut b);
+
+    // `other` ends empty; its old keys are dead
+    // `other` 最终为空；其旧 key 失效
+    assert!(b.is_empty());
+    for key in &b_keys {
+        assert!(!b.contains_key(*key));
+    }
+
+    // Value multiset is preserved
+    // 值的多重集保持不变
+    let mut values: Vec<_> = a.iter().map(|(_, v)| *v).collect();
+    values.sort();
+    assert_eq!(values, [1, 3, 5, 5, 6]);
+
+    // The remap points every old key at its value
+    // 重映射将每个旧 key 指向其值
+    assert_eq!(
+        remap.iter().map(|(old, _)| *old).collect::<Vec<_>>(),
+        &b_keys[1..]
+    );
+    for ((_, new), expected) in remap.iter().zip([5, 5, 6]) {
+        assert_eq!(a.get(*new), Some(&expected));
+    }
+    assert_eq!(a.get(a_keys[0]), Some(&1));
+
+    // Outstanding handles of `other` stay usable
+    // `other` 未完成的 handle 仍可使用
+    b.insert(pending, 7);
+    assert_eq!(b.len(), 1);
+}
+
+#[test]
+fn test_normalize_generations() {
+    let mut map = DeferredMap::new();
+    let mut keys = Vec::new();
+    for round in 0..3 {
+        for i in 0..4 {
+            let handle = map.allocate_handle();
+            keys.push(handle.key());
+            map.insert(handle, round * 10 + i);
+        }
+        if round < 2 {
+            for key in keys.drain(..) {
+                map.remove(key);
+            }
+        }
+    }
+    // Live keys are now at generation 3
+    // 存活 key 现在处于第 3 代
+    assert!(keys.iter().all(|k| k.generation().get() == 3));
+    map.remove(keys[1]);
+    let pending = map.allocate_handle();
+    let pending_generation = pending.generation();
+
+    let remap = map.normalize_generations();
+    assert_eq!(remap.len(), 3);
+
+    // Old keys die, new keys resolve to the same values
+    // 旧 key 失效，新 key 解析到相同的值
+    for &(old, new) in &remap {
+        assert_eq!(old.index(), new.index());
+        assert_eq!(new.generation(), crate::Generation::MIN);
+        assert_eq!(map.get(old), None);
+    }
+    assert_eq!(
+        remap
+            .iter()
+            .map(|&(_, new)| *map.get(new).unwrap())
+            .collect::<Vec<_>>(),
+        [20, 22, 23]
+    );
+
+    // The reserved slot is untouched
+    // 预留的 slot 不受影响
+    assert_eq!(pending.generation(), pending_generation);
+    let pending_key = pending.key();
+    map.insert(pending, 99);
+    assert_eq!(map.get(pending_key), Some(&99));
+    assert_eq!(map.len(), 4);
+}
+
+#[test]
+fn test_get2_mut_or_same() {
+    use crate::OneOrTwo;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+
+    // Same key
+    // 相同 key
+    match map.get2_mut_or_same(keys[1], keys[1]) {
+        Some(OneOrTwo::One(v)) => *v += 100,
+        other => panic!("expected One, got {other:?}"),
+    }
+    assert_eq!(map.get(keys[1]), Some(&102));
+
+    // Distinct keys, in argument order
+    // 不同 key，按参数顺序
+    match map.get2_mut_or_same(keys[2], keys[0]) {
+        Some(OneOrTwo::Two(a, b)) => {
+            assert_eq!((*a, *b), (3, 1));
+            std::mem::swap(a, b);
+        }
+        other => panic!("expected Two, got {other:?}"),
+    }
+    assert_eq!(map.get(keys[0]), Some(&3));
+    assert_eq!(map.get(keys[2]), Some(&1));
+
+    // Invalid keys
+    // 无效 key
+    map.remove(keys[0]);
+    assert!(map.get2_mut_or_same(keys[0], keys[0]).is_none());
+    assert!(map.get2_mut_or_same(keys[0], keys[1]).is_none());
+    let handle = map.allocate_handle();
+    let reused = handle.key();
+    map.insert(handle, 0);
+    assert!(map.get2_mut_or_same(keys[0], reused).is_none());
+}
+
+#[test]
+fn test_occupied_indices_matches_iter() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..8);
+    map.remove(keys[0]);
+    map.remove(keys[5]);
+    // Reserved slots are not occupied
+    // 预留的 slot 不算占用
+    let _pending = map.allocate_handle();
+
+    let expected: Vec<u32> = map.iter().map(|(k, _)| k.index()).collect();
+    assert_eq!(map.occupied_indices(), expected);
+    assert!(!map.occupied_indices().contains(&0));
+
+    map.clear();
+    assert!(map.occupied_indices().is_empty());
+}
+
+#[test]
+fn test_map_values_preserves_keys() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..5);
+    map.remove(keys[1]);
+    let pending = map.allocate_handle();
+    let stale = keys[1];
+    let versions: Vec<u32> = map.versions().collect();
+
+    let mut mapped = map.map_values(|v: i32| format!("#{v}"));
+
+    assert_eq!(mapped.versions().collect::<Vec<_>>(), versions);
+    assert_eq!(mapped.len(), 4);
+    assert_eq!(mapped.get(keys[0]), Some(&"#0".to_string()));
+    assert_eq!(mapped.get(keys[4]), Some(&"#4".to_string()));
+    assert_eq!(mapped.get(stale), None);
+
+    // Outstanding handles and the free list carry over
+    // 未完成的 handle 和空闲列表都会保留
+    let pending_key = pending.key();
+    mapped.insert(pending, "late".to_string());
+    assert_eq!(mapped.get(pending_key), Some(&"late".to_string()));
+    let h = mapped.allocate_handle();
+    mapped.insert(h, "new".to_string());
+    assert!(mapped.validate().is_ok());
+}
+
+#[test]
+fn test_filter_map_values_frees_dropped_entries() {
+    let (map, keys) = DeferredMap::<_>::from_values(0..10);
+
+    let evens = map.filter_map_values(|v: i32| (v % 2 == 0).then(|| v as u64 * 2));
+    assert_eq!(evens.len(), 5);
+    assert!(evens.validate().is_ok());
+    for (i, &key) in keys.iter().enumerate() {
+        if i % 2 == 0 {
+            assert_eq!(evens.get(key), Some(&(i as u64 * 2)));
+        } else {
+            assert_eq!(evens.get(key), None);
+        }
+    }
+
+    // Freed slots are reused without reviving the dropped keys
+    // 释放的 slot 会被复用，而不会让被丢弃的 key 复活
+    let mut evens = evens;
+    let before = evens.slot_stats().total;
+    for _ in 0..5 {
+        let h = evens.allocate_handle();
+        evens.insert(h, 0);
+    }
+    assert_eq!(evens.slot_stats().total, before);
+    for &key in keys.iter().skip(1).step_by(2) {
+        assert_eq!(evens.get(key), None);
+    }
+    assert!(evens.validate().is_ok());
+}
+
+#[test]
+fn test_raw_key_lookups_match_typed_keys() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values([10, 20, 30]);
+    let raws: Vec<u64> = keys.iter().map(|k| k.raw()).collect();
+
+    for (&key, &raw) in keys.iter().zip(&raws) {
+        assert_eq!(map.get_raw(raw), map.get(key));
+        assert_eq!(map.contains_key_raw(raw), map.contains_key(key));
+    }
+
+    *map.get_mut_raw(raws[1]).unwrap() += 1;
+    assert_eq!(map.get(keys[1]), Some(&21));
+
+    assert_eq!(map.remove_raw(raws[0]), Some(10));
+    assert_eq!(map.get(keys[0]), None);
+    assert_eq!(map.get_raw(raws[0]), None);
+    assert_eq!(map.remove_raw(raws[0]), None);
+
+    // Zero generation and out-of-bounds raw values never resolve
+    // 代数为零和越界的原始值永远无法解析
+    assert_eq!(map.get_raw(u64::from(keys[2].index())), None);
+    assert!(!map.contains_key_raw(0));
+    assert_eq!(map.get_raw((1 << 32) | 1000), None);
+    assert!(map.validate().is_ok());
+}
+
+#[test]
+fn test_get_raw_skips_map_id_check() {
+    use crate::Key;
+
+    let (a, a_keys) = DeferredMap::<_>::from_values([1]);
+    let (b, _) = DeferredMap::<_>::from_values([2]);
+
+    // Same index and generation in another map: get_raw resolves it without asserting
+    // 另一个 map 中相同的 index 和代数：get_raw 直接解析而不断言
+    let raw = a_keys[0].raw();
+    assert_eq!(a.get_raw(raw), Some(&1));
+    assert_eq!(b.get_raw(raw), Some(&2));
+}
+
+#[test]
+fn test_iterated_keys_round_trip() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..6);
+    map.remove(keys[2]);
+    let h = map.allocate_handle();
+    map.insert(h, 60);
+
+    let iterated: Vec<_> = map.iter().map(|(k, _)| k).collect();
+    assert_eq!(iterated.len(), map.len());
+    for &key in &iterated {
+        assert!(map.get(key).is_some());
+    }
+    assert!(!iterated.contains(&keys[2]));
+
+    let mutated: Vec<_> = map.iter_mut().map(|(k, v)| (k, *v)).collect();
+    for (key, value) in mutated {
+        assert_eq!(map.get(key), Some(&value));
+        assert_eq!(map.remove(key), Some(value));
+    }
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_iteration_follows_slot_index_not_insertion_order() {
+    use crate::Key;
+
+    let mut map = DeferredMap::new();
+    let mut handles: Vec<_> = (0..8).map(|_| Some(map.allocate_handle())).collect();
+
+    // Insert in a scrambled order
+    // 以打乱的顺序插入
+    for i in [5, 2, 7, 0, 3, 6, 1, 4] {
+        let handle = handles[i].take().unwrap();
+        let index = handle.index();
+        map.insert(handle, index);
+    }
+
+    let indices: Vec<u32> = map.iter().map(|(k, _)| k.index()).collect();
+    assert_eq!(indices, (1..=8).collect::<Vec<_>>());
+    assert!(map.iter().all(|(k, &v)| k.index() == v));
+
+    let mut_indices: Vec<u32> = map.iter_mut().map(|(k, _)| k.index()).collect();
+    assert_eq!(mut_indices, indices);
+    let sorted: Vec<u32> = map.iter_sorted().map(|(k, _)| k.index()).collect();
+    assert_eq!(sorted, indices);
+
+    // Reused slots keep their position in the order
+    // 被复用的 slot 在顺序中保持其位置
+    let key = map.iter().nth(5).unwrap().0;
+    map.remove(key);
+    let h = map.allocate_handle();
+    map.insert(h, key.index());
+    let indices_after: Vec<u32> = map.iter().map(|(k, _)| k.index()).collect();
+    assert_eq!(indices_after, indices);
+}
+
+#[test]
+fn test_get_cloned() {
+    let (mut map, keys) = DeferredMap::<_>::from_values([vec![1, 2], vec![3]]);
+
+    let mut owned = map.get_cloned(keys[0]).unwrap();
+    owned.push(9);
+    assert_eq!(map.get(keys[0]), Some(&vec![1, 2]));
+    assert_eq!(map.get_cloned(keys[1]), Some(vec![3]));
+
+    map.remove(keys[1]);
+    assert_eq!(map.get_cloned(keys[1]), None);
+}
+
+#[test]
+fn test_utilization_and_fragmentation() {
+    let empty: DeferredMap<u8> = DeferredMap::new();
+    assert_eq!(empty.utilization(), 0.0);
+    assert_eq!(empty.fragmentation(), 0.0);
+
+    let mut map: DeferredMap<u32> = DeferredMap::with_capacity(8);
+    assert_eq!(map.capacity(), 8);
+    let keys = map.seed(0..8);
+    assert_eq!(map.utilization(), 1.0);
+    assert_eq!(map.fragmentation(), 0.0);
+
+    for &key in keys.iter().step_by(2) {
+        map.remove(key);
+    }
+    assert_eq!(map.utilization(), 0.5);
+    assert_eq!(map.fragmentation(), 0.5);
+
+    map.remove(keys[1]);
+    assert_eq!(map.utilization(), 3.0 / 8.0);
+    assert_eq!(map.utilization() + map.fragmentation(), 1.0);
+}
+
+#[test]
+fn test_retain_reports_removed_and_retained() {
+    let (mut map, _) = DeferredMap::<_>::from_values(0..20);
+    // Reserved slots are neither removed nor retained
+    // 预留的 slot 既不算移除也不算保留
+    let pending = map.allocate_handle();
+
+    let len_before = map.len();
+    let result = map.retain(|_, v| *v % 3 == 0);
+    assert_eq!(result.removed + result.retained, len_before);
+    assert_eq!(result.retained, 7);
+    assert_eq!(result.retained, map.len());
+
+    let result = map.retain_mut(|_, v| {
+        *v += 1;
+        true
+    });
+    assert_eq!(
+        result,
+        crate::RetainResult {
+            removed: 0,
+            retained: 7
+        }
+    );
+
+    let result = map.retain(|_, _| false);
+    assert_eq!(result.removed, 7);
+    assert!(map.is_empty());
+    assert_eq!(map.retain(|_, _| true), crate::RetainResult::default());
+
+    map.insert(pending, 0);
+    assert!(map.validate().is_ok());
+}
+
+#[test]
+fn test_split_mut_matches_sequential() {
+    let (mut map, keys) = DeferredMap::<u64>::from_values(0..1000);
+    for key in keys.iter().step_by(7) {
+        map.remove(*key);
+    }
+    let sequential: u64 = map.iter().map(|(_, v)| v).sum();
+    let order: Vec<_> = map.iter().map(|(k, _)| k).collect();
+
+    for parts in [1, 3, 8, 2000] {
+        let ranges = map.split_mut(parts);
+        assert_eq!(ranges.len(), parts);
+
+        let sum: u64 = std::thread::scope(|scope| {
+            let workers: Vec<_> = ranges
+                .into_iter()
+                .map(|range| scope.spawn(move || range.map(|(_, v)| *v).sum::<u64>()))
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).sum()
+        });
+        assert_eq!(sum, sequential);
+
+        // Chained ranges reproduce iter_mut exactly
+        // 依次连接的区间与 iter_mut 完全一致
+        let chained: Vec<_> = map
+            .split_mut(parts)
+            .into_iter()
+            .flatten()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(chained, order);
+    }
+
+    let total: u64 = std::thread::scope(|scope| {
+        let workers: Vec<_> = map
+            .split_mut(4)
+            .into_iter()
+            .map(|range| {
+                scope.spawn(move || {
+                    range
+                        .map(|(_, v)| {
+                            *v += 1;
+                            *v - 1
+                        })
+                        .sum::<u64>()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+    assert_eq!(total, sequential);
+    assert_eq!(
+        map.iter().map(|(_, v)| v).sum::<u64>(),
+        sequential + map.len() as u64
+    );
+
+    let mut empty = DeferredMap::<u64>::new();
+    assert!(
+        empty
+            .split_mut(3)
+            .into_iter()
+            .all(|mut r| r.next().is_none())
+    );
+}
+
+#[test]
+fn test_seed_keys_resolve() {
+    let mut map = DeferredMap::<u32>::new();
+    let h = map.allocate_handle();
+    let first = map.insert(h, 0);
+    let pending = map.allocate_handle();
+    let h = map.allocate_handle();
+    let hole = map.insert(h, 0);
+    map.remove(hole);
+
+    // Seeding appends past the vacant hole instead of reusing it
+    // seed 追加到空闲空洞之后，而不是复用它
+    let keys = map.seed(1..=100);
+    assert_eq!(keys.len(), 100);
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(key.index(), 4 + i as u32);
+        assert_eq!(map.get(*key), Some(&(i as u32 + 1)));
+    }
+    assert_eq!(map.len(), 101);
+    assert_eq!(map.reserved_len(), 1);
+    map.validate().unwrap();
+
+    // The free list still hands out the hole, then fresh slots after the seeded ones
+    // 空闲列表仍先分配该空洞，然后是 seed 之后的新 slot
+    let reused = map.allocate_handle();
+    assert_eq!(reused.index(), hole.index());
+    let fresh = map.allocate_handle();
+    assert_eq!(fresh.index(), 104);
+    map.insert(reused, 7);
+    map.insert(fresh, 8);
+    map.insert(pending, 9);
+    assert_eq!(map.get(first), Some(&0));
+    map.validate().unwrap();
+
+    assert!(map.seed(std::iter::empty()).is_empty());
+    map.validate().unwrap();
+}
+
+#[test]
+fn test_seed_panicking_iterator_keeps_free_list_valid() {
+    for fifo in [false, true] {
+        let mut map = if fifo {
+            DeferredMap::<u32>::with_fifo_free_list()
+        } else {
+            DeferredMap::new()
+        };
+        let hole = map.seed([0, 1])[1];
+        map.remove(hole);
+        let epoch = map.epoch();
+
+        // The values iterator panics after two values were appended
+        // values 迭代器在追加两个值之后 panic
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.seed((10..20).map(|v| if v < 12 { v } else { panic!("boom") }))
+        }));
+        assert!(result.is_err());
+        assert_eq!(map.len(), 3);
+        assert!(map.epoch() > epoch);
+        map.validate().unwrap();
+
+        // The free list reuses the hole, then continues past the seeded slots
+        // 空闲列表先复用空洞，然后从 seed 的 slot 之后继续
+        let reused = map.allocate_handle();
+        assert_eq!(reused.index(), hole.index());
+        let fresh = map.allocate_handle();
+        assert_eq!(fresh.index(), 5);
+        map.insert(reused, 2);
+        map.insert(fresh, 3);
+        assert_eq!(map.len(), 5);
+        map.validate().unwrap();
+    }
+}
+
+#[test]
+fn test_occupancy_bitmap_reconstructs_live_values() {
+    let (mut map, keys) = DeferredMap::<u32>::from_values(0..200);
+    for key in keys.iter().step_by(3) {
+        map.remove(*key);
+    }
+    let pending = map.allocate_handle();
+
+    // Rebuild (index, value) pairs from the bitmap and the packed versions only
+    // 仅凭位图和打包的版本字重建 (index, value) 对
+    let bitmap = map.occupancy_bitmap();
+    let versions: Vec<u32> = map.versions().collect();
+    assert_eq!(bitmap.len(), versions.len().div_ceil(64));
+    let mut rebuilt = Vec::new();
+    for (word_index, &word) in bitmap.iter().enumerate() {
+        let mut bits = word;
+        while bits != 0 {
+            let index = word_index * 64 + bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            let raw = (u64::from(versions[index] >> 2) << 32) | index as u64;
+            rebuilt.push((index as u32, *map.get_raw(raw).unwrap()));
+        }
+    }
+
+    let expected: Vec<_> = map.iter().map(|(k, v)| (k.index(), *v)).collect();
+    assert_eq!(rebuilt, expected);
+    assert_eq!(bitmap[0] & 1, 0);
+    // Reserved slots are not occupied
+    // 预留的 slot 不算被占用
+    let pending_index = pending.index() as usize;
+    assert_eq!(bitmap[pending_index / 64] & (1 << (pending_index % 64)), 0);
+
+    map.release_handle(pending);
+}
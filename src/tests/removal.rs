@@ -121,7 +121,7 @@ fn test_removal_increments_generation() {
 
     // Generation (high 30 bits) should increment by 1
     // Generation（高 30 位）应该递增 1
-    assert_eq!(gen2.get(), gen1.get() + 1);
+    assert_eq!(gen2, gen1 + 1);
 }
 
 #[test]
@@ -216,7 +216,7 @@ fn test_removal_frees_memory() {
     // 插入大字符串
     let mut keys = Vec::new();
     for _ in 0..100 {
-        let large_string = format!("{}", "a".repeat(1000));
+        let large_string = "a".repeat(1000);
         let h = map.allocate_handle();
         let k = h.key();
         map.insert(h, large_string);
@@ -295,8 +295,8 @@ fn test_removal_pattern_fifo() {
 
     // Remove in FIFO order (first in, first out)
     // 以 FIFO 顺序删除（先进先出）
-    for i in 0..10 {
-        let removed = map.remove(keys[i]);
+    for (i, &key) in keys.iter().enumerate() {
+        let removed = map.remove(key);
         assert_eq!(removed, Some(i));
     }
 
@@ -429,3 +429,657 @@ fn test_double_removal_fails() {
     // 第二次删除失败
     assert_eq!(map.remove(k), None);
 }
+
+#[test]
+fn test_len_never_underflows() {
+    let mut map = DeferredMap::<i32>::new();
+
+    let h = map.allocate_handle();
+    let k = h.key();
+
+    // Removing a reserved-but-empty key does not touch the count
+    // 删除已预留但未插入的 key 不影响计数
+    assert_eq!(map.remove(k), None);
+    assert_eq!(map.len(), 0);
+
+    map.insert(h, 1);
+    assert_eq!(map.remove(k), Some(1));
+
+    // Every further removal path on an empty map is a no-op
+    // 在空 map 上的其他删除路径都是空操作
+    assert_eq!(map.remove(k), None);
+    map.retain(|_, _| false);
+    assert_eq!(map.len(), 0);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_remove_if_predicate_true() {
+    let mut map = DeferredMap::new();
+    let h = map.allocate_handle();
+    let k = h.key();
+    map.insert(h, 10);
+
+    assert_eq!(map.remove_if(k, |v| *v == 10), Some(10));
+    assert_eq!(map.get(k), None);
+    assert!(map.is_empty());
+
+    // The freed slot is reused with a new generation
+    // 被释放的 slot 以新的 generation 复用
+    let h2 = map.allocate_handle();
+    assert_eq!(h2.index(), k.index());
+    assert_ne!(h2.key(), k);
+}
+
+#[test]
+fn test_remove_if_predicate_false() {
+    let mut map = DeferredMap::new();
+    let h = map.allocate_handle();
+    let k = h.key();
+    map.insert(h, 10);
+
+    assert_eq!(map.remove_if(k, |v| *v > 10), None);
+    assert_eq!(map.get(k), Some(&10));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_remove_if_stale_key() {
+    let mut map = DeferredMap::new();
+    let h = map.allocate_handle();
+    let k = h.key();
+    map.insert(h, 10);
+    map.remove(k);
+
+    let mut called = false;
+    assert_eq!(
+        map.remove_if(k, |_| {
+            called = true;
+            true
+        }),
+        None
+    );
+    assert!(!called);
+}
+
+#[test]
+fn test_extract_into_splits_even_values() {
+    let (mut map, keys) = DeferredMap::<i32>::from_values(0..10);
+    let (evens, remap) = map.extract_into(|_, v| v % 2 == 0);
+
+    assert_eq!(map.len(), 5);
+    assert_eq!(evens.len(), 5);
+    assert!(map.iter().all(|(_, v)| v % 2 == 1));
+    assert!(evens.iter().all(|(_, v)| v % 2 == 0));
+
+    // Old keys are invalid in the source, new keys resolve in the extracted map
+    // 旧 key 在源 map 中失效，新 key 可在提取出的 map 中解析
+    assert_eq!(remap.len(), 5);
+    for (i, &(old, new)) in remap.iter().enumerate() {
+        assert_eq!(old, keys[i * 2]);
+        assert_eq!(map.get(old), None);
+        assert_eq!(evens.get(new), Some(&(i as i32 * 2)));
+    }
+    for (i, &key) in keys.iter().enumerate().skip(1).step_by(2) {
+        assert_eq!(map.get(key), Some(&(i as i32)));
+    }
+
+    // Freed slots are reused with a newer generation
+    // 释放的 slot 以更新的代数被复用
+    let h = map.allocate_handle();
+    let reused = h.key();
+    map.insert(h, 42);
+    assert!(remap.iter().all(|&(old, _)| old != reused));
+    assert_eq!(map.len(), 6);
+}
+
+#[test]
+fn test_bulk_remove_with_duplicates_and_stale_keys() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..5);
+
+    // Make keys[1] stale and reuse its slot
+    // 使 keys[1] 过期并复用其 slot
+    map.remove(keys[1]);
+    let handle = map.allocate_handle();
+    let reused = handle.key();
+    map.insert(handle, 10);
+
+    let epoch = map.epoch();
+    let removed = map.bulk_remove(&[keys[0], keys[1], keys[3], keys[0], reused, keys[3]]);
+    assert_eq!(removed, [Some(0), None, Some(3), None, Some(10), None]);
+
+    // One epoch bump for the batch, slots freed in slice order
+    // 整个批次只递增一次 epoch，slot 按切片顺序释放
+    assert_eq!(map.epoch(), epoch + 1);
+    assert_eq!(
+        map.free_list_indices(),
+        [reused.index(), keys[3].index(), keys[0].index()]
+    );
+    map.validate().unwrap();
+    assert_eq!(map.bulk_remove(&[keys[0]]), [None]);
+    assert_eq!(map.epoch(), epoch + 1);
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(keys[2]), Some(&2));
+    assert_eq!(map.get(keys[4]), Some(&4));
+
+    // Freed slots go back on the free list
+    // 释放的 slot 回到空闲列表
+    for _ in 0..3 {
+        let handle = map.allocate_handle();
+        assert!(handle.index() <= 5);
+        map.insert(handle, 0);
+    }
+    assert_eq!(map.slot_stats().total, 5);
+}
+
+#[test]
+fn test_bulk_remove_empty() {
+    let mut map = DeferredMap::<i32>::new();
+    assert!(map.bulk_remove(&[]).is_empty());
+}
+
+#[test]
+fn test_remove_guarded_restore_preserves_key() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..4);
+
+    // Remove two entries, restore them out of order so one is not the free head
+    // 删除两个条目，乱序恢复，使其中一个不在空闲列表头部
+    let first = map.remove_guarded(keys[1]).unwrap();
+    let mut second = map.remove_guarded(keys[2]).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(first.key(), keys[1]);
+    *second.value_mut() += 10;
+
+    assert_eq!(first.restore(&mut map), Ok(keys[1]));
+    assert_eq!(second.restore(&mut map), Ok(keys[2]));
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.get(keys[1]), Some(&1));
+    assert_eq!(map.get(keys[2]), Some(&12));
+
+    // The free list is intact: new allocations append fresh slots
+    // 空闲列表完好：新的分配追加新 slot
+    let handle = map.allocate_handle();
+    assert_eq!(handle.index(), 5);
+    map.insert(handle, 4);
+    assert_eq!(map.slot_stats().vacant, 0);
+}
+
+#[test]
+fn test_remove_guarded_commit_kills_key() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, "a");
+
+    let guard = map.remove_guarded(key).unwrap();
+    assert_eq!(guard.commit(), "a");
+    assert_eq!(map.get(key), None);
+    assert!(map.remove_guarded(key).is_none());
+
+    // The slot is recycled under a new generation
+    // slot 以新代数被复用
+    let handle = map.allocate_handle();
+    let new_key = handle.key();
+    map.insert(handle, "b");
+    assert_eq!(new_key.index(), key.index());
+    assert_ne!(new_key, key);
+}
+
+#[test]
+fn test_remove_guarded_restore_after_reuse_fails() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 1);
+
+    // Slot reused and still reserved
+    // slot 被复用且仍处于预留状态
+    let guard = map.remove_guarded(key).unwrap();
+    let pending = map.allocate_handle();
+    assert_eq!(pending.index(), key.index());
+    let guard_value = guard.restore(&mut map);
+    assert_eq!(guard_value, Err(1));
+
+    // Slot reused then released: generation moved on twice
+    // slot 被复用后释放：代数前进了两次
+    map.release_handle(pending);
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    map.insert(handle, 2);
+    let guard = map.remove_guarded(key).unwrap();
+    let pending = map.allocate_handle();
+    map.release_handle(pending);
+    assert_eq!(guard.restore(&mut map), Err(2));
+    assert_eq!(map.get(key), None);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_extract_if_removes_matching() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..10);
+
+    let extracted: Vec<_> = map
+        .extract_if(|_, v| {
+            *v += 100;
+            *v % 3 == 0
+        })
+        .collect();
+
+    assert_eq!(extracted, [(keys[2], 102), (keys[5], 105), (keys[8], 108)]);
+    assert_eq!(map.len(), 7);
+    for (i, key) in keys.iter().enumerate() {
+        if (i + 100) % 3 == 0 {
+            assert_eq!(map.get(*key), None);
+        } else {
+            // Mutations of kept entries persist
+            // 保留条目的修改会保留
+            assert_eq!(map.get(*key), Some(&(i + 100)));
+        }
+    }
+}
+
+#[test]
+fn test_extract_if_early_drop() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..10);
+
+    let mut calls = 0;
+    let mut iter = map.extract_if(|_, v| {
+        calls += 1;
+        *v % 2 == 0
+    });
+    assert_eq!(iter.next(), Some((keys[0], 0)));
+    assert_eq!(iter.next(), Some((keys[2], 2)));
+    drop(iter);
+
+    // Predicate was evaluated for indices 0..=2 only
+    // 谓词只对索引 0..=2 求值
+    assert_eq!(calls, 3);
+    assert_eq!(map.len(), 8);
+    assert_eq!(map.get(keys[0]), None);
+    assert_eq!(map.get(keys[2]), None);
+    assert_eq!(map.get(keys[4]), Some(&4));
+
+    // Removed slots are recycled
+    // 被移除的 slot 被复用
+    let handle = map.allocate_handle();
+    assert_eq!(handle.index(), keys[2].index());
+    map.insert(handle, 20);
+}
+
+// Value that counts its drops and can optionally panic while being dropped
+// 计数 drop 次数并可在 drop 时 panic 的值
+struct DropCounter {
+    drops: std::rc::Rc<std::cell::Cell<usize>>,
+    panic_on_drop: bool,
+}
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+        if self.panic_on_drop {
+            panic!("DropCounter panicked on drop");
+        }
+    }
+}
+
+#[test]
+fn test_remove_never_double_drops() {
+    let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+    let (mut map, keys) = DeferredMap::<_>::from_values((0..5).map(|_| DropCounter {
+        drops: drops.clone(),
+        panic_on_drop: false,
+    }));
+
+    drop(map.remove(keys[1]));
+    drop(map.remove(keys[3]));
+    assert_eq!(drops.get(), 2);
+
+    // Removing again does not touch the moved-out value
+    // 再次删除不会触及已移出的值
+    assert!(map.remove(keys[1]).is_none());
+    assert_eq!(drops.get(), 2);
+
+    drop(map);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn test_panicking_drop_during_retain_no_double_drop() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+    let (mut map, keys) = DeferredMap::<_>::from_values((0..5).map(|i| DropCounter {
+        drops: drops.clone(),
+        panic_on_drop: i == 2,
+    }));
+
+    // The removed value's Drop panics after its slot was already vacated
+    // 被移除值的 Drop 在其 slot 已被腾空后 panic
+    let result = catch_unwind(AssertUnwindSafe(|| map.retain(|_, _| false)));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3);
+    assert_eq!(map.len(), 2);
+    assert!(!map.contains_key(keys[2]));
+
+    drop(map);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn test_trim_drops_vacant_tail_only() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..10);
+
+    // Hole in the middle stays, tail goes
+    // 中间的空洞保留，末尾被移除
+    map.remove(keys[3]);
+    for &key in &keys[6..] {
+        map.remove(key);
+    }
+    // A reserved slot stops the trim below it
+    // 预留的 slot 会阻止截断其下方的 slot
+    let pending = map.allocate_handle();
+    let pending_key = pending.key();
+    assert_eq!(pending_key.index(), 10);
+
+    assert_eq!(map.trim(), 0);
+    map.release_handle(pending);
+    assert_eq!(map.trim(), 4);
+    assert_eq!(map.slot_stats().total, 6);
+    assert!(map.validate().is_ok());
+
+    for (i, &key) in keys.iter().enumerate().take(6) {
+        if i == 3 {
+            assert_eq!(map.get(key), None);
+        } else {
+            assert_eq!(map.get(key), Some(&i));
+        }
+    }
+
+    // The hole is reused first, then fresh slots are appended
+    // 先复用空洞，然后追加新 slot
+    let h = map.allocate_handle();
+    assert_eq!(h.index(), 4);
+    map.insert(h, 40);
+    let h = map.allocate_handle();
+    assert_eq!(h.index(), 7);
+    map.insert(h, 70);
+    assert!(map.validate().is_ok());
+
+    // Emptied maps trim down to the sentinel
+    // 清空的 map 会截断到只剩 sentinel
+    let keys: Vec<_> = map.iter().map(|(k, _)| k).collect();
+    for key in keys {
+        map.remove(key);
+    }
+    assert_eq!(map.trim(), 7);
+    assert_eq!(map.slot_stats().total, 0);
+    assert!(map.validate().is_ok());
+}
+
+#[test]
+fn test_release_all_reserved_reclaims_leaked_handles() {
+    let mut map = DeferredMap::new();
+    let kept = map.allocate_handle();
+    let kept_key = map.insert(kept, 0);
+
+    // Leak a batch of handles by dropping them
+    // 通过 drop 泄漏一批 handle
+    let leaked: Vec<_> = (0..5).map(|_| map.allocate_handle()).collect();
+    let leaked_keys: Vec<_> = leaked.iter().map(|h| h.key()).collect();
+    drop(leaked);
+    assert_eq!(map.reserved_len(), 5);
+
+    let total = map.slot_stats().total;
+    assert_eq!(map.release_all_reserved(), 5);
+    assert_eq!(map.reserved_len(), 0);
+    assert_eq!(map.release_all_reserved(), 0);
+    assert!(map.validate().is_ok());
+
+    // Reclaimed slots are reused without growing, under new generations
+    // 回收的 slot 以新代数被复用，不会增长
+    for i in 1..=5 {
+        let h = map.allocate_handle();
+        let key = map.insert(h, i);
+        assert!(leaked_keys.iter().any(|k| k.index() == key.index()));
+        assert!(!leaked_keys.contains(&key));
+    }
+    assert_eq!(map.slot_stats().total, total);
+    assert_eq!(map.get(kept_key), Some(&0));
+    assert_eq!(map.len(), 6);
+    assert!(map.validate().is_ok());
+}
+
+#[test]
+fn test_insert_with_reclaimed_handle_fails_cleanly() {
+    use crate::DeferredMapError;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let mut map = DeferredMap::new();
+    let held_again = map.allocate_handle();
+    let held = map.allocate_handle();
+    let held_key = held.key();
+    assert_eq!(map.release_all_reserved(), 2);
+
+    // The reclaimed slot is reused before the held handle comes back
+    // 在仍持有的 handle 回来之前，被回收的 slot 已被复用
+    let h = map.allocate_handle();
+    let live = map.insert(h, 1);
+    assert_eq!(live.index(), held_key.index());
+    let epoch = map.epoch();
+
+    // Same outcome in every build profile: insert panics, try_insert reports it
+    // 在任何构建配置下结果都相同：insert panic，try_insert 报告错误
+    let result = catch_unwind(AssertUnwindSafe(|| map.insert(held, 2)));
+    assert!(result.is_err());
+    assert_eq!(
+        map.try_insert(held_again, 3),
+        Err(DeferredMapError::GenerationMismatch)
+    );
+
+    assert_eq!(map.epoch(), epoch);
+    assert_eq!(map.get(live), Some(&1));
+    assert_eq!(map.get(held_key), None);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.reserved_len(), 0);
+    map.validate().unwrap();
+}
+
+#[test]
+fn test_dedup_values_reports_canonical_keys() {
+    let values = [3, 1, 3, 2, 1, 3, 4];
+    let (mut map, keys) = DeferredMap::<_>::from_values(values);
+
+    let remap = map.dedup_values(|a, b| a == b);
+    assert_eq!(map.len(), 4);
+    assert_eq!(remap.len(), 3);
+    assert_eq!(remap[&keys[2]], keys[0]);
+    assert_eq!(remap[&keys[5]], keys[0]);
+    assert_eq!(remap[&keys[4]], keys[1]);
+
+    // Survivors keep their keys; duplicates are gone
+    // 保留项保持其 key；重复项被移除
+    for (i, &key) in keys.iter().enumerate() {
+        match remap.get(&key) {
+            Some(&canonical) => {
+                assert_eq!(map.get(key), None);
+                assert_eq!(map.get(canonical), Some(&values[i]));
+            }
+            None => assert_eq!(map.get(key), Some(&values[i])),
+        }
+    }
+    assert!(map.validate().is_ok());
+
+    // Custom equivalence: same parity
+    // 自定义等价关系：奇偶性相同
+    let remap = map.dedup_values(|a, b| a % 2 == b % 2);
+    assert_eq!(map.len(), 2);
+    assert_eq!(remap[&keys[1]], keys[0]);
+    assert_eq!(remap[&keys[6]], keys[3]);
+    assert!(map.dedup_values(|a, b| a == b).is_empty());
+}
+
+#[test]
+fn test_take_matches_remove() {
+    let (mut map, keys) = DeferredMap::<_>::from_values([1, 2]);
+    let epoch = map.epoch();
+
+    assert_eq!(map.take(keys[0]), Some(1));
+    assert!(map.epoch() > epoch);
+    assert_eq!(map.get(keys[0]), None);
+    assert_eq!(map.take(keys[0]), None);
+    assert_eq!(map.remove(keys[1]), Some(2));
+    assert_eq!(map.take(keys[1]), None);
+    assert!(map.is_empty());
+    assert!(map.validate().is_ok());
+
+    let mut sec = crate::SecondaryMap::new();
+    sec.insert(keys[0], "a");
+    assert_eq!(sec.take(keys[0]), Some("a"));
+    assert_eq!(sec.take(keys[0]), None);
+}
+
+#[test]
+fn test_free_list_indices_follow_removal_order() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..8);
+    assert!(map.free_list_indices().is_empty());
+
+    for i in [2, 5, 0, 7] {
+        map.remove(keys[i]);
+    }
+    let expected: Vec<u32> = [7, 0, 5, 2].iter().map(|&i| keys[i].index()).collect();
+    assert_eq!(map.free_list_indices(), expected);
+
+    // Reuse takes entries from the front of the list
+    // 复用从列表头部依次取出
+    for &index in &expected {
+        let handle = map.allocate_handle();
+        assert_eq!(handle.index(), index);
+        map.insert(handle, 0);
+    }
+    assert!(map.free_list_indices().is_empty());
+
+    // Released handles also go to the front
+    // 被释放的 handle 同样放到头部
+    let handle = map.allocate_handle();
+    let index = handle.index();
+    map.release_handle(handle);
+    assert_eq!(map.free_list_indices(), vec![index]);
+}
+
+#[test]
+fn test_fifo_free_list_reuse_order() {
+    let order = [2, 5, 0, 7];
+    let reuse = |mut map: DeferredMap<i32>| {
+        let keys = map.seed(0..8);
+        for i in order {
+            map.remove(keys[i]);
+        }
+        map.validate().unwrap();
+        let reused: Vec<_> = (0..order.len())
+            .map(|i| {
+                let handle = map.allocate_handle();
+                let index = handle.index();
+                map.insert(handle, i as i32);
+                keys.iter().position(|k| k.index() == index).unwrap()
+            })
+            .collect();
+        map.validate().unwrap();
+        reused
+    };
+
+    assert_eq!(reuse(DeferredMap::new()), [7, 0, 5, 2]);
+    assert_eq!(reuse(DeferredMap::with_fifo_free_list()), order);
+}
+
+#[test]
+fn test_fifo_free_list_survives_structural_ops() {
+    let mut map = DeferredMap::<i32>::with_fifo_free_list();
+    let keys = map.seed(0..10);
+    map.remove(keys[3]);
+    map.remove(keys[9]);
+    map.remove(keys[1]);
+    let handle = map.allocate_handle();
+    assert_eq!(handle.index(), keys[3].index());
+    map.release_handle(handle);
+    assert_eq!(
+        map.free_list_indices(),
+        [9, 1, 3].map(|i| keys[i].index()).to_vec()
+    );
+
+    // Trimming drops the trailing slot and keeps the tail consistent
+    // trim 丢弃末尾 slot 并保持尾部一致
+    assert_eq!(map.trim(), 1);
+    map.validate().unwrap();
+    let handle = map.allocate_handle();
+    let index = handle.index();
+    map.release_handle(handle);
+    assert_eq!(map.free_list_indices().last(), Some(&index));
+
+    // Seeding past the list, then cloning, keeps appending at the tail
+    // 在列表之后 seed，然后克隆，仍在尾部追加
+    let seeded = map.seed([10, 11]);
+    map.validate().unwrap();
+    map.remove(seeded[0]);
+    assert_eq!(map.free_list_indices().last(), Some(&seeded[0].index()));
+    let mut copy = map.clone();
+    assert_eq!(copy.free_list_indices(), map.free_list_indices());
+    let (key, _) = copy.iter().find(|(_, v)| **v == 11).unwrap();
+    copy.remove(key);
+    assert_eq!(copy.free_list_indices().last(), Some(&key.index()));
+    copy.validate().unwrap();
+
+    map.retain(|_, v| *v % 2 == 0);
+    map.validate().unwrap();
+    map.clear();
+    let keys = map.seed(0..3);
+    map.remove(keys[0]);
+    map.remove(keys[2]);
+    assert_eq!(
+        map.free_list_indices(),
+        vec![keys[0].index(), keys[2].index()]
+    );
+    map.validate().unwrap();
+}
+
+#[test]
+fn test_trim_does_not_revive_stale_keys() {
+    let (mut map, keys) = DeferredMap::<i32>::from_values([1, 2, 3]);
+    let mut sec = crate::SecondaryMap::new();
+    sec.insert(keys[2], "old");
+
+    // Cycle the last slot a few times before trimming it
+    // 在截断之前让最后一个 slot 循环几次
+    let mut stale = keys[2];
+    map.remove(stale);
+    for _ in 0..3 {
+        let h = map.allocate_handle();
+        stale = map.insert(h, 0);
+        map.remove(stale);
+    }
+    assert_eq!(map.trim(), 1);
+
+    let h = map.allocate_handle();
+    let fresh = map.insert(h, 99);
+    assert_eq!(fresh.index(), stale.index());
+    assert!(fresh.generation() > stale.generation());
+    assert_eq!(map.get(stale), None);
+    assert_eq!(map.get(keys[2]), None);
+    assert_eq!(map.get(fresh), Some(&99));
+
+    // The secondary map sees the new key as newer than its stored entry
+    // 辅助 map 将新 key 视为比已存储条目更新
+    assert_eq!(sec.insert(fresh, "new"), None);
+    assert_eq!(sec.get(fresh), Some(&"new"));
+    assert_eq!(sec.get(keys[2]), None);
+
+    // Seeding and clearing follow the same floor
+    // seed 和 clear 遵循同样的下限
+    let seeded = map.seed([7]);
+    assert_eq!(seeded[0].generation(), fresh.generation());
+    map.validate().unwrap();
+    map.clear();
+    let h = map.allocate_handle();
+    assert_eq!(h.generation(), 1);
+    map.insert(h, 0);
+}
@@ -1,4 +1,4 @@
-use crate::{DeferredMap, SecondaryMap};
+use crate::{ConflictPolicy, DeferredMap, DeferredMapError, SecondaryMap};
 
 #[test]
 fn test_secondary_map_basic() {
@@ -167,3 +167,565 @@ fn test_iterators() {
     assert_eq!(sec.get(k1), Some(&101));
     assert_eq!(sec.get(k2), Some(&201));
 }
+
+#[test]
+fn test_shrink_to_len() {
+    let mut sec = SecondaryMap::new();
+
+    let (_map, keys) = DeferredMap::<_>::from_values(0..10);
+    for &k in &keys {
+        sec.insert(k, k);
+    }
+
+    // Primary indices start at 1, so keys[4] has index 5
+    // 主 map 的索引从 1 开始，因此 keys[4] 的索引为 5
+    sec.shrink_to_len(5);
+    assert_eq!(sec.len(), 5);
+    assert!(sec.capacity() <= 6);
+    for &k in &keys[..5] {
+        assert_eq!(sec.get(k), Some(&k));
+    }
+    for &k in &keys[5..] {
+        assert!(!sec.contains_key(k));
+    }
+
+    // Shrinking above the current length keeps every entry
+    // 收缩到大于当前长度时保留所有条目
+    sec.shrink_to_len(100);
+    assert_eq!(sec.len(), 5);
+
+    // Entries above the cut can be inserted again
+    // 截断位置之上的条目可以重新插入
+    sec.insert(keys[9], keys[9]);
+    assert_eq!(sec.len(), 6);
+}
+
+#[test]
+fn test_from_iter_resolves_generations() {
+    use crate::Key;
+
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..4);
+
+    // Reuse the slot of keys[1] to get a newer generation at the same index
+    // 复用 keys[1] 的 slot，在相同索引上得到更新的代数
+    map.remove(keys[1]);
+    let h = map.allocate_handle();
+    let newer = h.key();
+    map.insert(h, 10);
+    assert_eq!(newer.index(), keys[1].index());
+
+    // Out of order, with the newer generation appearing before the stale one
+    // 乱序，且较新的代数出现在过期代数之前
+    let sec: SecondaryMap<&str> = vec![
+        (keys[3], "three"),
+        (newer, "newer"),
+        (keys[0], "zero"),
+        (keys[1], "stale"),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(sec.len(), 3);
+    assert_eq!(sec.get(keys[0]), Some(&"zero"));
+    assert_eq!(sec.get(keys[3]), Some(&"three"));
+    assert_eq!(sec.get(newer), Some(&"newer"));
+    assert_eq!(sec.get(keys[1]), None);
+
+    // Stale first, newer later: the newer generation still wins
+    // 先过期后较新：较新的代数仍然胜出
+    let sec: SecondaryMap<&str> = vec![(keys[1], "stale"), (newer, "newer")]
+        .into_iter()
+        .collect();
+    assert_eq!(sec.len(), 1);
+    assert_eq!(sec.get(newer), Some(&"newer"));
+}
+
+#[test]
+fn test_gc_stale_returns_dead_entries() {
+    let (mut map, keys) = DeferredMap::<_>::from_values(0..5);
+    let mut sec = SecondaryMap::new();
+    for (i, &k) in keys.iter().enumerate() {
+        sec.insert(k, format!("res{}", i));
+    }
+
+    assert!(sec.gc_stale(&map).is_empty());
+    assert_eq!(sec.len(), 5);
+
+    map.remove(keys[1]);
+    map.remove(keys[3]);
+    // A new entry reusing a freed slot does not revive the stale secondary value
+    // 复用已释放 slot 的新条目不会使过期的辅助值复活
+    let h = map.allocate_handle();
+    map.insert(h, 99);
+
+    // Entries come back in index order
+    // 条目按索引顺序返回
+    let removed = sec.gc_stale(&map);
+    assert_eq!(
+        removed,
+        vec![(keys[1], "res1".to_string()), (keys[3], "res3".to_string())]
+    );
+
+    assert_eq!(sec.len(), 3);
+    for &k in [keys[0], keys[2], keys[4]].iter() {
+        assert!(sec.contains_key(k));
+    }
+    assert!(sec.gc_stale(&map).is_empty());
+}
+
+#[test]
+fn test_for_primary_preallocates() {
+    let mut map: DeferredMap<usize> = DeferredMap::with_capacity(100);
+    let mut sec = SecondaryMap::for_primary(&map);
+    let capacity = sec.capacity();
+    assert!(capacity > map.capacity());
+
+    for i in 0..map.capacity() {
+        let h = map.allocate_handle();
+        let k = h.key();
+        map.insert(h, i);
+        sec.insert(k, i);
+    }
+    assert_eq!(sec.len(), map.len());
+    assert_eq!(sec.capacity(), capacity);
+}
+
+#[test]
+fn test_iter_live_skips_removed_primary_entries() {
+    let (mut map, keys) = DeferredMap::<i32>::from_values(0..5);
+    let sec: SecondaryMap<i32> = keys.iter().map(|&k| (k, 10)).collect();
+
+    map.remove(keys[1]);
+    map.remove(keys[4]);
+    // A newer occupant of a freed slot does not revive the stale entry
+    // 已释放 slot 的新占用者不会使过期条目复活
+    let h = map.allocate_handle();
+    map.insert(h, 99);
+
+    let live: Vec<_> = sec.iter_live(&map).map(|(k, _)| k).collect();
+    assert_eq!(live, vec![keys[0], keys[2], keys[3]]);
+
+    // The stale entries are still stored
+    // 过期条目仍然被存储
+    assert_eq!(sec.len(), 5);
+    assert_eq!(sec.iter().count(), 5);
+}
+
+#[test]
+fn test_named_iterators() {
+    use crate::secondary::{Iter, IterMut};
+
+    // Named iterator types can be stored in structs
+    // 具名迭代器类型可以存储在结构体中
+    struct Cursor<'a> {
+        iter: Iter<'a, i32>,
+    }
+
+    let (mut map, keys) = DeferredMap::<i32>::from_values(0..6);
+    let mut sec: SecondaryMap<i32> = keys.iter().map(|&k| (k, 1)).collect();
+    sec.remove(keys[2]);
+    map.remove(keys[2]);
+
+    let mut cursor = Cursor { iter: sec.iter() };
+    assert_eq!(cursor.iter.len(), 5);
+    cursor.iter.next();
+    assert_eq!(cursor.iter.len(), 4);
+    assert_eq!(cursor.iter.clone().count(), 4);
+
+    let expected: Vec<_> = map.iter().map(|(k, _)| k).collect();
+    let yielded: Vec<_> = (&sec).into_iter().map(|(k, _)| k).collect();
+    assert_eq!(yielded, expected);
+
+    let iter_mut: IterMut<'_, i32> = sec.iter_mut();
+    assert_eq!(iter_mut.len(), 5);
+    for (_, v) in iter_mut {
+        *v += 1;
+    }
+    for (_, v) in &mut sec {
+        *v *= 10;
+    }
+    assert!(sec.iter().all(|(_, v)| *v == 20));
+
+    let mut exhausted = sec.iter();
+    exhausted.by_ref().for_each(drop);
+    assert_eq!(exhausted.len(), 0);
+    assert!(exhausted.next().is_none());
+}
+
+#[test]
+fn test_try_insert_generation_orderings() {
+    use crate::DeferredMapError;
+
+    let mut map = DeferredMap::new();
+    let h = map.allocate_handle();
+    let old = h.key();
+    map.insert(h, ());
+    map.remove(old);
+    let h = map.allocate_handle();
+    let new = h.key();
+    map.insert(h, ());
+
+    // Empty slot
+    // 空 slot
+    let mut sec = SecondaryMap::new();
+    assert_eq!(sec.try_insert(old, "old"), Ok(None));
+    assert_eq!(sec.len(), 1);
+
+    // Same generation replaces
+    // 相同代数替换
+    assert_eq!(sec.try_insert(old, "old2"), Ok(Some("old")));
+
+    // Newer generation overwrites the stale entry
+    // 更新的代数覆盖过期条目
+    assert_eq!(sec.try_insert(new, "new"), Ok(None));
+    assert_eq!(sec.len(), 1);
+    assert_eq!(sec.get(new), Some(&"new"));
+
+    // Older generation is rejected and leaves the map unchanged
+    // 更旧的代数被拒绝且 map 保持不变
+    assert_eq!(
+        sec.try_insert(old, "stale"),
+        Err(DeferredMapError::GenerationMismatch)
+    );
+    assert_eq!(sec.get(new), Some(&"new"));
+    assert_eq!(sec.len(), 1);
+
+    // Plain insert still silently ignores the stale key
+    // 普通 insert 仍然静默忽略过期 key
+    assert_eq!(sec.insert(old, "stale"), None);
+    assert_eq!(sec.get(new), Some(&"new"));
+}
+
+#[test]
+fn test_ensure_index_materializes_slots() {
+    let (_map, keys) = DeferredMap::<_>::from_values(0..32);
+
+    let mut sec = SecondaryMap::new();
+    sec.ensure_index(32);
+    assert!(sec.is_empty());
+    assert!(sec.capacity() >= 33);
+    for &key in &keys {
+        assert_eq!(sec.get(key), None);
+    }
+
+    // Inserts within the ensured range never reallocate
+    // 在已确保的范围内插入不会重新分配
+    let capacity = sec.capacity();
+    for (i, &key) in keys.iter().enumerate() {
+        sec.insert(key, i);
+    }
+    assert_eq!(sec.capacity(), capacity);
+    assert_eq!(sec.len(), 32);
+
+    // Ensuring a smaller index is a no-op
+    // 确保更小的索引不做任何事
+    sec.ensure_index(3);
+    assert_eq!(sec.capacity(), capacity);
+    assert_eq!(sec.len(), 32);
+}
+
+#[test]
+fn test_merge_from_generation_rules() {
+    let mut map = DeferredMap::new();
+    let mut keys = Vec::new();
+    for i in 0..3 {
+        let handle = map.allocate_handle();
+        keys.push(handle.key());
+        map.insert(handle, i);
+    }
+    // Produce an older and a newer key for the same slot
+    // 为同一个 slot 生成旧 key 和新 key
+    let old = keys[2];
+    map.remove(old);
+    let handle = map.allocate_handle();
+    let new = handle.key();
+    map.insert(handle, 9);
+
+    let mut a = SecondaryMap::new();
+    a.insert(keys[0], "a0");
+    a.insert(old, "a-old");
+
+    let mut b = SecondaryMap::new();
+    b.insert(keys[0], "b0"); // exact-generation conflict | 相同代数冲突
+    b.insert(keys[1], "b1"); // absent in `a` | `a` 中不存在
+    b.insert(new, "b-new"); // newer than `a`'s entry | 比 `a` 的条目更新
+
+    let mut conflicts = Vec::new();
+    a.merge_from(b, |key, mine, theirs| {
+        conflicts.push((key, *mine, theirs));
+        *mine = theirs;
+    });
+
+    assert_eq!(conflicts, [(keys[0], "a0", "b0")]);
+    assert_eq!(a.len(), 3);
+    assert_eq!(a.get(keys[0]), Some(&"b0"));
+    assert_eq!(a.get(keys[1]), Some(&"b1"));
+    assert_eq!(a.get(new), Some(&"b-new"));
+    assert_eq!(a.get(old), None);
+
+    // Older incoming entries are dropped
+    // 更旧的传入条目被 drop
+    let mut stale = SecondaryMap::new();
+    stale.insert(old, "stale");
+    a.merge_from(stale, |_, _, _| panic!("no conflict expected"));
+    assert_eq!(a.get(new), Some(&"b-new"));
+    assert_eq!(a.len(), 3);
+}
+
+#[test]
+fn test_values_mut_keys_and_disjoint_unchecked() {
+    let (_map, keys) = DeferredMap::<_>::from_values(0..5);
+
+    let mut sec = SecondaryMap::new();
+    for (i, &key) in keys.iter().enumerate() {
+        if i != 2 {
+            sec.insert(key, i * 10);
+        }
+    }
+
+    for value in sec.values_mut() {
+        *value += 1;
+    }
+    for (i, &key) in keys.iter().enumerate() {
+        let expected = (i != 2).then_some(i * 10 + 1);
+        assert_eq!(sec.get(key).copied(), expected);
+    }
+
+    let collected: Vec<_> = sec.keys().collect();
+    assert_eq!(collected, [keys[0], keys[1], keys[3], keys[4]]);
+
+    // SAFETY: keys 0, 3 and 4 are present and distinct
+    let [a, b, c] = unsafe { sec.get_disjoint_unchecked_mut([keys[4], keys[0], keys[3]]) };
+    std::mem::swap(a, b);
+    *c = 0;
+    assert_eq!(sec.get(keys[0]), Some(&41));
+    assert_eq!(sec.get(keys[4]), Some(&1));
+    assert_eq!(sec.get(keys[3]), Some(&0));
+}
+
+#[test]
+fn test_map_values_keeps_generations() {
+    use crate::Key;
+
+    let mut map = DeferredMap::new();
+    let old = {
+        let h = map.allocate_handle();
+        let k = h.key();
+        map.insert(h, ());
+        k
+    };
+    map.remove(old);
+    let keys: Vec<_> = (0..3)
+        .map(|_| {
+            let h = map.allocate_handle();
+            let k = h.key();
+            map.insert(h, ());
+            k
+        })
+        .collect();
+
+    let mut sec = SecondaryMap::new();
+    for (i, &k) in keys.iter().enumerate() {
+        sec.insert(k, i as u32);
+    }
+    let before: Vec<_> = sec.iter_with_index().map(|(i, g, _)| (i, g)).collect();
+
+    let doubled = sec.map_values(|v| u64::from(v) * 2);
+    let after: Vec<_> = doubled.iter_with_index().map(|(i, g, _)| (i, g)).collect();
+    assert_eq!(after, before);
+    assert_eq!(doubled.len(), 3);
+    for (i, &k) in keys.iter().enumerate() {
+        assert_eq!(doubled.get(k), Some(&(i as u64 * 2)));
+    }
+    // The reused slot still rejects the stale key
+    // 被复用的 slot 仍然拒绝过期 key
+    assert_eq!(keys[0].index(), old.index());
+    assert_eq!(doubled.get(old), None);
+}
+
+#[test]
+fn test_reserve_like_avoids_reallocation() {
+    let mut map: DeferredMap<u32> = DeferredMap::new();
+    let mut sec: SecondaryMap<u32> = SecondaryMap::new();
+
+    // Drive the primary from the secondary, then the secondary from the primary
+    // 先由辅助 map 驱动主 map，再由主 map 驱动辅助 map
+    sec.reserve_exact(100);
+    map.reserve_like(&sec);
+    sec.reserve_like(&map);
+
+    let map_ptr = map.slots.as_ptr();
+    let sec_capacity = sec.capacity();
+    let map_capacity = map.capacity();
+
+    for i in 0..99 {
+        let h = map.allocate_handle();
+        let k = h.key();
+        map.insert(h, i);
+        sec.insert(k, i * 2);
+    }
+
+    assert_eq!(map.capacity(), map_capacity);
+    assert_eq!(map.slots.as_ptr(), map_ptr);
+    assert_eq!(sec.capacity(), sec_capacity);
+    assert_eq!(sec.len(), 99);
+}
+
+#[test]
+fn test_conflict_policies() {
+    use crate::Key;
+
+    // Three generations of the same slot
+    // 同一 slot 的三个代数
+    let mut map: DeferredMap<()> = DeferredMap::new();
+    let mut gens = Vec::new();
+    for _ in 0..3 {
+        let h = map.allocate_handle();
+        let k = map.insert(h, ());
+        map.remove(k);
+        gens.push(k);
+    }
+    let (older, stored, newer) = (gens[0], gens[1], gens[2]);
+    assert_eq!(older.index(), newer.index());
+
+    // (policy, incoming key, value stored afterwards, try_insert accepted)
+    // （策略，传入的 key，之后存储的值，try_insert 是否接受）
+    let cases = [
+        (ConflictPolicy::OverwriteStale, older, (stored, 1), false),
+        (ConflictPolicy::OverwriteStale, stored, (stored, 2), true),
+        (ConflictPolicy::OverwriteStale, newer, (newer, 2), true),
+        (ConflictPolicy::Strict, older, (stored, 1), false),
+        (ConflictPolicy::Strict, stored, (stored, 2), true),
+        (ConflictPolicy::Strict, newer, (stored, 1), false),
+        (ConflictPolicy::AlwaysOverwrite, older, (older, 2), true),
+        (ConflictPolicy::AlwaysOverwrite, stored, (stored, 2), true),
+        (ConflictPolicy::AlwaysOverwrite, newer, (newer, 2), true),
+    ];
+
+    for (policy, incoming, (winner, value), accepted) in cases {
+        let replaced = if incoming == stored { Some(1) } else { None };
+
+        let mut sec = SecondaryMap::with_policy(policy);
+        assert_eq!(sec.policy(), policy);
+        sec.insert(stored, 1);
+        assert_eq!(sec.insert(incoming, 2), replaced, "{policy:?}");
+        assert_eq!(sec.get(winner), Some(&value), "{policy:?}");
+        assert_eq!(sec.len(), 1);
+
+        let mut sec = SecondaryMap::with_policy(policy);
+        sec.insert(stored, 1);
+        let expected = if accepted {
+            Ok(replaced)
+        } else {
+            Err(DeferredMapError::GenerationMismatch)
+        };
+        assert_eq!(sec.try_insert(incoming, 2), expected, "{policy:?}");
+        assert_eq!(sec.get(winner), Some(&value), "{policy:?}");
+    }
+
+    assert_eq!(
+        SecondaryMap::<i32>::new().policy(),
+        ConflictPolicy::default()
+    );
+    assert_eq!(ConflictPolicy::default(), ConflictPolicy::OverwriteStale);
+}
+
+#[test]
+fn test_entry_or_default_accumulates() {
+    let (mut map, keys) = DeferredMap::<()>::from_values([(); 4]);
+
+    // Histogram over a stream of keys
+    // 对 key 流统计直方图
+    let mut hits: SecondaryMap<u32> = SecondaryMap::new();
+    for i in 0..20 {
+        *hits.entry_or_default(keys[i % 3]) += 1;
+    }
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits.get(keys[0]), Some(&7));
+    assert_eq!(hits.get(keys[1]), Some(&7));
+    assert_eq!(hits.get(keys[2]), Some(&6));
+    assert_eq!(hits.get(keys[3]), None);
+
+    // A newer generation of the slot starts over from the default
+    // 该 slot 的新代数从默认值重新开始
+    map.remove(keys[0]);
+    let h = map.allocate_handle();
+    let reused = map.insert(h, ());
+    *hits.entry_or_default(reused) += 5;
+    assert_eq!(hits.get(reused), Some(&5));
+    assert_eq!(hits.get(keys[0]), None);
+    assert_eq!(hits.len(), 3);
+
+    let mut calls = 0;
+    hits.get_or_insert_with(reused, || {
+        calls += 1;
+        0
+    });
+    assert_eq!(calls, 0);
+
+    // The stale key is refused by the default policy, but taken by AlwaysOverwrite
+    // 默认策略拒绝过期 key，但 AlwaysOverwrite 会接受它
+    let stale = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        *hits.entry_or_default(keys[0]) += 1;
+    }));
+    assert!(stale.is_err());
+    assert_eq!(hits.get(reused), Some(&5));
+
+    let mut lenient = SecondaryMap::with_policy(ConflictPolicy::AlwaysOverwrite);
+    *lenient.entry_or_default(reused) += 1u32;
+    *lenient.entry_or_default(keys[0]) += 1;
+    assert_eq!(lenient.get(keys[0]), Some(&1));
+    assert_eq!(lenient.get(reused), None);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_secondary_policy_serde_round_trip() {
+    let (_map, keys) = DeferredMap::from_values([1, 2]);
+    let mut sec = SecondaryMap::with_policy(ConflictPolicy::Strict);
+    sec.insert(keys[1], 20);
+
+    let json = serde_json::to_string(&sec).unwrap();
+    let restored: SecondaryMap<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.policy(), ConflictPolicy::Strict);
+    assert_eq!(restored.get(keys[1]), Some(&20));
+
+    // Data without a policy field falls back to the default
+    // 没有 policy 字段的数据回退到默认值
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value.as_object_mut().unwrap().remove("policy");
+    let legacy: SecondaryMap<i32> = serde_json::from_value(value).unwrap();
+    assert_eq!(legacy.policy(), ConflictPolicy::OverwriteStale);
+    assert_eq!(legacy.get(keys[1]), Some(&20));
+}
+
+#[test]
+fn test_unbound_secondary_yields_keys_usable_with_primary() {
+    use crate::DefaultKey;
+
+    // Ensure the primary map's id is not 0, even when this test runs alone
+    // 确保主 map 的 id 不为 0，即使单独运行此测试
+    let _first = DeferredMap::<i32>::new();
+    let (mut map, keys) = DeferredMap::<_>::from_values([1, 2, 3]);
+
+    // Keys rebuilt from raw values are not bound to any map, so neither is `sec`
+    // 从原始值重建的 key 不绑定任何 map，因此 `sec` 也不绑定
+    let mut sec = SecondaryMap::new();
+    for &key in &keys {
+        let rebuilt = DefaultKey::try_from(u64::from(key)).unwrap();
+        sec.insert(rebuilt, u64::from(key));
+    }
+
+    for (key, _) in sec.iter() {
+        assert!(map.get(key).is_some());
+    }
+    for (key, _) in sec.iter_mut() {
+        assert!(map.get_mut(key).is_some());
+    }
+    sec.retain_mut(|key, _| map.get(key) != Some(&2));
+    assert_eq!(sec.len(), 2);
+    for (key, _) in sec.iter() {
+        assert!(map.remove(key).is_some());
+    }
+    assert_eq!(map.len(), 1);
+}
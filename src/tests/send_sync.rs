@@ -0,0 +1,52 @@
+// Compile-time checks that the public types are Send/Sync whenever their contents are
+// 编译期检查：只要内容满足，公共类型就是 Send/Sync
+
+use crate::{DefaultKey, DeferredMap, Handle, SecondaryMap};
+use std::cell::Cell;
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn test_keys_and_handles_are_send_sync() {
+    assert_send::<DefaultKey>();
+    assert_sync::<DefaultKey>();
+    assert_send::<Handle>();
+    assert_sync::<Handle>();
+}
+
+#[test]
+fn test_maps_follow_value_type() {
+    assert_send::<DeferredMap<String>>();
+    assert_sync::<DeferredMap<String>>();
+    assert_send::<SecondaryMap<String>>();
+    assert_sync::<SecondaryMap<String>>();
+
+    // Send but not Sync values keep the maps Send
+    // 值为 Send 但非 Sync 时，map 仍然是 Send
+    assert_send::<DeferredMap<Cell<i32>>>();
+    assert_send::<SecondaryMap<Cell<i32>>>();
+}
+
+#[test]
+fn test_map_moves_across_threads() {
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+
+    // Handle and map both cross the thread boundary
+    // Handle 和 map 都可以跨越线程边界
+    let map = std::thread::spawn(move || {
+        map.insert(handle, String::from("sent"));
+        map
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(map.get(key).map(String::as_str), Some("sent"));
+}
+
+// `Rc` is neither Send nor Sync, so neither are maps of it:
+// uncommenting the following line fails to compile.
+// `Rc` 既非 Send 也非 Sync，map 也必须如此。取消注释下一行会导致编译失败。
+// fn _not_send() { assert_send::<DeferredMap<std::rc::Rc<i32>>>(); }
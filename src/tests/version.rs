@@ -0,0 +1,239 @@
+// Version state transitions and generation wraparound tests
+// Version 状态转换和代数回绕的测试
+
+use crate::{Generation, Version, VersionState};
+
+fn generation(value: u32) -> Generation {
+    assert!(value != 0 && value <= Generation::MAX.get());
+    unsafe { Generation::new_unchecked(value) }
+}
+
+#[test]
+fn test_generation_max_fits_in_version() {
+    assert_eq!(Generation::MAX.get(), (1 << 30) - 1);
+    assert!(Generation::MIN < Generation::MAX);
+
+    for state in [0b00, 0b01, 0b11] {
+        let version = Version::new(Generation::MAX, state);
+        assert_eq!(version.generation(), Generation::MAX);
+        assert_eq!(version.state(), state);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Generation exceeds 30 bits")]
+fn test_generation_above_max_is_rejected() {
+    // SAFETY: the value is non-zero; it only exceeds the 30-bit range
+    let too_large = unsafe { Generation::new_unchecked(Generation::MAX.get() + 1) };
+    let _ = Version::with_state(too_large, VersionState::Vacant);
+}
+
+#[test]
+fn test_full_cycle_below_max() {
+    let mut version = Version::new(generation(Generation::MAX.get() - 2), 0b00);
+
+    for expected in [Generation::MAX.get() - 1, Generation::MAX.get()] {
+        version.vacant_to_reserved();
+        assert!(version.is_reserved());
+        version.reserved_to_occupied();
+        assert!(version.is_occupied());
+        version.occupied_to_vacant();
+        assert!(version.is_vacant());
+        assert_eq!(version.generation().get(), expected);
+    }
+}
+
+#[test]
+fn test_occupied_to_vacant_wraps_to_min() {
+    let mut version = Version::new(Generation::MAX, 0b00);
+    version.vacant_to_reserved();
+    version.reserved_to_occupied();
+    assert_eq!(version.generation(), Generation::MAX);
+    assert!(version.is_occupied());
+
+    version.occupied_to_vacant();
+    assert!(version.is_vacant());
+    assert_eq!(version.generation(), Generation::MIN);
+
+    // The wrapped slot keeps cycling normally
+    // 回绕后的 slot 继续正常循环
+    version.vacant_to_reserved();
+    assert!(version.is_reserved());
+    assert_eq!(version.generation(), Generation::MIN);
+    version.reserved_to_occupied();
+    version.occupied_to_vacant();
+    assert_eq!(version.generation().get(), 2);
+}
+
+#[test]
+fn test_reserved_to_vacant_wraps_to_min() {
+    let mut version = Version::new(Generation::MAX, 0b01);
+    version.reserved_to_vacant();
+    assert!(version.is_vacant());
+    assert_eq!(version.generation(), Generation::MIN);
+}
+
+#[test]
+fn test_generation_never_zero_across_wrap() {
+    let mut version = Version::new(generation(Generation::MAX.get() - 3), 0b00);
+
+    // Alternate release and remove paths across the wrap boundary
+    // 跨越回绕边界时交替使用释放和删除路径
+    for step in 0..8 {
+        version.vacant_to_reserved();
+        if step % 2 == 0 {
+            version.reserved_to_vacant();
+        } else {
+            version.reserved_to_occupied();
+            version.occupied_to_vacant();
+        }
+        assert!(version.is_vacant());
+        assert!(version.generation().get() != 0);
+        assert!(version.generation() <= Generation::MAX);
+    }
+    assert_eq!(version.generation().get(), 5);
+}
+
+#[test]
+fn test_version_state_round_trip() {
+    for state in [
+        VersionState::Vacant,
+        VersionState::Reserved,
+        VersionState::Occupied,
+    ] {
+        for g in [Generation::MIN, generation(12345), Generation::MAX] {
+            let version = Version::with_state(g, state);
+            assert_eq!(version.to_state(), state);
+            assert_eq!(version.try_to_state(), Some(state));
+            assert_eq!(version.generation(), g);
+            assert_eq!(version, Version::new(g, version.state()));
+        }
+    }
+}
+
+#[test]
+fn test_invalid_state_bits_are_reported() {
+    let corrupt = Version::new(generation(5), 0b10);
+    assert_eq!(corrupt.try_to_state(), None);
+    assert_eq!(corrupt.generation(), generation(5));
+
+    let err = std::panic::catch_unwind(|| corrupt.to_state()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<String>().map(String::as_str),
+        Some("invalid version state bits 0b10")
+    );
+}
+
+#[test]
+fn test_version_state_follows_transitions() {
+    let mut version = Version::with_state(Generation::MIN, VersionState::Vacant);
+    assert!(version.is_vacant());
+
+    version.vacant_to_reserved();
+    assert_eq!(version.to_state(), VersionState::Reserved);
+
+    version.reserved_to_occupied();
+    assert_eq!(version.to_state(), VersionState::Occupied);
+
+    version.occupied_to_vacant();
+    assert_eq!(version.to_state(), VersionState::Vacant);
+    assert_eq!(version.generation(), generation(2));
+}
+
+#[test]
+fn test_generation_numeric_relationship() {
+    use crate::{DeferredMap, Key};
+
+    let mut map = DeferredMap::new();
+    let handle = map.allocate_handle();
+    let key = handle.key();
+    let index = key.index() as usize;
+
+    // Handle, key and slot agree on the logical value
+    // handle、key 和 slot 的逻辑值一致
+    assert_eq!(handle.generation(), Generation::MIN);
+    assert_eq!(handle.generation().as_u32(), 1);
+    assert_eq!(map.versions().nth(index).unwrap() >> 2, 1);
+    assert_eq!(key.raw() >> 32, 1);
+    map.insert(handle, 0);
+    map.remove(key);
+
+    // Reuse bumps the logical value by exactly one everywhere
+    // 复用时各处的逻辑值都恰好加一
+    let handle = map.allocate_handle();
+    let reused = handle.key();
+    assert_eq!(reused.index(), key.index());
+    assert_eq!(reused.generation().as_u32(), 2);
+    assert_eq!(map.versions().nth(index).unwrap() >> 2, 2);
+    assert_eq!(reused.raw() >> 32, 2);
+    assert_eq!(map.versions().nth(index).unwrap() & 0b11, 0b01);
+    map.insert(handle, 1);
+
+    assert_eq!(format!("{}", reused.generation()), "2");
+    assert_eq!(format!("{:?}", reused.generation()), "Generation(2)");
+    assert_eq!(format!("{:?}", Generation::MAX), "Generation(1073741823)");
+}
+
+#[test]
+fn test_generation_u32_ordering_and_add() {
+    assert!(Generation::MIN < 2);
+    assert!(Generation::MIN <= 1);
+    assert!(Generation::MAX > 1);
+    assert!(0 < Generation::MIN);
+    assert!(Generation::MAX >= Generation::MAX.as_u32());
+
+    assert_eq!(Generation::MIN + 0, Generation::MIN);
+    assert_eq!(Generation::MIN + 1, 2);
+    assert_eq!(generation(41) + 1, 42);
+
+    // Wrap boundary: MAX + 1 skips zero and lands on MIN
+    // 回绕边界：MAX + 1 跳过零并落在 MIN
+    assert_eq!(Generation::MAX + 1, Generation::MIN);
+    assert_eq!(Generation::MAX + 2, 2);
+    assert_eq!(generation(Generation::MAX.get() - 1) + 1, Generation::MAX);
+    assert_eq!(Generation::MIN + Generation::MAX.get(), Generation::MIN);
+    assert_eq!(
+        Generation::MAX + u32::MAX,
+        (Generation::MAX + (u32::MAX % Generation::MAX.get()))
+    );
+    assert!((Generation::MAX + u32::MAX) <= Generation::MAX.get());
+
+    // `+ 1` matches what freeing a slot does
+    // `+ 1` 与释放 slot 的效果一致
+    for start in [Generation::MIN, generation(7), Generation::MAX] {
+        let mut version = Version::with_state(start, VersionState::Occupied);
+        version.occupied_to_vacant();
+        assert_eq!(version.generation(), start + 1);
+    }
+}
+
+#[test]
+fn test_generation_headroom_after_recycling() {
+    use crate::{DeferredMap, Key};
+
+    let mut map = DeferredMap::<u32>::new();
+    let cold = {
+        let h = map.allocate_handle();
+        map.insert(h, 0)
+    };
+
+    // Recycle one hot slot many times
+    // 多次回收同一个热点 slot
+    let mut index = 0;
+    for i in 0..1000 {
+        let h = map.allocate_handle();
+        index = h.index();
+        let key = map.insert(h, i);
+        map.remove(key);
+    }
+    let max = Generation::MAX.get();
+    assert_eq!(map.generation_headroom(index), Some(max - 1001));
+    assert_eq!(map.generation_headroom(cold.index()), Some(max - 1));
+
+    // A slot one recycle away from wrapping has no headroom, then gets it all back
+    // 距离回绕只差一次回收的 slot 没有余量，回绕后余量全部恢复
+    let last = unsafe { map.insert_at(10, generation(max), 0) };
+    assert_eq!(map.generation_headroom(10), Some(0));
+    map.remove(last);
+    assert_eq!(map.generation_headroom(10), Some(max - 1));
+}
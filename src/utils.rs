@@ -62,3 +62,43 @@ pub(crate) fn unlikely(b: bool) -> bool {
     }
     b
 }
+
+/// Map id carried by keys and handles rebuilt from raw values
+///
+/// Such keys are not tied to any map instance, so debug map-id checks accept them.
+///
+/// 从原始值重建的 key 和 handle 所携带的 map id
+///
+/// 这类 key 不绑定任何 map 实例，因此 debug 下的 map id 检查会接受它们。
+#[cfg(debug_assertions)]
+pub(crate) const UNBOUND_MAP_ID: u64 = u64::MAX;
+
+/// Debug check: whether a key's map id is acceptable for a map with `expected` id
+///
+/// 调试检查：key 的 map id 是否可用于 id 为 `expected` 的 map
+#[cfg(debug_assertions)]
+#[inline(always)]
+pub(crate) fn map_id_matches(expected: u64, actual: u64) -> bool {
+    expected == actual || actual == UNBOUND_MAP_ID
+}
+
+/// Software prefetch hint: bring the cache line at `ptr` closer for reading
+///
+/// Uses `_mm_prefetch` on x86_64 and compiles to nothing elsewhere. Prefetching
+/// never faults, so `ptr` does not need to be dereferenceable.
+///
+/// 软件预取提示：将 `ptr` 所在的缓存行提前加载以供读取
+///
+/// 在 x86_64 上使用 `_mm_prefetch`，其他平台上为空操作。预取不会引发错误，
+/// 因此 `ptr` 不需要可解引用。
+#[inline(always)]
+pub(crate) fn prefetch_read<T>(ptr: *const T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: SSE is part of the x86_64 baseline and prefetch never dereferences `ptr`
+    unsafe {
+        use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        _mm_prefetch::<_MM_HINT_T0>(ptr as *const i8);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = ptr;
+}